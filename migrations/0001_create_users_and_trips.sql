@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS users (
+  uuid TEXT PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  user_name TEXT NOT NULL,
+  role TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS trips (
+  uuid TEXT PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  start_coords TEXT NOT NULL,
+  end_coords TEXT NOT NULL,
+  driver_uuid TEXT REFERENCES users (uuid),
+  consumer_uuid TEXT NOT NULL REFERENCES users (uuid)
+);
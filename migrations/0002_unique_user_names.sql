@@ -0,0 +1,3 @@
+-- User names are unique regardless of case, "JohnDoe" and "johndoe" clash.
+CREATE UNIQUE INDEX IF NOT EXISTS users_user_name_unique_idx
+  ON users (LOWER(user_name));
@@ -0,0 +1,26 @@
+CREATE TABLE IF NOT EXISTS shifts (
+  uuid TEXT PRIMARY KEY,
+  driver_uuid TEXT NOT NULL REFERENCES users (uuid),
+  started_at TIMESTAMPTZ NOT NULL,
+  ended_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS shifts_driver_uuid_started_at_idx
+  ON shifts (driver_uuid, started_at);
+
+CREATE TABLE IF NOT EXISTS shift_breaks (
+  shift_uuid TEXT NOT NULL REFERENCES shifts (uuid),
+  started_at TIMESTAMPTZ NOT NULL,
+  ended_at TIMESTAMPTZ,
+  PRIMARY KEY (shift_uuid, started_at)
+);
+
+CREATE TABLE IF NOT EXISTS driver_availabilities (
+  driver_uuid TEXT PRIMARY KEY REFERENCES users (uuid),
+  status TEXT NOT NULL,
+  last_heartbeat_at TIMESTAMPTZ NOT NULL,
+  shift_uuid TEXT REFERENCES shifts (uuid)
+);
+
+CREATE INDEX IF NOT EXISTS driver_availabilities_status_idx
+  ON driver_availabilities (status);
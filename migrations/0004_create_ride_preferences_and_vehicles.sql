@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS customer_preferences (
+  customer_uuid TEXT PRIMARY KEY REFERENCES users (uuid),
+  wheelchair_accessible BOOLEAN NOT NULL DEFAULT FALSE,
+  assistance_dog BOOLEAN NOT NULL DEFAULT FALSE,
+  child_seat BOOLEAN NOT NULL DEFAULT FALSE,
+  preferred_language TEXT
+);
+
+CREATE TABLE IF NOT EXISTS vehicles (
+  driver_uuid TEXT PRIMARY KEY REFERENCES users (uuid),
+  plate TEXT NOT NULL,
+  wheelchair_accessible BOOLEAN NOT NULL DEFAULT FALSE,
+  assistance_dog BOOLEAN NOT NULL DEFAULT TRUE,
+  child_seat BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+ALTER TABLE trips
+  ADD COLUMN IF NOT EXISTS wheelchair_accessible BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS assistance_dog BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS child_seat BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS preferred_language TEXT;
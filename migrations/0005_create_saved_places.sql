@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS saved_places (
+  uuid TEXT PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  customer_uuid TEXT NOT NULL REFERENCES users (uuid),
+  name TEXT NOT NULL,
+  coords TEXT NOT NULL
+);
+
+-- A customer cannot have two places called "Home" and "home"
+CREATE UNIQUE INDEX IF NOT EXISTS saved_places_customer_name_unique_idx
+  ON saved_places (customer_uuid, LOWER(name));
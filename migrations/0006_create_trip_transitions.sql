@@ -0,0 +1,15 @@
+ALTER TABLE trips
+  ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT '"requested"';
+
+CREATE INDEX IF NOT EXISTS trips_status_idx ON trips (status);
+
+CREATE TABLE IF NOT EXISTS trip_transitions (
+  trip_uuid TEXT NOT NULL REFERENCES trips (uuid),
+  from_status TEXT NOT NULL,
+  to_status TEXT NOT NULL,
+  at TIMESTAMPTZ NOT NULL,
+  actor_uuid TEXT REFERENCES users (uuid)
+);
+
+CREATE INDEX IF NOT EXISTS trip_transitions_trip_uuid_at_idx
+  ON trip_transitions (trip_uuid, at);
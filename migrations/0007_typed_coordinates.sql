@@ -0,0 +1,41 @@
+-- Coordinates used to be free-form "lat,lng" text. Rows that do not parse
+-- make the cast fail and abort the migration, so they get fixed by hand
+-- instead of being silently dropped.
+ALTER TABLE trips
+  ADD COLUMN start_lat DOUBLE PRECISION,
+  ADD COLUMN start_lng DOUBLE PRECISION,
+  ADD COLUMN end_lat DOUBLE PRECISION,
+  ADD COLUMN end_lng DOUBLE PRECISION;
+
+UPDATE trips SET
+  start_lat = TRIM(SPLIT_PART(start_coords, ',', 1))::DOUBLE PRECISION,
+  start_lng = TRIM(SPLIT_PART(start_coords, ',', 2))::DOUBLE PRECISION,
+  end_lat = TRIM(SPLIT_PART(end_coords, ',', 1))::DOUBLE PRECISION,
+  end_lng = TRIM(SPLIT_PART(end_coords, ',', 2))::DOUBLE PRECISION;
+
+ALTER TABLE trips
+  ALTER COLUMN start_lat SET NOT NULL,
+  ALTER COLUMN start_lng SET NOT NULL,
+  ALTER COLUMN end_lat SET NOT NULL,
+  ALTER COLUMN end_lng SET NOT NULL,
+  ADD CONSTRAINT trips_start_coords_range_check
+    CHECK (start_lat BETWEEN -90 AND 90 AND start_lng BETWEEN -180 AND 180),
+  ADD CONSTRAINT trips_end_coords_range_check
+    CHECK (end_lat BETWEEN -90 AND 90 AND end_lng BETWEEN -180 AND 180),
+  DROP COLUMN start_coords,
+  DROP COLUMN end_coords;
+
+ALTER TABLE saved_places
+  ADD COLUMN lat DOUBLE PRECISION,
+  ADD COLUMN lng DOUBLE PRECISION;
+
+UPDATE saved_places SET
+  lat = TRIM(SPLIT_PART(coords, ',', 1))::DOUBLE PRECISION,
+  lng = TRIM(SPLIT_PART(coords, ',', 2))::DOUBLE PRECISION;
+
+ALTER TABLE saved_places
+  ALTER COLUMN lat SET NOT NULL,
+  ALTER COLUMN lng SET NOT NULL,
+  ADD CONSTRAINT saved_places_coords_range_check
+    CHECK (lat BETWEEN -90 AND 90 AND lng BETWEEN -180 AND 180),
+  DROP COLUMN coords;
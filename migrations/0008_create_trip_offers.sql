@@ -0,0 +1,16 @@
+ALTER TABLE driver_availabilities
+  ADD COLUMN IF NOT EXISTS lat DOUBLE PRECISION,
+  ADD COLUMN IF NOT EXISTS lng DOUBLE PRECISION;
+
+CREATE TABLE IF NOT EXISTS trip_offers (
+  trip_uuid TEXT NOT NULL REFERENCES trips (uuid),
+  driver_uuid TEXT NOT NULL REFERENCES users (uuid),
+  offered_at TIMESTAMPTZ NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+  outcome TEXT NOT NULL,
+  responded_at TIMESTAMPTZ,
+  PRIMARY KEY (trip_uuid, driver_uuid, offered_at)
+);
+
+CREATE INDEX IF NOT EXISTS trips_driver_uuid_status_idx
+  ON trips (driver_uuid, status);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS driver_locations (
+  driver_uuid TEXT NOT NULL REFERENCES users (uuid),
+  recorded_at TIMESTAMPTZ NOT NULL,
+  received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  lat DOUBLE PRECISION NOT NULL CHECK (lat BETWEEN -90 AND 90),
+  lng DOUBLE PRECISION NOT NULL CHECK (lng BETWEEN -180 AND 180),
+  accuracy_m DOUBLE PRECISION NOT NULL,
+  heading_deg DOUBLE PRECISION,
+  speed_mps DOUBLE PRECISION,
+  -- Apps resend buffered fixes until they get an answer
+  PRIMARY KEY (driver_uuid, recorded_at)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS trip_fares (
+  trip_uuid TEXT PRIMARY KEY REFERENCES trips (uuid),
+  tariff_version TEXT NOT NULL,
+  rate TEXT NOT NULL,
+  total_cents BIGINT NOT NULL CHECK (total_cents >= 0)
+);
+
+CREATE TABLE IF NOT EXISTS trip_fare_items (
+  trip_uuid TEXT NOT NULL REFERENCES trip_fares (trip_uuid),
+  position INTEGER NOT NULL,
+  code TEXT NOT NULL,
+  amount_cents BIGINT NOT NULL,
+  PRIMARY KEY (trip_uuid, position)
+);
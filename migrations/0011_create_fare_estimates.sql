@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS fare_estimates (
+  uuid TEXT PRIMARY KEY,
+  consumer_uuid TEXT NOT NULL REFERENCES users (uuid),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  start_lat DOUBLE PRECISION NOT NULL,
+  start_lng DOUBLE PRECISION NOT NULL,
+  end_lat DOUBLE PRECISION NOT NULL,
+  end_lng DOUBLE PRECISION NOT NULL,
+  pickup_at TIMESTAMPTZ NOT NULL,
+  distance_m BIGINT NOT NULL,
+  duration_secs BIGINT NOT NULL,
+  tariff_version TEXT NOT NULL,
+  rate TEXT NOT NULL,
+  min_cents BIGINT NOT NULL,
+  max_cents BIGINT NOT NULL CHECK (max_cents >= min_cents)
+);
+
+ALTER TABLE trips
+  ADD COLUMN IF NOT EXISTS fare_estimate_uuid TEXT
+    REFERENCES fare_estimates (uuid);
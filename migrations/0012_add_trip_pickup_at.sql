@@ -0,0 +1,6 @@
+ALTER TABLE trips
+  ADD COLUMN IF NOT EXISTS pickup_at TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS reminded_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS trips_status_pickup_at_idx
+  ON trips (status, pickup_at);
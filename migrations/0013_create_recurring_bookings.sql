@@ -0,0 +1,42 @@
+CREATE TABLE IF NOT EXISTS recurring_bookings (
+  uuid TEXT PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  consumer_uuid TEXT NOT NULL REFERENCES users (uuid),
+  start_lat DOUBLE PRECISION NOT NULL,
+  start_lng DOUBLE PRECISION NOT NULL,
+  end_lat DOUBLE PRECISION NOT NULL,
+  end_lng DOUBLE PRECISION NOT NULL,
+  wheelchair_accessible BOOLEAN NOT NULL DEFAULT FALSE,
+  assistance_dog BOOLEAN NOT NULL DEFAULT FALSE,
+  child_seat BOOLEAN NOT NULL DEFAULT FALSE,
+  preferred_language TEXT,
+  weekdays TEXT NOT NULL,
+  pickup_time TIME NOT NULL,
+  starts_on DATE NOT NULL,
+  until DATE CHECK (until >= starts_on),
+  exception_dates DATE[] NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS recurring_bookings_until_idx
+  ON recurring_bookings (until);
+
+-- Single dates moved to another time or place
+CREATE TABLE IF NOT EXISTS recurring_booking_changes (
+  booking_uuid TEXT NOT NULL REFERENCES recurring_bookings (uuid),
+  occurs_on DATE NOT NULL,
+  pickup_time TIME,
+  start_lat DOUBLE PRECISION,
+  start_lng DOUBLE PRECISION,
+  end_lat DOUBLE PRECISION,
+  end_lng DOUBLE PRECISION,
+  PRIMARY KEY (booking_uuid, occurs_on)
+);
+
+-- Scheduled trips created for dates of the series
+CREATE TABLE IF NOT EXISTS recurring_booking_trips (
+  booking_uuid TEXT NOT NULL REFERENCES recurring_bookings (uuid),
+  occurs_on DATE NOT NULL,
+  trip_uuid TEXT NOT NULL UNIQUE REFERENCES trips (uuid),
+  PRIMARY KEY (booking_uuid, occurs_on)
+);
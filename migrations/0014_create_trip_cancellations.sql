@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS trip_cancellations (
+  trip_uuid TEXT PRIMARY KEY REFERENCES trips (uuid),
+  cancelled_by TEXT NOT NULL,
+  actor_uuid TEXT,
+  reason TEXT NOT NULL,
+  note TEXT,
+  at TIMESTAMPTZ NOT NULL,
+  fee_cents BIGINT NOT NULL DEFAULT 0 CHECK (fee_cents >= 0)
+);
@@ -0,0 +1,5 @@
+-- Trip history is listed per participant in pickup order
+CREATE INDEX IF NOT EXISTS trips_consumer_uuid_pickup_idx
+  ON trips (consumer_uuid, (COALESCE(pickup_at, created_at)), uuid);
+CREATE INDEX IF NOT EXISTS trips_driver_uuid_pickup_idx
+  ON trips (driver_uuid, (COALESCE(pickup_at, created_at)), uuid);
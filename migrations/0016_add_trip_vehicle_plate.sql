@@ -0,0 +1,3 @@
+ALTER TABLE trips ADD COLUMN IF NOT EXISTS vehicle_plate TEXT;
+
+CREATE INDEX IF NOT EXISTS trips_vehicle_plate_idx ON trips (vehicle_plate);
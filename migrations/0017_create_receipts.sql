@@ -0,0 +1,16 @@
+ALTER TABLE vehicles ADD COLUMN IF NOT EXISTS spsv_licence TEXT;
+
+ALTER TABLE trips ADD COLUMN IF NOT EXISTS payment_method TEXT;
+
+CREATE TABLE IF NOT EXISTS receipt_sequences (
+  organisation_code TEXT PRIMARY KEY,
+  last_number BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS receipts (
+  trip_uuid TEXT PRIMARY KEY REFERENCES trips (uuid),
+  organisation_code TEXT NOT NULL,
+  number BIGINT NOT NULL,
+  issued_at TIMESTAMPTZ NOT NULL,
+  UNIQUE (organisation_code, number)
+);
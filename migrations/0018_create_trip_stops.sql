@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS trip_stops (
+  uuid TEXT PRIMARY KEY,
+  trip_uuid TEXT NOT NULL REFERENCES trips (uuid),
+  position INTEGER NOT NULL,
+  lat DOUBLE PRECISION NOT NULL,
+  lng DOUBLE PRECISION NOT NULL,
+  arrived_at TIMESTAMPTZ,
+  departed_at TIMESTAMPTZ,
+  UNIQUE (trip_uuid, position)
+);
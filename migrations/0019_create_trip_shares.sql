@@ -0,0 +1,9 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS first_name TEXT;
+
+CREATE TABLE IF NOT EXISTS trip_shares (
+  token TEXT PRIMARY KEY,
+  trip_uuid TEXT NOT NULL REFERENCES trips (uuid),
+  created_at TIMESTAMPTZ NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+  revoked_at TIMESTAMPTZ
+);
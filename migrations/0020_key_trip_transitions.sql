@@ -0,0 +1,25 @@
+-- Saves used to insert the latest transition again, keep the first copy
+DELETE FROM trip_transitions duplicate
+  USING trip_transitions original
+  WHERE duplicate.trip_uuid = original.trip_uuid
+    AND duplicate.to_status = original.to_status
+    AND duplicate.at = original.at
+    AND duplicate.ctid > original.ctid;
+
+-- Transitions are numbered per trip, event ids are built from them
+ALTER TABLE trip_transitions ADD COLUMN IF NOT EXISTS position INTEGER;
+
+UPDATE trip_transitions SET position = numbered.position
+  FROM (
+    SELECT
+      ctid,
+      (ROW_NUMBER() OVER (PARTITION BY trip_uuid ORDER BY at) - 1)::INTEGER
+        AS position
+    FROM trip_transitions
+  ) numbered
+  WHERE trip_transitions.ctid = numbered.ctid;
+
+ALTER TABLE trip_transitions ALTER COLUMN position SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS trip_transitions_trip_uuid_position_idx
+  ON trip_transitions (trip_uuid, position);
@@ -0,0 +1,2 @@
+-- Bumped on every save so concurrent edits of a trip are detected
+ALTER TABLE trips ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+-- Everyone travelling, passengers after the first are charged extra
+ALTER TABLE trips
+  ADD COLUMN IF NOT EXISTS passengers INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE fare_estimates
+  ADD COLUMN IF NOT EXISTS passengers INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,16 @@
+-- Recurring booking date a trip was created for
+ALTER TABLE trips
+  ADD COLUMN IF NOT EXISTS booking_uuid TEXT
+    REFERENCES recurring_bookings (uuid),
+  ADD COLUMN IF NOT EXISTS booking_occurs_on DATE;
+
+UPDATE trips SET
+  booking_uuid = booked.booking_uuid,
+  booking_occurs_on = booked.occurs_on
+  FROM recurring_booking_trips booked
+  WHERE booked.trip_uuid = trips.uuid;
+
+-- A date gets another trip only once its trip is cancelled
+CREATE UNIQUE INDEX IF NOT EXISTS trips_booking_occurrence_idx
+  ON trips (booking_uuid, booking_occurs_on)
+  WHERE status <> '"cancelled"';
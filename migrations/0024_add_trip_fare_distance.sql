@@ -0,0 +1,2 @@
+-- Distance the fare was charged on, printed on receipts
+ALTER TABLE trip_fares ADD COLUMN IF NOT EXISTS distance_m BIGINT;
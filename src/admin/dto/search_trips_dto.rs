@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use validator::ValidationError;
+use validator_derive::Validate;
+
+use crate::shared::geo_point::ServiceArea;
+use crate::trips::dto::list_trips_dto::deserialize_statuses;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::repository::trip_repository::SortOrder;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_range"))]
+pub struct SearchTripsDto {
+  #[validate(length(min = 1))]
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: Option<String>,
+  #[validate(length(min = 1))]
+  #[serde(rename = "customerUuid")]
+  pub customer_uuid: Option<String>,
+  #[validate(length(min = 1, max = 16))]
+  #[serde(rename = "vehiclePlate")]
+  pub vehicle_plate: Option<String>,
+  // Comma separated, e.g. "completed,cancelled"
+  #[serde(default)]
+  #[serde(deserialize_with = "deserialize_statuses")]
+  pub status: Vec<TripStatus>,
+  // Pickup time range, the end is exclusive
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+  // Pickup within "min_lat,min_lng,max_lat,max_lng"
+  #[serde(default)]
+  #[serde(deserialize_with = "deserialize_area")]
+  pub area: Option<ServiceArea>,
+  #[serde(default)]
+  pub order: SortOrder,
+  #[validate(length(min = 1, max = 64))]
+  pub cursor: Option<String>,
+  #[validate(range(min = 1, max = 100))]
+  pub limit: Option<i64>,
+}
+
+fn deserialize_area<'de, D>(
+  deserializer: D,
+) -> Result<Option<ServiceArea>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  String::deserialize(deserializer)?
+    .parse()
+    .map(Some)
+    .map_err(|_| serde::de::Error::custom("invalid area"))
+}
+
+fn validate_range(dto: &SearchTripsDto) -> Result<(), ValidationError> {
+  if let (Some(from), Some(to)) = (dto.from, dto.to) {
+    if to <= from {
+      return Err(ValidationError::new("to_before_from"));
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,276 @@
+pub mod dto;
+pub mod rto;
+
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use dto::search_trips_dto::SearchTripsDto;
+use rto::trip_details_rto::{LocationPointRto, TripDetailsRto};
+use validator::Validate;
+
+use crate::dispatch::rto::trip_offer_rto::TripOfferRto;
+use crate::drivers::model::location_fix::LocationFix;
+use crate::drivers::repository::driver_repository::DriverRepository;
+use crate::shared::http_error::HttpError;
+use crate::shared::role::Role;
+use crate::trips::dto::get_trip_dto::GetTripDto;
+use crate::trips::repository::trip_repository::{TripQuery, TripRepository};
+use crate::trips::rto::get_trip_rto::GetTripRto;
+use crate::trips::trip_page;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+// Any trip, for the support team answering customers and drivers
+pub async fn search_trips<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  query: web::Query<SearchTripsDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Admin && auth.role != Role::Manager {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  // Perform validation
+  if let Err(validation_errors) = query.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let query = query.into_inner();
+  let trip_query = TripQuery {
+    consumer_uuid: query.customer_uuid,
+    driver_uuid: query.driver_uuid,
+    vehicle_plate: query.vehicle_plate,
+    area: query.area,
+    statuses: query.status,
+    from: query.from,
+    to: query.to,
+    order: query.order,
+    ..TripQuery::default()
+  };
+  trip_page(&**trip_repository, trip_query, query.cursor, query.limit).await
+}
+
+// Everything known about a trip, including where the driver went
+pub async fn get_trip_details<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Admin && auth.role != Role::Manager {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let Some(trip) = trip_repository.find_one(&path.uuid).await else {
+    return HttpResponse::NotFound()
+      .content_type("application/json")
+      .json(HttpError::from("Trip not found"));
+  };
+  let location_trace = match (&trip.driver_uuid, trip.driven_period(Utc::now()))
+  {
+    (Some(driver_uuid), Some((from, to))) => {
+      driver_repository
+        .find_locations(driver_uuid, from, to)
+        .await
+    }
+    _ => Vec::new(),
+  };
+  let offers = trip.offers.clone();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(TripDetailsRto {
+      trip: GetTripRto::from(trip),
+      offers: offers.into_iter().map(TripOfferRto::from).collect(),
+      location_trace: location_trace
+        .into_iter()
+        .map(LocationPointRto::from)
+        .collect(),
+    })
+}
+
+// Transform LocationFix domain to RTO
+impl From<LocationFix> for LocationPointRto {
+  fn from(fix: LocationFix) -> Self {
+    Self {
+      coords: fix.coords,
+      recorded_at: fix.recorded_at,
+      accuracy_m: fix.accuracy_m,
+      speed_mps: fix.speed_mps,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::{DateTime, Duration};
+
+  use super::*;
+  use crate::custom_nanoid;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::ride_requirements::RideRequirements;
+  use crate::trips::model::trip_status::TripStatus;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+  use crate::trips::repository::trip_repository::CreateTrip;
+  use crate::trips::rto::trip_page_rto::TripPageRto;
+
+  fn create_trip(consumer_uuid: &str, start_coords: GeoPoint) -> CreateTrip {
+    CreateTrip {
+      uuid: custom_nanoid(),
+      start_coords,
+      end_coords: GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      },
+      stops: Vec::new(),
+      driver_uuid: None,
+      consumer_uuid: String::from(consumer_uuid),
+      requirements: RideRequirements::default(),
+      passengers: 1,
+      fare_estimate_uuid: None,
+      pickup_at: None,
+      booking: None,
+    }
+  }
+
+  fn fix(driver_uuid: &str, recorded_at: DateTime<Utc>) -> LocationFix {
+    LocationFix {
+      driver_uuid: String::from(driver_uuid),
+      coords: GeoPoint {
+        lat: 53.35,
+        lng: -6.26,
+      },
+      accuracy_m: 5.0,
+      heading_deg: None,
+      speed_mps: Some(8.0),
+      recorded_at,
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_search_and_inspect_trips() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let trip_repository = InMemoryTripRepository::new();
+    let driver_uuid = custom_nanoid();
+    let dublin = GeoPoint {
+      lat: 53.3498,
+      lng: -6.2603,
+    };
+    let cork = GeoPoint {
+      lat: 51.8985,
+      lng: -8.4756,
+    };
+    let trip = trip_repository
+      .create(create_trip(&custom_nanoid(), dublin))
+      .await
+      .unwrap();
+    trip_repository
+      .create(create_trip(&custom_nanoid(), cork))
+      .await
+      .unwrap();
+    let now = Utc::now();
+    let accepted_at = now - Duration::minutes(20);
+    let completed_at = now - Duration::minutes(5);
+    let driven = trip
+      .offer(&driver_uuid, None, accepted_at, now)
+      .unwrap()
+      .accept(Some(String::from("241-D-12345")), None, accepted_at)
+      .unwrap()
+      .transition(TripStatus::DriverArrived, None, accepted_at)
+      .unwrap()
+      .transition(TripStatus::InProgress, None, accepted_at)
+      .unwrap()
+      .transition(TripStatus::Completed, None, completed_at)
+      .unwrap();
+    {
+      let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+      trips[0] = driven.clone();
+    }
+    let driver_repository = InMemoryDriverRepository::new();
+    driver_repository
+      .save_locations(&[
+        fix(&driver_uuid, accepted_at - Duration::minutes(1)),
+        fix(&driver_uuid, accepted_at + Duration::minutes(1)),
+        fix(&driver_uuid, completed_at - Duration::minutes(1)),
+        fix(&driver_uuid, now),
+      ])
+      .await
+      .unwrap();
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+    let driver_repository = web::Data::from(Arc::new(driver_repository));
+
+    for query in [
+      String::from("vehiclePlate=241-D-12345"),
+      format!("driverUuid={driver_uuid}&status=completed"),
+      String::from("area=53.2,-6.5,53.5,-6.0"),
+    ] {
+      let responder = search_trips(
+        trip_repository.clone(),
+        web::Query::from_query(&query).unwrap(),
+        create_fake_access_token_claims(),
+      )
+      .await;
+      let page: TripPageRto =
+        parse_http_response(responder, &request, StatusCode::OK).await;
+      assert_eq!(page.trips.len(), 1, "{query}");
+      assert_eq!(page.trips[0].uuid, driven.uuid);
+    }
+    let responder = search_trips(
+      trip_repository.clone(),
+      web::Query::from_query("").unwrap(),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let page: TripPageRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(page.trips.len(), 2);
+
+    // Customers and drivers only see their own trips
+    let responder = search_trips(
+      trip_repository.clone(),
+      web::Query::from_query("").unwrap(),
+      AccessTokenClaims {
+        role: Role::Customer,
+        ..create_fake_access_token_claims()
+      },
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let responder = get_trip_details(
+      trip_repository,
+      driver_repository,
+      web::Path::from(GetTripDto {
+        uuid: driven.uuid.clone(),
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: TripDetailsRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.trip.status, TripStatus::Completed);
+    assert_eq!(rto.trip.transitions.len(), 5);
+    assert_eq!(rto.offers.len(), 1);
+    // Only fixes recorded while driving the trip
+    let recorded: Vec<_> = rto
+      .location_trace
+      .iter()
+      .map(|point| point.recorded_at.timestamp())
+      .collect();
+    assert_eq!(
+      recorded,
+      vec![
+        (accepted_at + Duration::minutes(1)).timestamp(),
+        (completed_at - Duration::minutes(1)).timestamp(),
+      ]
+    );
+  }
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::dispatch::rto::trip_offer_rto::TripOfferRto;
+use crate::shared::geo_point::GeoPoint;
+use crate::trips::rto::get_trip_rto::GetTripRto;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocationPointRto {
+  pub coords: GeoPoint,
+  #[serde(rename = "recordedAt")]
+  pub recorded_at: DateTime<Utc>,
+  #[serde(rename = "accuracyM")]
+  pub accuracy_m: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "speedMps")]
+  pub speed_mps: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripDetailsRto {
+  #[serde(flatten)]
+  pub trip: GetTripRto,
+  pub offers: Vec<TripOfferRto>,
+  // Positions of the driver from accepting until the trip ended
+  #[serde(rename = "locationTrace")]
+  pub location_trace: Vec<LocationPointRto>,
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Europe::Dublin;
+
+use super::model::occurrence::BookedOccurrence;
+use super::model::RecurringBooking;
+use super::repository::booking_repository::{
+  BookingRepository, BookingRepositoryError,
+};
+use crate::custom_nanoid;
+use crate::shared::config::Config;
+use crate::trips::model::trip_booking::TripBooking;
+use crate::trips::repository::trip_repository::{CreateTrip, TripRepository};
+
+const MATERIALISER_INTERVAL_SECS: u64 = 300;
+
+pub fn spawn_booking_materialiser<
+  BR: BookingRepository + 'static,
+  TR: TripRepository + 'static,
+>(
+  booking_repository: Arc<BR>,
+  trip_repository: Arc<TR>,
+  config: Config,
+) {
+  actix_web::rt::spawn(async move {
+    let mut interval = actix_web::rt::time::interval(StdDuration::from_secs(
+      MATERIALISER_INTERVAL_SECS,
+    ));
+    loop {
+      interval.tick().await;
+      materialise_bookings(
+        &*booking_repository,
+        &*trip_repository,
+        &config,
+        Utc::now(),
+      )
+      .await;
+    }
+  });
+}
+
+// Creates the scheduled trips of every running series, returns the
+// number of trips created
+pub async fn materialise_bookings<BR: BookingRepository, TR: TripRepository>(
+  booking_repository: &BR,
+  trip_repository: &TR,
+  config: &Config,
+  now: DateTime<Utc>,
+) -> usize {
+  let today = now.with_timezone(&Dublin).date_naive();
+  let mut created = 0;
+  for booking in booking_repository.find_active(today).await {
+    let booked = booking.occurrences.len();
+    // Errors are retried on the next run
+    if let Ok(booking) = materialise_booking(
+      booking_repository,
+      trip_repository,
+      config,
+      booking,
+      now,
+    )
+    .await
+    {
+      created += booking.occurrences.len().saturating_sub(booked);
+    }
+  }
+  created
+}
+
+// Scheduled trips are created ahead of time for the dates coming up, so
+// they go through the same reminders and dispatch as one-off bookings
+pub async fn materialise_booking<BR: BookingRepository, TR: TripRepository>(
+  booking_repository: &BR,
+  trip_repository: &TR,
+  config: &Config,
+  mut booking: RecurringBooking,
+  now: DateTime<Utc>,
+) -> Result<RecurringBooking, BookingRepositoryError> {
+  let horizon = Duration::seconds(config.scheduling.materialise_ahead_secs);
+  let due = booking.due_occurrences(now, horizon);
+  if due.is_empty() {
+    return Ok(booking);
+  }
+  for occurrence in due {
+    // Creating a trip for a date that has one returns that trip, so a
+    // date left unrecorded by an earlier run is never booked twice
+    let created = trip_repository
+      .create(CreateTrip {
+        uuid: custom_nanoid(),
+        start_coords: occurrence.start_coords,
+        end_coords: occurrence.end_coords,
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: booking.consumer_uuid.clone(),
+        requirements: booking.requirements.clone(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: Some(occurrence.pickup_at),
+        booking: Some(TripBooking {
+          booking_uuid: booking.uuid.clone(),
+          occurs_on: occurrence.occurs_on,
+        }),
+      })
+      .await;
+    // Errors are retried on the next run
+    let Ok(trip) = created else {
+      break;
+    };
+    // Only the date is recorded, edits to the series made meanwhile are
+    // kept
+    booking = booking_repository
+      .book_occurrence(
+        booking,
+        BookedOccurrence {
+          occurs_on: occurrence.occurs_on,
+          trip_uuid: trip.uuid,
+        },
+      )
+      .await?;
+  }
+  Ok(booking)
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{Days, NaiveTime, Weekday};
+
+  use super::*;
+  use crate::bookings::model::recurrence_rule::RecurrenceRule;
+  use crate::bookings::repository::booking_repository::tests::InMemoryBookingRepository;
+  use crate::bookings::repository::booking_repository::CreateRecurringBooking;
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::ride_requirements::RideRequirements;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+
+  #[actix_web::test]
+  async fn test_unrecorded_trips_are_not_booked_twice() {
+    let booking_repository = InMemoryBookingRepository::new();
+    let trip_repository = InMemoryTripRepository::new();
+    let config = Config::default();
+    let now = Utc::now();
+    let today = now.with_timezone(&Dublin).date_naive();
+    let booking = booking_repository
+      .create(CreateRecurringBooking {
+        uuid: custom_nanoid(),
+        consumer_uuid: custom_nanoid(),
+        start_coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+        end_coords: GeoPoint {
+          lat: 53.3302,
+          lng: -6.2945,
+        },
+        requirements: RideRequirements::default(),
+        rule: RecurrenceRule {
+          weekdays: vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+          ],
+          pickup_time: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+          starts_on: today + Days::new(1),
+          until: None,
+          exception_dates: Vec::new(),
+        },
+      })
+      .await
+      .unwrap();
+    // An earlier run created the first trip but could not record it
+    let occurs_on = today + Days::new(1);
+    let occurrence = booking.occurrence(occurs_on).unwrap();
+    let left_over = trip_repository
+      .create(CreateTrip {
+        uuid: custom_nanoid(),
+        start_coords: occurrence.start_coords,
+        end_coords: occurrence.end_coords,
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: booking.consumer_uuid.clone(),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: Some(occurrence.pickup_at),
+        booking: Some(TripBooking {
+          booking_uuid: booking.uuid.clone(),
+          occurs_on,
+        }),
+      })
+      .await
+      .unwrap();
+
+    let materialised = materialise_booking(
+      &booking_repository,
+      &trip_repository,
+      &config,
+      booking.clone(),
+      now,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+      materialised.booked_trip_uuid(occurs_on),
+      Some(left_over.uuid.as_str())
+    );
+    {
+      let trips = trip_repository.trips.read().unwrap(); // Acquire read lock
+      assert_eq!(trips.len(), materialised.occurrences.len());
+    }
+
+    // Edits based on the series as loaded before would drop those trips
+    let result = booking_repository
+      .update(booking.skip_occurrence(occurs_on))
+      .await;
+    assert!(matches!(result, Err(BookingRepositoryError::Changed)));
+  }
+}
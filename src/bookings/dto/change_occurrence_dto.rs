@@ -0,0 +1,31 @@
+use chrono::NaiveTime;
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_has_change"))]
+pub struct ChangeOccurrenceDto {
+  #[serde(rename = "pickupTime")]
+  pub pickup_time: Option<NaiveTime>,
+  #[validate(nested)]
+  #[serde(rename = "startCoords")]
+  pub start_coords: Option<GeoPoint>,
+  #[validate(nested)]
+  #[serde(rename = "endCoords")]
+  pub end_coords: Option<GeoPoint>,
+}
+
+fn validate_has_change(
+  dto: &ChangeOccurrenceDto,
+) -> Result<(), ValidationError> {
+  if dto.pickup_time.is_none()
+    && dto.start_coords.is_none()
+    && dto.end_coords.is_none()
+  {
+    return Err(ValidationError::new("no_change"));
+  }
+  Ok(())
+}
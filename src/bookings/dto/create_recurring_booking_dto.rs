@@ -0,0 +1,43 @@
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+use crate::trips::dto::create_trip_dto::RideRequirementsDto;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_until"))]
+pub struct CreateRecurringBookingDto {
+  #[validate(nested)]
+  #[serde(rename = "startCoords")]
+  pub start_coords: GeoPoint,
+  #[validate(nested)]
+  #[serde(rename = "endCoords")]
+  pub end_coords: GeoPoint,
+  // Overrides the customer's stored ride preferences for the series
+  #[validate(nested)]
+  #[serde(default)]
+  pub requirements: Option<RideRequirementsDto>,
+  // e.g. ["mon", "thu"]
+  #[validate(length(min = 1, max = 7))]
+  pub weekdays: Vec<Weekday>,
+  // Irish local time, e.g. "08:30"
+  #[serde(rename = "pickupTime")]
+  pub pickup_time: NaiveTime,
+  #[serde(rename = "startsOn")]
+  pub starts_on: NaiveDate,
+  pub until: Option<NaiveDate>,
+  #[serde(default)]
+  #[serde(rename = "exceptionDates")]
+  pub exception_dates: Vec<NaiveDate>,
+}
+
+fn validate_until(
+  dto: &CreateRecurringBookingDto,
+) -> Result<(), ValidationError> {
+  if dto.until.is_some_and(|until| until < dto.starts_on) {
+    return Err(ValidationError::new("until_before_starts_on"));
+  }
+  Ok(())
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetRecurringBookingDto {
+  pub uuid: String,
+}
@@ -0,0 +1,4 @@
+pub mod change_occurrence_dto;
+pub mod create_recurring_booking_dto;
+pub mod get_recurring_booking_dto;
+pub mod occurrence_dto;
@@ -0,0 +1,10 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OccurrenceDto {
+  pub uuid: String,
+  // Day of the series, e.g. "2025-03-05"
+  pub date: NaiveDate,
+}
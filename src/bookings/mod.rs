@@ -0,0 +1,523 @@
+pub mod booking_materialiser;
+pub mod dto;
+pub mod model;
+pub mod repository;
+pub mod rto;
+
+use actix_web::http::header;
+use actix_web::{web, HttpResponse, Responder};
+use booking_materialiser::materialise_booking;
+use chrono::{NaiveDate, Utc};
+use dto::change_occurrence_dto::ChangeOccurrenceDto;
+use dto::create_recurring_booking_dto::CreateRecurringBookingDto;
+use dto::get_recurring_booking_dto::GetRecurringBookingDto;
+use dto::occurrence_dto::OccurrenceDto;
+use model::occurrence::{BookedOccurrence, OccurrenceChange};
+use model::recurrence_rule::RecurrenceRule;
+use model::RecurringBooking;
+use repository::booking_repository::{
+  BookingRepository, BookingRepositoryError, CreateRecurringBooking,
+};
+use rto::recurring_booking_rto::{
+  BookedOccurrenceRto, OccurrenceChangeRto, RecurringBookingRto,
+};
+use validator::Validate;
+
+use crate::custom_nanoid;
+use crate::customers::repository::customer_repository::CustomerRepository;
+use crate::shared::config::Config;
+use crate::shared::http_error::HttpError;
+use crate::shared::ride_requirements::RideRequirementsOverride;
+use crate::shared::role::Role;
+use crate::shared::rto::created_rto::CreatedRto;
+use crate::trips::model::trip_cancellation::{
+  CancellationReason, CancellationRequest,
+};
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::repository::trip_repository::TripRepository;
+use crate::trips::{cancelled_by, reject_outside_service_area};
+use crate::users::model::access_token_claims::AccessTokenClaims;
+use crate::zones::{pickup_point, pickup_refused};
+
+pub async fn create_recurring_booking<
+  BR: BookingRepository,
+  TR: TripRepository,
+  CR: CustomerRepository,
+>(
+  booking_repository: web::Data<BR>,
+  trip_repository: web::Data<TR>,
+  customer_repository: web::Data<CR>,
+  config: web::Data<Config>,
+  dto: web::Json<CreateRecurringBookingDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if let Some(response) =
+    reject_outside_service_area(&config, &dto.start_coords, &dto.end_coords)
+  {
+    return response;
+  }
+  let start_coords =
+    match pickup_point(&config.organisation.zones, &dto.start_coords) {
+      Ok(start_coords) => start_coords,
+      Err(error) => return pickup_refused(error),
+    };
+  let dto = dto.into_inner();
+  let preferences = customer_repository
+    .find_preferences(&auth.uuid)
+    .await
+    .unwrap_or_default();
+  let changes = dto.requirements.map(RideRequirementsOverride::from);
+  let booking = match booking_repository
+    .create(CreateRecurringBooking {
+      uuid: custom_nanoid(),
+      consumer_uuid: auth.uuid,
+      start_coords,
+      end_coords: dto.end_coords,
+      requirements: preferences.with_override(changes.unwrap_or_default()),
+      rule: RecurrenceRule {
+        weekdays: dto.weekdays,
+        pickup_time: dto.pickup_time,
+        starts_on: dto.starts_on,
+        until: dto.until,
+        exception_dates: dto.exception_dates,
+      },
+    })
+    .await
+  {
+    Ok(booking) => booking,
+    Err(error) => return failed_save_booking(error),
+  };
+  // Trips coming up soon are booked right away, the materialiser retries
+  // if this fails
+  let _ = materialise_booking(
+    &**booking_repository,
+    &**trip_repository,
+    &config,
+    booking.clone(),
+    Utc::now(),
+  )
+  .await;
+  HttpResponse::Created()
+    .content_type("application/json")
+    .append_header((
+      header::LOCATION,
+      format!("/v1/recurring-bookings/{}", booking.uuid),
+    ))
+    .json(CreatedRto { uuid: booking.uuid })
+}
+
+pub async fn get_recurring_booking<BR: BookingRepository>(
+  booking_repository: web::Data<BR>,
+  path: web::Path<GetRecurringBookingDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  find_visible_booking(&**booking_repository, &path.uuid, &auth)
+    .await
+    .map_or_else(booking_not_found, booking_found)
+}
+
+// Drops a single date from the series and cancels its trip
+pub async fn skip_occurrence<BR: BookingRepository, TR: TripRepository>(
+  booking_repository: web::Data<BR>,
+  trip_repository: web::Data<TR>,
+  config: web::Data<Config>,
+  path: web::Path<OccurrenceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let Some(booking) =
+    find_visible_booking(&**booking_repository, &path.uuid, &auth).await
+  else {
+    return booking_not_found();
+  };
+  if booking.occurrence(path.date).is_none() {
+    return occurrence_not_found();
+  }
+  if let Some(response) =
+    cancel_booked_trip(&**trip_repository, &config, &booking, path.date, &auth)
+      .await
+  {
+    return response;
+  }
+  booking_repository
+    .update(booking.skip_occurrence(path.date).unbook(path.date))
+    .await
+    .map(booking_found)
+    .unwrap_or_else(failed_save_booking)
+}
+
+// Moves a single date to another time or place, its trip is replaced
+pub async fn change_occurrence<BR: BookingRepository, TR: TripRepository>(
+  booking_repository: web::Data<BR>,
+  trip_repository: web::Data<TR>,
+  config: web::Data<Config>,
+  path: web::Path<OccurrenceDto>,
+  dto: web::Json<ChangeOccurrenceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let Some(booking) =
+    find_visible_booking(&**booking_repository, &path.uuid, &auth).await
+  else {
+    return booking_not_found();
+  };
+  if booking.occurrence(path.date).is_none() {
+    return occurrence_not_found();
+  }
+  let dto = dto.into_inner();
+  let start_coords = match dto
+    .start_coords
+    .map(|coords| pickup_point(&config.organisation.zones, &coords))
+    .transpose()
+  {
+    Ok(start_coords) => start_coords,
+    Err(error) => return pickup_refused(error),
+  };
+  let booking = booking.change_occurrence(OccurrenceChange {
+    occurs_on: path.date,
+    pickup_time: dto.pickup_time,
+    start_coords,
+    end_coords: dto.end_coords,
+  });
+  let Some(occurrence) = booking.occurrence(path.date) else {
+    return occurrence_not_found();
+  };
+  if let Some(response) = reject_outside_service_area(
+    &config,
+    &occurrence.start_coords,
+    &occurrence.end_coords,
+  ) {
+    return response;
+  }
+  let now = Utc::now();
+  if !config
+    .scheduling
+    .is_within_booking_window(occurrence.pickup_at, now)
+  {
+    return HttpResponse::BadRequest()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "outside_booking_window",
+        "Pickup time is too soon or too far ahead",
+      ));
+  }
+  if let Some(response) =
+    cancel_booked_trip(&**trip_repository, &config, &booking, path.date, &auth)
+      .await
+  {
+    return response;
+  }
+  let booking = match booking_repository.update(booking.unbook(path.date)).await
+  {
+    Ok(booking) => booking,
+    Err(error) => return failed_save_booking(error),
+  };
+  materialise_booking(
+    &**booking_repository,
+    &**trip_repository,
+    &config,
+    booking,
+    now,
+  )
+  .await
+  .map(booking_found)
+  .unwrap_or_else(failed_save_booking)
+}
+
+// Series of other customers are reported as not found
+async fn find_visible_booking<BR: BookingRepository>(
+  booking_repository: &BR,
+  uuid: &str,
+  auth: &AccessTokenClaims,
+) -> Option<RecurringBooking> {
+  booking_repository.find_one(uuid).await.filter(|booking| {
+    booking.consumer_uuid == auth.uuid
+      || auth.role == Role::Admin
+      || auth.role == Role::Manager
+  })
+}
+
+// Error response when the trip of the date cannot be cancelled any more
+async fn cancel_booked_trip<TR: TripRepository>(
+  trip_repository: &TR,
+  config: &Config,
+  booking: &RecurringBooking,
+  date: NaiveDate,
+  auth: &AccessTokenClaims,
+) -> Option<HttpResponse> {
+  let trip_uuid = booking.booked_trip_uuid(date)?;
+  let trip = trip_repository.find_one(trip_uuid).await?;
+  let cancelled = match trip.status {
+    TripStatus::Cancelled => return None,
+    // Only trips still waiting for dispatch are replaced
+    TripStatus::Scheduled => {
+      let request = CancellationRequest {
+        cancelled_by: cancelled_by(&trip, auth),
+        actor_uuid: Some(auth.uuid.clone()),
+        reason: CancellationReason::ChangedPlans,
+        note: Some(format!("Recurring booking {} on {}", booking.uuid, date)),
+      };
+      trip.cancel(request, &config.cancellation, Utc::now()).ok()
+    }
+    _ => None,
+  };
+  let saved = match cancelled {
+    Some(cancelled) => trip_repository
+      .update(cancelled, TripStatus::Scheduled)
+      .await
+      .is_ok(),
+    None => false,
+  };
+  (!saved).then(|| {
+    HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "occurrence_dispatched",
+        "The trip for this date is already being dispatched",
+      ))
+  })
+}
+
+fn booking_found(booking: RecurringBooking) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .append_header((
+      header::LOCATION,
+      format!("/v1/recurring-bookings/{}", booking.uuid),
+    ))
+    .json(RecurringBookingRto::from(booking))
+}
+
+fn booking_not_found() -> HttpResponse {
+  HttpResponse::NotFound()
+    .content_type("application/json")
+    .json(HttpError::from("Recurring booking not found"))
+}
+
+fn occurrence_not_found() -> HttpResponse {
+  HttpResponse::NotFound()
+    .content_type("application/json")
+    .json(HttpError::from("The series has no trip on this date"))
+}
+
+fn failed_save_booking(error: BookingRepositoryError) -> HttpResponse {
+  match error {
+    BookingRepositoryError::Changed => HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "booking_changed",
+        "Recurring booking was updated concurrently",
+      )),
+    _ => HttpResponse::InternalServerError().finish(),
+  }
+}
+
+// Transform RecurringBooking domain to RTO
+impl From<RecurringBooking> for RecurringBookingRto {
+  fn from(booking: RecurringBooking) -> Self {
+    Self {
+      uuid: booking.uuid,
+      consumer_uuid: booking.consumer_uuid,
+      start_coords: booking.start_coords,
+      end_coords: booking.end_coords,
+      requirements: booking.requirements,
+      weekdays: booking.rule.weekdays,
+      pickup_time: booking.rule.pickup_time,
+      starts_on: booking.rule.starts_on,
+      until: booking.rule.until,
+      exception_dates: booking.rule.exception_dates,
+      changes: booking
+        .changes
+        .into_iter()
+        .map(OccurrenceChangeRto::from)
+        .collect(),
+      occurrences: booking
+        .occurrences
+        .into_iter()
+        .map(BookedOccurrenceRto::from)
+        .collect(),
+    }
+  }
+}
+
+// Transform OccurrenceChange domain to RTO
+impl From<OccurrenceChange> for OccurrenceChangeRto {
+  fn from(change: OccurrenceChange) -> Self {
+    Self {
+      occurs_on: change.occurs_on,
+      pickup_time: change.pickup_time,
+      start_coords: change.start_coords,
+      end_coords: change.end_coords,
+    }
+  }
+}
+
+// Transform BookedOccurrence domain to RTO
+impl From<BookedOccurrence> for BookedOccurrenceRto {
+  fn from(occurrence: BookedOccurrence) -> Self {
+    Self {
+      occurs_on: occurrence.occurs_on,
+      trip_uuid: occurrence.trip_uuid,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::{Days, NaiveTime};
+  use chrono_tz::Europe::Dublin;
+  use repository::booking_repository::tests::InMemoryBookingRepository;
+
+  use crate::customers::repository::customer_repository::tests::InMemoryCustomerRepository;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+
+  use super::*;
+
+  fn create_booking_dto(starts_on: NaiveDate) -> CreateRecurringBookingDto {
+    serde_json::from_value(serde_json::json!({
+      "startCoords": { "lat": 53.3498, "lng": -6.2603 },
+      "endCoords": { "lat": 53.3302, "lng": -6.2945 },
+      "weekdays": ["mon", "tue", "wed", "thu", "fri", "sat", "sun"],
+      "pickupTime": "12:00",
+      "startsOn": starts_on,
+    }))
+    .unwrap()
+  }
+
+  #[actix_web::test]
+  async fn test_skip_and_change_single_occurrences() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = create_fake_access_token_claims();
+    let booking_repository = web::Data::new(InMemoryBookingRepository::new());
+    let trip_repository = web::Data::new(InMemoryTripRepository::new());
+    let config = web::Data::new(Config::default());
+    let today = Utc::now().with_timezone(&Dublin).date_naive();
+    let (skipped, changed) = (today + Days::new(2), today + Days::new(3));
+
+    let responder = create_recurring_booking(
+      booking_repository.clone(),
+      trip_repository.clone(),
+      web::Data::from(Arc::new(InMemoryCustomerRepository::new())),
+      config.clone(),
+      web::Json(create_booking_dto(today + Days::new(1))),
+      auth.clone(),
+    )
+    .await;
+    let created: CreatedRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    let booking = booking_repository.find_one(&created.uuid).await.unwrap();
+    // A week of trips, the last one may fall just outside the horizon
+    assert!(booking.occurrences.len() >= 6);
+    let skipped_trip = booking.booked_trip_uuid(skipped).unwrap().to_owned();
+    let changed_trip = booking.booked_trip_uuid(changed).unwrap().to_owned();
+    let trip = trip_repository.find_one(&skipped_trip).await.unwrap();
+    assert_eq!(trip.status, TripStatus::Scheduled);
+
+    let responder = skip_occurrence(
+      booking_repository.clone(),
+      trip_repository.clone(),
+      config.clone(),
+      web::Path::from(OccurrenceDto {
+        uuid: created.uuid.clone(),
+        date: skipped,
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: RecurringBookingRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.exception_dates, vec![skipped]);
+    assert!(rto
+      .occurrences
+      .iter()
+      .all(|booked| booked.occurs_on != skipped));
+    let trip = trip_repository.find_one(&skipped_trip).await.unwrap();
+    assert_eq!(trip.status, TripStatus::Cancelled);
+    let cancellation = trip.cancellation.unwrap();
+    assert_eq!(cancellation.reason, CancellationReason::ChangedPlans);
+    assert_eq!(cancellation.fee_cents, 0);
+
+    let responder = change_occurrence(
+      booking_repository.clone(),
+      trip_repository.clone(),
+      config.clone(),
+      web::Path::from(OccurrenceDto {
+        uuid: created.uuid.clone(),
+        date: changed,
+      }),
+      web::Json(ChangeOccurrenceDto {
+        pickup_time: NaiveTime::from_hms_opt(15, 30, 0),
+        start_coords: None,
+        end_coords: None,
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: RecurringBookingRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    let replacement = rto
+      .occurrences
+      .iter()
+      .find(|booked| booked.occurs_on == changed)
+      .unwrap();
+    assert_ne!(replacement.trip_uuid, changed_trip);
+    let trip = trip_repository.find_one(&changed_trip).await.unwrap();
+    assert_eq!(trip.status, TripStatus::Cancelled);
+    let trip = trip_repository
+      .find_one(&replacement.trip_uuid)
+      .await
+      .unwrap();
+    let pickup_at = trip.pickup_at.unwrap().with_timezone(&Dublin);
+    assert_eq!(
+      pickup_at.time(),
+      NaiveTime::from_hms_opt(15, 30, 0).unwrap()
+    );
+
+    // The rest of the series is untouched
+    let next_day = changed + Days::new(1);
+    let booking = booking_repository.find_one(&created.uuid).await.unwrap();
+    let trip_uuid = booking.booked_trip_uuid(next_day).unwrap();
+    let trip = trip_repository.find_one(trip_uuid).await.unwrap();
+    let pickup_at = trip.pickup_at.unwrap().with_timezone(&Dublin);
+    assert_eq!(pickup_at.time(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+
+    // Dates outside the series cannot be skipped
+    let responder = skip_occurrence(
+      booking_repository,
+      trip_repository,
+      config,
+      web::Path::from(OccurrenceDto {
+        uuid: created.uuid,
+        date: today,
+      }),
+      auth,
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+  }
+}
@@ -0,0 +1,204 @@
+pub mod occurrence;
+pub mod recurrence_rule;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Dublin;
+
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+use occurrence::{BookedOccurrence, Occurrence, OccurrenceChange};
+use recurrence_rule::RecurrenceRule;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurringBooking {
+  pub uuid: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub consumer_uuid: String,
+  pub start_coords: GeoPoint,
+  pub end_coords: GeoPoint,
+  pub requirements: RideRequirements,
+  pub rule: RecurrenceRule,
+  pub changes: Vec<OccurrenceChange>,
+  // Dates a trip was already created for, oldest first
+  pub occurrences: Vec<BookedOccurrence>,
+}
+
+// Local pickup time on a date. Times skipped when the clocks go forward
+// are moved an hour later, repeated ones use the first.
+fn local_pickup_at(date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+  let local = date.and_time(time);
+  Dublin
+    .from_local_datetime(&local)
+    .earliest()
+    .or_else(|| {
+      Dublin
+        .from_local_datetime(&(local + Duration::hours(1)))
+        .earliest()
+    })
+    .map_or_else(|| local.and_utc(), |at| at.with_timezone(&Utc))
+}
+
+impl RecurringBooking {
+  pub fn occurrence(&self, date: NaiveDate) -> Option<Occurrence> {
+    if !self.rule.occurs_on(date) {
+      return None;
+    }
+    let change = self.changes.iter().find(|change| change.occurs_on == date);
+    let pickup_time = change
+      .and_then(|change| change.pickup_time)
+      .unwrap_or(self.rule.pickup_time);
+    Some(Occurrence {
+      occurs_on: date,
+      pickup_at: local_pickup_at(date, pickup_time),
+      start_coords: change
+        .and_then(|change| change.start_coords)
+        .unwrap_or(self.start_coords),
+      end_coords: change
+        .and_then(|change| change.end_coords)
+        .unwrap_or(self.end_coords),
+    })
+  }
+
+  // Occurrences with a pickup within the horizon and no trip yet
+  pub fn due_occurrences(
+    &self,
+    now: DateTime<Utc>,
+    horizon: Duration,
+  ) -> Vec<Occurrence> {
+    let until = now + horizon;
+    let today = now.with_timezone(&Dublin).date_naive();
+    let last_day = until.with_timezone(&Dublin).date_naive();
+    self
+      .rule
+      .dates_between(today, last_day)
+      .filter(|date| self.booked_trip_uuid(*date).is_none())
+      .filter_map(|date| self.occurrence(date))
+      .filter(|occurrence| {
+        occurrence.pickup_at > now && occurrence.pickup_at <= until
+      })
+      .collect()
+  }
+
+  pub fn booked_trip_uuid(&self, date: NaiveDate) -> Option<&str> {
+    self
+      .occurrences
+      .iter()
+      .find(|occurrence| occurrence.occurs_on == date)
+      .map(|occurrence| occurrence.trip_uuid.as_str())
+  }
+
+  // Replaces any earlier change to the same date
+  pub fn change_occurrence(mut self, change: OccurrenceChange) -> Self {
+    self
+      .changes
+      .retain(|other| other.occurs_on != change.occurs_on);
+    self.changes.push(change);
+    self.changes.sort_by_key(|change| change.occurs_on);
+    self
+  }
+
+  pub fn skip_occurrence(mut self, date: NaiveDate) -> Self {
+    if !self.rule.exception_dates.contains(&date) {
+      self.rule.exception_dates.push(date);
+      self.rule.exception_dates.sort();
+    }
+    self.changes.retain(|change| change.occurs_on != date);
+    self
+  }
+
+  pub fn book(mut self, occurrence: BookedOccurrence) -> Self {
+    self.occurrences.push(occurrence);
+    self
+      .occurrences
+      .sort_by_key(|occurrence| occurrence.occurs_on);
+    self
+  }
+
+  // The trip of a date is dropped so a new one can be created
+  pub fn unbook(mut self, date: NaiveDate) -> Self {
+    self
+      .occurrences
+      .retain(|occurrence| occurrence.occurs_on != date);
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::Weekday;
+
+  use super::*;
+
+  fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+  }
+
+  fn booking() -> RecurringBooking {
+    RecurringBooking {
+      uuid: String::from("booking"),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      consumer_uuid: String::from("customer"),
+      start_coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      end_coords: GeoPoint {
+        lat: 53.3302,
+        lng: -6.2945,
+      },
+      requirements: RideRequirements::default(),
+      rule: recurrence_rule::RecurrenceRule {
+        weekdays: vec![Weekday::Mon, Weekday::Wed],
+        pickup_time: NaiveTime::from_hms_opt(8, 15, 0).unwrap(),
+        starts_on: date(3, 1),
+        until: None,
+        exception_dates: Vec::new(),
+      },
+      changes: Vec::new(),
+      occurrences: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_pickup_is_in_irish_time() {
+    let booking = booking();
+    // Winter time is UTC, summer time is an hour ahead
+    let winter = booking.occurrence(date(3, 3)).unwrap();
+    assert_eq!(winter.pickup_at.to_rfc3339(), "2025-03-03T08:15:00+00:00");
+    let summer = booking.occurrence(date(7, 2)).unwrap();
+    assert_eq!(summer.pickup_at.to_rfc3339(), "2025-07-02T07:15:00+00:00");
+  }
+
+  #[test]
+  fn test_changed_and_skipped_occurrences() {
+    let now = local_pickup_at(date(3, 2), NaiveTime::MIN);
+    let booking = booking().skip_occurrence(date(3, 3)).change_occurrence(
+      OccurrenceChange {
+        occurs_on: date(3, 5),
+        pickup_time: NaiveTime::from_hms_opt(10, 0, 0),
+        start_coords: None,
+        end_coords: None,
+      },
+    );
+    let due = booking.due_occurrences(now, Duration::days(7));
+    let dates: Vec<NaiveDate> =
+      due.iter().map(|occurrence| occurrence.occurs_on).collect();
+    assert_eq!(dates, vec![date(3, 5)]);
+    assert_eq!(due[0].pickup_at.to_rfc3339(), "2025-03-05T10:00:00+00:00");
+    assert_eq!(due[0].start_coords, booking.start_coords);
+
+    let booking = RecurringBooking {
+      occurrences: vec![BookedOccurrence {
+        occurs_on: date(3, 5),
+        trip_uuid: String::from("trip"),
+      }],
+      ..booking
+    };
+    assert!(booking.due_occurrences(now, Duration::days(7)).is_empty());
+    assert_eq!(booking.booked_trip_uuid(date(3, 5)), Some("trip"));
+    let booking = booking.unbook(date(3, 5));
+    assert_eq!(booking.due_occurrences(now, Duration::days(7)).len(), 1);
+  }
+}
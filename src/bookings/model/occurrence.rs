@@ -0,0 +1,28 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+use crate::shared::geo_point::GeoPoint;
+
+// Changes to a single date of the series, the rest is left as is
+#[derive(Debug, Clone, PartialEq)]
+pub struct OccurrenceChange {
+  pub occurs_on: NaiveDate,
+  pub pickup_time: Option<NaiveTime>,
+  pub start_coords: Option<GeoPoint>,
+  pub end_coords: Option<GeoPoint>,
+}
+
+// A date of the series with its changes applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+  pub occurs_on: NaiveDate,
+  pub pickup_at: DateTime<Utc>,
+  pub start_coords: GeoPoint,
+  pub end_coords: GeoPoint,
+}
+
+// Scheduled trip created for a date of the series
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookedOccurrence {
+  pub occurs_on: NaiveDate,
+  pub trip_uuid: String,
+}
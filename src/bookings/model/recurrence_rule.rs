@@ -0,0 +1,61 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+
+// Weekly repetition on fixed days at the same local pickup time, e.g.
+// every Tuesday and Thursday at 09:30 until the end of term
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+  pub weekdays: Vec<Weekday>,
+  // Irish local time
+  pub pickup_time: NaiveTime,
+  pub starts_on: NaiveDate,
+  // Last day the trip can take place, repeats forever when unset
+  pub until: Option<NaiveDate>,
+  // Days the trip does not take place, e.g. bank holidays
+  pub exception_dates: Vec<NaiveDate>,
+}
+
+impl RecurrenceRule {
+  pub fn occurs_on(&self, date: NaiveDate) -> bool {
+    date >= self.starts_on
+      && self.until.is_none_or(|until| date <= until)
+      && self.weekdays.contains(&date.weekday())
+      && !self.exception_dates.contains(&date)
+  }
+
+  // Occurrence dates from `from` to `to`, both included
+  pub fn dates_between(
+    &self,
+    from: NaiveDate,
+    to: NaiveDate,
+  ) -> impl Iterator<Item = NaiveDate> + '_ {
+    from
+      .max(self.starts_on)
+      .iter_days()
+      .take_while(move |date| *date <= to)
+      .filter(|date| self.occurs_on(*date))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+  }
+
+  #[test]
+  fn test_dates_between() {
+    // Tuesdays and Thursdays, the 2nd of September 2025 is a Tuesday
+    let rule = RecurrenceRule {
+      weekdays: vec![Weekday::Tue, Weekday::Thu],
+      pickup_time: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      starts_on: date(3),
+      until: Some(date(18)),
+      exception_dates: vec![date(9)],
+    };
+    let dates: Vec<NaiveDate> = rule.dates_between(date(1), date(30)).collect();
+    assert_eq!(dates, vec![date(4), date(11), date(16), date(18)]);
+    assert!(!rule.occurs_on(date(2)));
+  }
+}
@@ -0,0 +1,428 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use sqlx::Row;
+use sqlx::{postgres::PgRow, Pool, Postgres};
+use thiserror::Error;
+
+use crate::bookings::model::occurrence::{BookedOccurrence, OccurrenceChange};
+use crate::bookings::model::recurrence_rule::RecurrenceRule;
+use crate::bookings::model::RecurringBooking;
+use crate::shared::database::Database;
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+
+#[derive(Debug, Error)]
+pub enum BookingRepositoryError {
+  #[error("Database error: {0}")]
+  DatabaseError(#[from] sqlx::Error),
+
+  #[error("Serialization error: {0}")]
+  SerializationError(#[from] serde_json::Error),
+
+  #[error("Recurring booking changed concurrently")]
+  Changed,
+}
+
+pub trait BookingRepository {
+  async fn find_one(&self, uuid: &str) -> Option<RecurringBooking>;
+  // Series still running on the given day
+  async fn find_active(&self, today: NaiveDate) -> Vec<RecurringBooking>;
+  async fn create(
+    &self,
+    create_booking: CreateRecurringBooking,
+  ) -> Result<RecurringBooking, BookingRepositoryError>;
+  // Saves the rule, changes and booked occurrences, but only while the
+  // series is unchanged since it was loaded
+  async fn update(
+    &self,
+    booking: RecurringBooking,
+  ) -> Result<RecurringBooking, BookingRepositoryError>;
+  // Records the trip created for a date, whatever else changed
+  async fn book_occurrence(
+    &self,
+    booking: RecurringBooking,
+    occurrence: BookedOccurrence,
+  ) -> Result<RecurringBooking, BookingRepositoryError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateRecurringBooking {
+  pub uuid: String,
+  pub consumer_uuid: String,
+  pub start_coords: GeoPoint,
+  pub end_coords: GeoPoint,
+  pub requirements: RideRequirements,
+  pub rule: RecurrenceRule,
+}
+
+pub struct BookingRepositoryImpl {
+  pool: Arc<Pool<Postgres>>,
+}
+
+impl BookingRepositoryImpl {
+  pub fn new(database: Arc<Database>) -> Self {
+    Self {
+      pool: database.pool.clone(),
+    }
+  }
+
+  async fn load(
+    &self,
+    row: PgRow,
+  ) -> Result<RecurringBooking, BookingRepositoryError> {
+    let booking = RecurringBooking::try_from(row)?;
+    let changes = sqlx::query(
+      r#"
+        SELECT * FROM recurring_booking_changes WHERE booking_uuid = $1
+        ORDER BY occurs_on
+      "#,
+    )
+    .bind(&booking.uuid)
+    .map(|row: PgRow| OccurrenceChange::from(row))
+    .fetch_all(&*self.pool)
+    .await?;
+    let occurrences = sqlx::query(
+      r#"
+        SELECT * FROM recurring_booking_trips WHERE booking_uuid = $1
+        ORDER BY occurs_on
+      "#,
+    )
+    .bind(&booking.uuid)
+    .map(|row: PgRow| BookedOccurrence {
+      occurs_on: row.get("occurs_on"),
+      trip_uuid: row.get("trip_uuid"),
+    })
+    .fetch_all(&*self.pool)
+    .await?;
+    Ok(RecurringBooking {
+      changes,
+      occurrences,
+      ..booking
+    })
+  }
+}
+
+impl BookingRepository for BookingRepositoryImpl {
+  async fn find_one(&self, uuid: &str) -> Option<RecurringBooking> {
+    let row = sqlx::query("SELECT * FROM recurring_bookings WHERE uuid = $1")
+      .bind(uuid)
+      .fetch_one(&*self.pool)
+      .await
+      .ok()?;
+    self.load(row).await.ok()
+  }
+
+  async fn find_active(&self, today: NaiveDate) -> Vec<RecurringBooking> {
+    let rows = sqlx::query(
+      "SELECT * FROM recurring_bookings WHERE until IS NULL OR until >= $1",
+    )
+    .bind(today)
+    .fetch_all(&*self.pool)
+    .await
+    .unwrap_or_default();
+    let mut bookings = Vec::with_capacity(rows.len());
+    for row in rows {
+      if let Ok(booking) = self.load(row).await {
+        bookings.push(booking);
+      }
+    }
+    bookings
+  }
+
+  async fn create(
+    &self,
+    create_booking: CreateRecurringBooking,
+  ) -> Result<RecurringBooking, BookingRepositoryError> {
+    let query = r#"
+      INSERT INTO recurring_bookings (
+        uuid, consumer_uuid, start_lat, start_lng, end_lat, end_lng,
+        wheelchair_accessible, assistance_dog, child_seat, preferred_language,
+        weekdays, pickup_time, starts_on, until, exception_dates
+      )
+      VALUES (
+        $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+      )
+      RETURNING *
+    "#;
+    let rule = &create_booking.rule;
+    let row = sqlx::query(query)
+      .bind(&create_booking.uuid)
+      .bind(&create_booking.consumer_uuid)
+      .bind(create_booking.start_coords.lat)
+      .bind(create_booking.start_coords.lng)
+      .bind(create_booking.end_coords.lat)
+      .bind(create_booking.end_coords.lng)
+      .bind(create_booking.requirements.wheelchair_accessible)
+      .bind(create_booking.requirements.assistance_dog)
+      .bind(create_booking.requirements.child_seat)
+      .bind(&create_booking.requirements.preferred_language)
+      .bind(serde_json::to_string(&rule.weekdays)?)
+      .bind(rule.pickup_time)
+      .bind(rule.starts_on)
+      .bind(rule.until)
+      .bind(&rule.exception_dates)
+      .fetch_one(&*self.pool)
+      .await?;
+    RecurringBooking::try_from(row)
+  }
+
+  async fn update(
+    &self,
+    booking: RecurringBooking,
+  ) -> Result<RecurringBooking, BookingRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
+    let query = r#"
+      UPDATE recurring_bookings SET
+        updated_at = NOW(), weekdays = $2, pickup_time = $3, starts_on = $4,
+        until = $5, exception_dates = $6
+      WHERE uuid = $1 AND updated_at = $7
+      RETURNING updated_at
+    "#;
+    let Some(row) = sqlx::query(query)
+      .bind(&booking.uuid)
+      .bind(serde_json::to_string(&booking.rule.weekdays)?)
+      .bind(booking.rule.pickup_time)
+      .bind(booking.rule.starts_on)
+      .bind(booking.rule.until)
+      .bind(&booking.rule.exception_dates)
+      .bind(booking.updated_at)
+      .fetch_optional(&mut *transaction)
+      .await?
+    else {
+      return Err(BookingRepositoryError::Changed);
+    };
+    sqlx::query(
+      "DELETE FROM recurring_booking_changes WHERE booking_uuid = $1",
+    )
+    .bind(&booking.uuid)
+    .execute(&mut *transaction)
+    .await?;
+    for change in &booking.changes {
+      let query = r#"
+        INSERT INTO recurring_booking_changes (
+          booking_uuid, occurs_on, pickup_time, start_lat, start_lng,
+          end_lat, end_lng
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+      "#;
+      sqlx::query(query)
+        .bind(&booking.uuid)
+        .bind(change.occurs_on)
+        .bind(change.pickup_time)
+        .bind(change.start_coords.map(|coords| coords.lat))
+        .bind(change.start_coords.map(|coords| coords.lng))
+        .bind(change.end_coords.map(|coords| coords.lat))
+        .bind(change.end_coords.map(|coords| coords.lng))
+        .execute(&mut *transaction)
+        .await?;
+    }
+    sqlx::query("DELETE FROM recurring_booking_trips WHERE booking_uuid = $1")
+      .bind(&booking.uuid)
+      .execute(&mut *transaction)
+      .await?;
+    for occurrence in &booking.occurrences {
+      let query = r#"
+        INSERT INTO recurring_booking_trips (booking_uuid, occurs_on, trip_uuid)
+        VALUES ($1, $2, $3)
+      "#;
+      sqlx::query(query)
+        .bind(&booking.uuid)
+        .bind(occurrence.occurs_on)
+        .bind(&occurrence.trip_uuid)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await?;
+    Ok(RecurringBooking {
+      updated_at: row.get("updated_at"),
+      ..booking
+    })
+  }
+
+  async fn book_occurrence(
+    &self,
+    booking: RecurringBooking,
+    occurrence: BookedOccurrence,
+  ) -> Result<RecurringBooking, BookingRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
+    let query = r#"
+      INSERT INTO recurring_booking_trips (booking_uuid, occurs_on, trip_uuid)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (booking_uuid, occurs_on) DO NOTHING
+    "#;
+    sqlx::query(query)
+      .bind(&booking.uuid)
+      .bind(occurrence.occurs_on)
+      .bind(&occurrence.trip_uuid)
+      .execute(&mut *transaction)
+      .await?;
+    // Edits from a series loaded before this date was booked would drop
+    // it, they have to load the series again
+    let row = sqlx::query(
+      r#"
+        UPDATE recurring_bookings SET updated_at = NOW() WHERE uuid = $1
+        RETURNING updated_at
+      "#,
+    )
+    .bind(&booking.uuid)
+    .fetch_one(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(RecurringBooking {
+      updated_at: row.get("updated_at"),
+      ..booking.book(occurrence)
+    })
+  }
+}
+
+impl TryFrom<PgRow> for RecurringBooking {
+  type Error = BookingRepositoryError;
+
+  fn try_from(row: PgRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      uuid: row.get("uuid"),
+      created_at: row.get::<DateTime<Utc>, _>("created_at"),
+      updated_at: row.get::<DateTime<Utc>, _>("updated_at"),
+      consumer_uuid: row.get("consumer_uuid"),
+      start_coords: GeoPoint {
+        lat: row.get("start_lat"),
+        lng: row.get("start_lng"),
+      },
+      end_coords: GeoPoint {
+        lat: row.get("end_lat"),
+        lng: row.get("end_lng"),
+      },
+      requirements: RideRequirements::from(&row),
+      rule: RecurrenceRule {
+        weekdays: serde_json::from_str(row.get("weekdays"))?,
+        pickup_time: row.get::<NaiveTime, _>("pickup_time"),
+        starts_on: row.get::<NaiveDate, _>("starts_on"),
+        until: row.get("until"),
+        exception_dates: row.get("exception_dates"),
+      },
+      changes: Vec::new(),
+      occurrences: Vec::new(),
+    })
+  }
+}
+
+impl From<PgRow> for OccurrenceChange {
+  fn from(row: PgRow) -> Self {
+    let coords = |lat: &str, lng: &str| {
+      Some(GeoPoint {
+        lat: row.get::<Option<f64>, _>(lat)?,
+        lng: row.get::<Option<f64>, _>(lng)?,
+      })
+    };
+    Self {
+      occurs_on: row.get("occurs_on"),
+      pickup_time: row.get("pickup_time"),
+      start_coords: coords("start_lat", "start_lng"),
+      end_coords: coords("end_lat", "end_lng"),
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use chrono::{NaiveDate, Utc};
+  use std::sync::RwLock;
+
+  use super::{
+    BookingRepository, BookingRepositoryError, CreateRecurringBooking,
+  };
+  use crate::bookings::model::occurrence::BookedOccurrence;
+  use crate::bookings::model::RecurringBooking;
+
+  pub struct InMemoryBookingRepository {
+    pub bookings: RwLock<Vec<RecurringBooking>>,
+  }
+
+  impl InMemoryBookingRepository {
+    pub fn new() -> Self {
+      Self {
+        bookings: RwLock::new(Vec::new()),
+      }
+    }
+  }
+
+  impl BookingRepository for InMemoryBookingRepository {
+    async fn find_one(&self, uuid: &str) -> Option<RecurringBooking> {
+      let bookings = self.bookings.read().unwrap(); // Acquire read lock
+      bookings
+        .iter()
+        .find(|booking| booking.uuid == uuid)
+        .cloned()
+    }
+
+    async fn find_active(&self, today: NaiveDate) -> Vec<RecurringBooking> {
+      let bookings = self.bookings.read().unwrap(); // Acquire read lock
+      bookings
+        .iter()
+        .filter(|booking| booking.rule.until.is_none_or(|until| until >= today))
+        .cloned()
+        .collect()
+    }
+
+    async fn create(
+      &self,
+      create_booking: CreateRecurringBooking,
+    ) -> Result<RecurringBooking, BookingRepositoryError> {
+      let mut bookings = self.bookings.write().unwrap(); // Acquire write lock
+      let booking = RecurringBooking {
+        uuid: create_booking.uuid,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        consumer_uuid: create_booking.consumer_uuid,
+        start_coords: create_booking.start_coords,
+        end_coords: create_booking.end_coords,
+        requirements: create_booking.requirements,
+        rule: create_booking.rule,
+        changes: Vec::new(),
+        occurrences: Vec::new(),
+      };
+      bookings.push(booking.clone());
+      Ok(booking)
+    }
+
+    async fn update(
+      &self,
+      booking: RecurringBooking,
+    ) -> Result<RecurringBooking, BookingRepositoryError> {
+      let mut bookings = self.bookings.write().unwrap(); // Acquire write lock
+      let stored = bookings
+        .iter_mut()
+        .find(|stored| stored.uuid == booking.uuid)
+        .filter(|stored| stored.updated_at == booking.updated_at)
+        .ok_or(BookingRepositoryError::Changed)?;
+      *stored = RecurringBooking {
+        updated_at: Utc::now(),
+        ..booking
+      };
+      Ok(stored.clone())
+    }
+
+    async fn book_occurrence(
+      &self,
+      booking: RecurringBooking,
+      occurrence: BookedOccurrence,
+    ) -> Result<RecurringBooking, BookingRepositoryError> {
+      let mut bookings = self.bookings.write().unwrap(); // Acquire write lock
+      let Some(stored) = bookings
+        .iter_mut()
+        .find(|stored| stored.uuid == booking.uuid)
+      else {
+        return Ok(booking.book(occurrence));
+      };
+      if stored.booked_trip_uuid(occurrence.occurs_on).is_none() {
+        *stored = stored.clone().book(occurrence.clone());
+      }
+      stored.updated_at = Utc::now();
+      Ok(RecurringBooking {
+        updated_at: stored.updated_at,
+        ..booking.book(occurrence)
+      })
+    }
+  }
+}
@@ -0,0 +1 @@
+pub mod recurring_booking_rto;
@@ -0,0 +1,51 @@
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OccurrenceChangeRto {
+  #[serde(rename = "occursOn")]
+  pub occurs_on: NaiveDate,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "pickupTime")]
+  pub pickup_time: Option<NaiveTime>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "startCoords")]
+  pub start_coords: Option<GeoPoint>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "endCoords")]
+  pub end_coords: Option<GeoPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookedOccurrenceRto {
+  #[serde(rename = "occursOn")]
+  pub occurs_on: NaiveDate,
+  #[serde(rename = "tripUuid")]
+  pub trip_uuid: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecurringBookingRto {
+  pub uuid: String,
+  #[serde(rename = "consumerUuid")]
+  pub consumer_uuid: String,
+  #[serde(rename = "startCoords")]
+  pub start_coords: GeoPoint,
+  #[serde(rename = "endCoords")]
+  pub end_coords: GeoPoint,
+  pub requirements: RideRequirements,
+  pub weekdays: Vec<Weekday>,
+  #[serde(rename = "pickupTime")]
+  pub pickup_time: NaiveTime,
+  #[serde(rename = "startsOn")]
+  pub starts_on: NaiveDate,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub until: Option<NaiveDate>,
+  #[serde(rename = "exceptionDates")]
+  pub exception_dates: Vec<NaiveDate>,
+  pub changes: Vec<OccurrenceChangeRto>,
+  pub occurrences: Vec<BookedOccurrenceRto>,
+}
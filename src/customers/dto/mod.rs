@@ -0,0 +1,3 @@
+pub mod get_saved_place_dto;
+pub mod saved_place_dto;
+pub mod update_ride_preferences_dto;
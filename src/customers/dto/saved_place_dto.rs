@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use validator::Validate;
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SavedPlaceDto {
+  #[validate(length(min = 1, max = 64))]
+  pub name: String,
+  #[validate(nested)]
+  pub coords: GeoPoint,
+}
@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateRidePreferencesDto {
+  #[serde(default)]
+  #[serde(rename = "wheelchairAccessible")]
+  pub wheelchair_accessible: bool,
+  #[serde(default)]
+  #[serde(rename = "assistanceDog")]
+  pub assistance_dog: bool,
+  #[serde(default)]
+  #[serde(rename = "childSeat")]
+  pub child_seat: bool,
+  // ISO 639 language code, e.g. "en" or "ga"
+  #[validate(length(min = 2, max = 3))]
+  #[serde(rename = "preferredLanguage")]
+  pub preferred_language: Option<String>,
+}
@@ -0,0 +1,455 @@
+pub mod dto;
+pub mod model;
+pub mod repository;
+pub mod rto;
+
+use actix_web::http::header;
+use actix_web::{web, HttpResponse, Responder};
+use dto::get_saved_place_dto::GetSavedPlaceDto;
+use dto::saved_place_dto::SavedPlaceDto;
+use dto::update_ride_preferences_dto::UpdateRidePreferencesDto;
+use model::saved_place::SavedPlace;
+use repository::customer_repository::{
+  CreateSavedPlace, CustomerRepository, CustomerRepositoryError,
+};
+use rto::ride_preferences_rto::RidePreferencesRto;
+use rto::saved_place_rto::SavedPlaceRto;
+use validator::Validate;
+
+use crate::custom_nanoid;
+use crate::shared::http_error::HttpError;
+use crate::shared::ride_requirements::RideRequirements;
+use crate::shared::role::Role;
+use crate::shared::rto::created_rto::CreatedRto;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+pub async fn get_ride_preferences<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Customer {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let preferences = customer_repository
+    .find_preferences(&auth.uuid)
+    .await
+    .unwrap_or_default();
+  preferences_found(auth.uuid, preferences)
+}
+
+pub async fn update_ride_preferences<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  dto: web::Json<UpdateRidePreferencesDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Customer {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  customer_repository
+    .save_preferences(&auth.uuid, RideRequirements::from(dto.into_inner()))
+    .await
+    .map(|preferences| preferences_found(auth.uuid, preferences))
+    .unwrap_or_else(failed_save_customer)
+}
+
+fn preferences_found(
+  customer_uuid: String,
+  preferences: RideRequirements,
+) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(RidePreferencesRto {
+      customer_uuid,
+      preferences,
+    })
+}
+
+fn failed_save_customer(error: CustomerRepositoryError) -> HttpResponse {
+  match error {
+    CustomerRepositoryError::PlaceNameTaken(_) => HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "place_name_taken",
+        "Place name already taken",
+      )),
+    CustomerRepositoryError::DatabaseError(_) => {
+      HttpResponse::InternalServerError().finish()
+    }
+  }
+}
+
+// Saved places of other customers are reported as not found
+pub async fn find_own_place<CR: CustomerRepository>(
+  customer_repository: &CR,
+  customer_uuid: &str,
+  uuid: &str,
+) -> Option<SavedPlace> {
+  customer_repository
+    .find_place(uuid)
+    .await
+    .filter(|place| place.customer_uuid == customer_uuid)
+}
+
+pub async fn get_saved_places<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Customer {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let places: Vec<SavedPlaceRto> = customer_repository
+    .find_places(&auth.uuid)
+    .await
+    .into_iter()
+    .map(SavedPlaceRto::from)
+    .collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(places)
+}
+
+pub async fn get_saved_place<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  path: web::Path<GetSavedPlaceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  find_own_place(&**customer_repository, &auth.uuid, &path.uuid)
+    .await
+    .ok_or_else(place_not_found)
+    .map(place_found)
+    .unwrap_or_else(|err| err)
+}
+
+pub async fn create_saved_place<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  dto: web::Json<SavedPlaceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Customer {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  customer_repository
+    .create_place(CreateSavedPlace::from(auth, dto.into_inner()))
+    .await
+    .map(place_created)
+    .unwrap_or_else(failed_save_customer)
+}
+
+pub async fn update_saved_place<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  path: web::Path<GetSavedPlaceDto>,
+  dto: web::Json<SavedPlaceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let Some(place) =
+    find_own_place(&**customer_repository, &auth.uuid, &path.uuid).await
+  else {
+    return place_not_found();
+  };
+  let dto = dto.into_inner();
+  customer_repository
+    .update_place(SavedPlace {
+      name: dto.name,
+      coords: dto.coords,
+      ..place
+    })
+    .await
+    .map(place_found)
+    .unwrap_or_else(failed_save_customer)
+}
+
+pub async fn delete_saved_place<CR: CustomerRepository>(
+  customer_repository: web::Data<CR>,
+  path: web::Path<GetSavedPlaceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let Some(place) =
+    find_own_place(&**customer_repository, &auth.uuid, &path.uuid).await
+  else {
+    return place_not_found();
+  };
+  customer_repository
+    .delete_place(&place.uuid)
+    .await
+    .map(|_| HttpResponse::NoContent().finish())
+    .unwrap_or_else(failed_save_customer)
+}
+
+fn place_found(place: SavedPlace) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .append_header((
+      header::LOCATION,
+      format!("/v1/customers/me/places/{}", place.uuid),
+    ))
+    .json(SavedPlaceRto::from(place))
+}
+
+fn place_created(place: SavedPlace) -> HttpResponse {
+  HttpResponse::Created()
+    .content_type("application/json")
+    .append_header((
+      header::LOCATION,
+      format!("/v1/customers/me/places/{}", place.uuid),
+    ))
+    .json(CreatedRto { uuid: place.uuid })
+}
+
+fn place_not_found() -> HttpResponse {
+  HttpResponse::NotFound()
+    .content_type("application/json")
+    .json(HttpError::from("Saved place not found"))
+}
+
+impl CreateSavedPlace {
+  fn from(auth: AccessTokenClaims, dto: SavedPlaceDto) -> Self {
+    Self {
+      uuid: custom_nanoid(),
+      customer_uuid: auth.uuid,
+      name: dto.name,
+      coords: dto.coords,
+    }
+  }
+}
+
+// Transform SavedPlace domain to RTO
+impl From<SavedPlace> for SavedPlaceRto {
+  fn from(place: SavedPlace) -> Self {
+    Self {
+      uuid: place.uuid,
+      name: place.name,
+      coords: place.coords,
+      updated_at: place.updated_at,
+    }
+  }
+}
+
+impl From<UpdateRidePreferencesDto> for RideRequirements {
+  fn from(dto: UpdateRidePreferencesDto) -> Self {
+    Self {
+      wheelchair_accessible: dto.wheelchair_accessible,
+      assistance_dog: dto.assistance_dog,
+      child_seat: dto.child_seat,
+      preferred_language: dto.preferred_language,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{http::StatusCode, HttpRequest};
+  use repository::customer_repository::tests::InMemoryCustomerRepository;
+
+  use crate::custom_nanoid;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::shared::geo_point::GeoPoint;
+
+  use super::*;
+
+  fn customer_claims() -> AccessTokenClaims {
+    AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_update_and_get_ride_preferences() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer_repository =
+      web::Data::from(Arc::new(InMemoryCustomerRepository::new()));
+    let auth = customer_claims();
+
+    let responder = update_ride_preferences(
+      customer_repository.clone(),
+      web::Json(UpdateRidePreferencesDto {
+        wheelchair_accessible: true,
+        assistance_dog: false,
+        child_seat: true,
+        preferred_language: Some(String::from("ga")),
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: RidePreferencesRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert!(rto.preferences.wheelchair_accessible);
+
+    let responder = get_ride_preferences(customer_repository, auth).await;
+    let rto: RidePreferencesRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert!(rto.preferences.wheelchair_accessible);
+    assert!(rto.preferences.child_seat);
+    assert_eq!(rto.preferences.preferred_language.as_deref(), Some("ga"));
+  }
+
+  #[actix_web::test]
+  async fn test_saved_place_crud() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer_repository =
+      web::Data::from(Arc::new(InMemoryCustomerRepository::new()));
+    let auth = customer_claims();
+
+    let responder = create_saved_place(
+      customer_repository.clone(),
+      web::Json(SavedPlaceDto {
+        name: String::from("Home"),
+        coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+      }),
+      auth.clone(),
+    )
+    .await;
+    let created: CreatedRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+
+    let responder = update_saved_place(
+      customer_repository.clone(),
+      web::Path::from(GetSavedPlaceDto {
+        uuid: created.uuid.clone(),
+      }),
+      web::Json(SavedPlaceDto {
+        name: String::from("Home"),
+        coords: GeoPoint {
+          lat: 53.35,
+          lng: -6.26,
+        },
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: SavedPlaceRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(
+      rto.coords,
+      GeoPoint {
+        lat: 53.35,
+        lng: -6.26,
+      }
+    );
+
+    let responder =
+      get_saved_places(customer_repository.clone(), auth.clone()).await;
+    let rtos: Vec<SavedPlaceRto> =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rtos.len(), 1);
+
+    let responder = delete_saved_place(
+      customer_repository.clone(),
+      web::Path::from(GetSavedPlaceDto {
+        uuid: created.uuid.clone(),
+      }),
+      auth.clone(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let responder = get_saved_place(
+      customer_repository,
+      web::Path::from(GetSavedPlaceDto { uuid: created.uuid }),
+      auth,
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::NOT_FOUND).await;
+    assert_eq!(rto.message, "Saved place not found");
+  }
+
+  #[actix_web::test]
+  async fn test_saved_place_duplicate_name_conflict() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer_repository =
+      web::Data::from(Arc::new(InMemoryCustomerRepository::new()));
+    let auth = customer_claims();
+    for name in ["Work", "work"] {
+      let responder = create_saved_place(
+        customer_repository.clone(),
+        web::Json(SavedPlaceDto {
+          name: String::from(name),
+          coords: GeoPoint {
+            lat: 53.3438,
+            lng: -6.2546,
+          },
+        }),
+        auth.clone(),
+      )
+      .await;
+      let response = responder.respond_to(&request);
+      if name == "work" {
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+      }
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_saved_place_of_other_customer_not_found() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer_repository = InMemoryCustomerRepository::new();
+    let place = customer_repository
+      .create_place(CreateSavedPlace {
+        uuid: custom_nanoid(),
+        customer_uuid: custom_nanoid(),
+        name: String::from("Hospital"),
+        coords: GeoPoint {
+          lat: 53.3264,
+          lng: -6.2349,
+        },
+      })
+      .await
+      .unwrap();
+
+    let responder = get_saved_place(
+      web::Data::from(Arc::new(customer_repository)),
+      web::Path::from(GetSavedPlaceDto { uuid: place.uuid }),
+      customer_claims(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+  }
+
+  #[actix_web::test]
+  async fn test_invalid_preferred_language() {
+    let responder = update_ride_preferences(
+      web::Data::from(Arc::new(InMemoryCustomerRepository::new())),
+      web::Json(UpdateRidePreferencesDto {
+        wheelchair_accessible: false,
+        assistance_dog: false,
+        child_seat: false,
+        preferred_language: Some(String::from("irish")),
+      }),
+      customer_claims(),
+    )
+    .await;
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+  }
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedPlace {
+  pub uuid: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub customer_uuid: String,
+  pub name: String,
+  pub coords: GeoPoint,
+}
@@ -0,0 +1,366 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use sqlx::{postgres::PgRow, Pool, Postgres};
+use thiserror::Error;
+
+use crate::customers::model::saved_place::SavedPlace;
+use crate::shared::database::Database;
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+
+#[derive(Debug, Error)]
+pub enum CustomerRepositoryError {
+  #[error("Place name already taken: {0}")]
+  PlaceNameTaken(String),
+
+  #[error("Database error: {0}")]
+  DatabaseError(sqlx::Error),
+}
+
+impl CustomerRepositoryError {
+  // Unique violations come from the customer's place names
+  fn from_sqlx(error: sqlx::Error, name: &str) -> Self {
+    match &error {
+      sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+        Self::PlaceNameTaken(String::from(name))
+      }
+      _ => Self::DatabaseError(error),
+    }
+  }
+}
+
+impl From<sqlx::Error> for CustomerRepositoryError {
+  fn from(error: sqlx::Error) -> Self {
+    Self::DatabaseError(error)
+  }
+}
+
+pub trait CustomerRepository {
+  async fn find_preferences(
+    &self,
+    customer_uuid: &str,
+  ) -> Option<RideRequirements>;
+  async fn save_preferences(
+    &self,
+    customer_uuid: &str,
+    preferences: RideRequirements,
+  ) -> Result<RideRequirements, CustomerRepositoryError>;
+  async fn find_places(&self, customer_uuid: &str) -> Vec<SavedPlace>;
+  async fn find_place(&self, uuid: &str) -> Option<SavedPlace>;
+  async fn create_place(
+    &self,
+    create_place: CreateSavedPlace,
+  ) -> Result<SavedPlace, CustomerRepositoryError>;
+  async fn update_place(
+    &self,
+    place: SavedPlace,
+  ) -> Result<SavedPlace, CustomerRepositoryError>;
+  async fn delete_place(
+    &self,
+    uuid: &str,
+  ) -> Result<(), CustomerRepositoryError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateSavedPlace {
+  pub uuid: String,
+  pub customer_uuid: String,
+  pub name: String,
+  pub coords: GeoPoint,
+}
+
+pub struct CustomerRepositoryImpl {
+  pool: Arc<Pool<Postgres>>,
+}
+
+impl CustomerRepositoryImpl {
+  pub fn new(database: Arc<Database>) -> Self {
+    Self {
+      pool: database.pool.clone(),
+    }
+  }
+}
+
+impl CustomerRepository for CustomerRepositoryImpl {
+  async fn find_preferences(
+    &self,
+    customer_uuid: &str,
+  ) -> Option<RideRequirements> {
+    sqlx::query(
+      "SELECT * FROM customer_preferences WHERE customer_uuid = $1 LIMIT 1",
+    )
+    .bind(customer_uuid)
+    .map(|row: PgRow| RideRequirements::from(&row))
+    .fetch_optional(&*self.pool)
+    .await
+    .ok()
+    .flatten()
+  }
+
+  async fn save_preferences(
+    &self,
+    customer_uuid: &str,
+    preferences: RideRequirements,
+  ) -> Result<RideRequirements, CustomerRepositoryError> {
+    let query = r#"
+      INSERT INTO customer_preferences
+        (customer_uuid, wheelchair_accessible, assistance_dog, child_seat,
+         preferred_language)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (customer_uuid) DO UPDATE SET
+        wheelchair_accessible = EXCLUDED.wheelchair_accessible,
+        assistance_dog = EXCLUDED.assistance_dog,
+        child_seat = EXCLUDED.child_seat,
+        preferred_language = EXCLUDED.preferred_language
+      RETURNING *
+    "#;
+    sqlx::query(query)
+      .bind(customer_uuid)
+      .bind(preferences.wheelchair_accessible)
+      .bind(preferences.assistance_dog)
+      .bind(preferences.child_seat)
+      .bind(&preferences.preferred_language)
+      .map(|row: PgRow| RideRequirements::from(&row))
+      .fetch_one(&*self.pool)
+      .await
+      .map_err(CustomerRepositoryError::from)
+  }
+
+  async fn find_places(&self, customer_uuid: &str) -> Vec<SavedPlace> {
+    sqlx::query(
+      "SELECT * FROM saved_places WHERE customer_uuid = $1 ORDER BY name",
+    )
+    .bind(customer_uuid)
+    .map(|row: PgRow| SavedPlace::from(row))
+    .fetch_all(&*self.pool)
+    .await
+    .unwrap_or_default()
+  }
+
+  async fn find_place(&self, uuid: &str) -> Option<SavedPlace> {
+    sqlx::query("SELECT * FROM saved_places WHERE uuid = $1 LIMIT 1")
+      .bind(uuid)
+      .map(|row: PgRow| SavedPlace::from(row))
+      .fetch_one(&*self.pool)
+      .await
+      .ok()
+  }
+
+  async fn create_place(
+    &self,
+    create_place: CreateSavedPlace,
+  ) -> Result<SavedPlace, CustomerRepositoryError> {
+    let query = r#"
+      INSERT INTO saved_places (uuid, customer_uuid, name, lat, lng)
+      VALUES ($1, $2, $3, $4, $5)
+      RETURNING *
+    "#;
+    sqlx::query(query)
+      .bind(&create_place.uuid)
+      .bind(&create_place.customer_uuid)
+      .bind(&create_place.name)
+      .bind(create_place.coords.lat)
+      .bind(create_place.coords.lng)
+      .map(|row: PgRow| SavedPlace::from(row))
+      .fetch_one(&*self.pool)
+      .await
+      .map_err(|error| {
+        CustomerRepositoryError::from_sqlx(error, &create_place.name)
+      })
+  }
+
+  async fn update_place(
+    &self,
+    place: SavedPlace,
+  ) -> Result<SavedPlace, CustomerRepositoryError> {
+    let query = r#"
+      UPDATE saved_places
+      SET name = $2, lat = $3, lng = $4, updated_at = NOW()
+      WHERE uuid = $1
+      RETURNING *
+    "#;
+    sqlx::query(query)
+      .bind(&place.uuid)
+      .bind(&place.name)
+      .bind(place.coords.lat)
+      .bind(place.coords.lng)
+      .map(|row: PgRow| SavedPlace::from(row))
+      .fetch_one(&*self.pool)
+      .await
+      .map_err(|error| CustomerRepositoryError::from_sqlx(error, &place.name))
+  }
+
+  async fn delete_place(
+    &self,
+    uuid: &str,
+  ) -> Result<(), CustomerRepositoryError> {
+    sqlx::query("DELETE FROM saved_places WHERE uuid = $1")
+      .bind(uuid)
+      .execute(&*self.pool)
+      .await?;
+    Ok(())
+  }
+}
+
+impl From<PgRow> for SavedPlace {
+  fn from(row: PgRow) -> Self {
+    Self {
+      uuid: row.get("uuid"),
+      created_at: row.get::<DateTime<Utc>, _>("created_at"),
+      updated_at: row.get::<DateTime<Utc>, _>("updated_at"),
+      customer_uuid: row.get("customer_uuid"),
+      name: row.get("name"),
+      coords: GeoPoint {
+        lat: row.get("lat"),
+        lng: row.get("lng"),
+      },
+    }
+  }
+}
+
+// Shared by every table storing ride requirements columns
+impl From<&PgRow> for RideRequirements {
+  fn from(row: &PgRow) -> Self {
+    Self {
+      wheelchair_accessible: row.get("wheelchair_accessible"),
+      assistance_dog: row.get("assistance_dog"),
+      child_seat: row.get("child_seat"),
+      preferred_language: row.get("preferred_language"),
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use chrono::Utc;
+  use std::collections::HashMap;
+  use std::sync::RwLock;
+
+  use super::{CreateSavedPlace, CustomerRepository, CustomerRepositoryError};
+  use crate::customers::model::saved_place::SavedPlace;
+  use crate::shared::ride_requirements::RideRequirements;
+
+  pub struct InMemoryCustomerRepository {
+    pub preferences: RwLock<HashMap<String, RideRequirements>>,
+    pub places: RwLock<Vec<SavedPlace>>,
+  }
+
+  impl InMemoryCustomerRepository {
+    pub fn new() -> Self {
+      Self {
+        preferences: RwLock::new(HashMap::new()),
+        places: RwLock::new(Vec::new()),
+      }
+    }
+
+    // Mirror the case-insensitive unique index on place names
+    fn ensure_name_free(
+      places: &[SavedPlace],
+      customer_uuid: &str,
+      name: &str,
+      uuid: &str,
+    ) -> Result<(), CustomerRepositoryError> {
+      let taken = places.iter().any(|place| {
+        place.uuid != uuid
+          && place.customer_uuid == customer_uuid
+          && place.name.to_lowercase() == name.to_lowercase()
+      });
+      if taken {
+        return Err(CustomerRepositoryError::PlaceNameTaken(String::from(
+          name,
+        )));
+      }
+      Ok(())
+    }
+  }
+
+  impl CustomerRepository for InMemoryCustomerRepository {
+    async fn find_preferences(
+      &self,
+      customer_uuid: &str,
+    ) -> Option<RideRequirements> {
+      let preferences = self.preferences.read().unwrap(); // Acquire read lock
+      preferences.get(customer_uuid).cloned()
+    }
+
+    async fn save_preferences(
+      &self,
+      customer_uuid: &str,
+      preferences: RideRequirements,
+    ) -> Result<RideRequirements, CustomerRepositoryError> {
+      let mut stored = self.preferences.write().unwrap(); // Acquire write lock
+      stored.insert(String::from(customer_uuid), preferences.clone());
+      Ok(preferences)
+    }
+
+    async fn find_places(&self, customer_uuid: &str) -> Vec<SavedPlace> {
+      let places = self.places.read().unwrap(); // Acquire read lock
+      let mut places: Vec<SavedPlace> = places
+        .iter()
+        .filter(|place| place.customer_uuid == customer_uuid)
+        .cloned()
+        .collect();
+      places.sort_by(|a, b| a.name.cmp(&b.name));
+      places
+    }
+
+    async fn find_place(&self, uuid: &str) -> Option<SavedPlace> {
+      let places = self.places.read().unwrap(); // Acquire read lock
+      places.iter().find(|place| place.uuid == uuid).cloned()
+    }
+
+    async fn create_place(
+      &self,
+      create_place: CreateSavedPlace,
+    ) -> Result<SavedPlace, CustomerRepositoryError> {
+      let mut places = self.places.write().unwrap(); // Acquire write lock
+      Self::ensure_name_free(
+        &places,
+        &create_place.customer_uuid,
+        &create_place.name,
+        &create_place.uuid,
+      )?;
+      let place = SavedPlace {
+        uuid: create_place.uuid,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        customer_uuid: create_place.customer_uuid,
+        name: create_place.name,
+        coords: create_place.coords,
+      };
+      places.push(place.clone());
+      Ok(place)
+    }
+
+    async fn update_place(
+      &self,
+      place: SavedPlace,
+    ) -> Result<SavedPlace, CustomerRepositoryError> {
+      let mut places = self.places.write().unwrap(); // Acquire write lock
+      Self::ensure_name_free(
+        &places,
+        &place.customer_uuid,
+        &place.name,
+        &place.uuid,
+      )?;
+      let place = SavedPlace {
+        updated_at: Utc::now(),
+        ..place
+      };
+      places.retain(|existing| existing.uuid != place.uuid);
+      places.push(place.clone());
+      Ok(place)
+    }
+
+    async fn delete_place(
+      &self,
+      uuid: &str,
+    ) -> Result<(), CustomerRepositoryError> {
+      let mut places = self.places.write().unwrap(); // Acquire write lock
+      places.retain(|place| place.uuid != uuid);
+      Ok(())
+    }
+  }
+}
@@ -0,0 +1,2 @@
+pub mod ride_preferences_rto;
+pub mod saved_place_rto;
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::ride_requirements::RideRequirements;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RidePreferencesRto {
+  #[serde(rename = "customerUuid")]
+  pub customer_uuid: String,
+  #[serde(flatten)]
+  pub preferences: RideRequirements,
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedPlaceRto {
+  pub uuid: String,
+  pub name: String,
+  pub coords: GeoPoint,
+  #[serde(rename = "updatedAt")]
+  pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use super::dispatcher::run_dispatch;
+use crate::drivers::location_store::DriverLocationStore;
+use crate::drivers::repository::driver_repository::DriverRepository;
+use crate::shared::config::Config;
+use crate::trips::repository::trip_repository::TripRepository;
+
+pub fn spawn_dispatch_monitor<
+  TR: TripRepository + 'static,
+  DR: DriverRepository + 'static,
+>(
+  trip_repository: Arc<TR>,
+  driver_repository: Arc<DR>,
+  location_store: Arc<DriverLocationStore>,
+  config: Config,
+) {
+  actix_web::rt::spawn(async move {
+    // Several runs per offer timeout keep the delay between an expired
+    // offer and the next one short
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(
+      (config.dispatch.offer_timeout_secs / 4).max(1) as u64,
+    ));
+    loop {
+      interval.tick().await;
+      run_dispatch(
+        &*trip_repository,
+        &*driver_repository,
+        &location_store,
+        &config,
+        Utc::now(),
+      )
+      .await;
+    }
+  });
+}
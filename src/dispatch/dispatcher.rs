@@ -0,0 +1,582 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::drivers::compliance::compliance_report;
+use crate::drivers::location_store::DriverLocationStore;
+use crate::drivers::matching::is_eligible;
+use crate::drivers::repository::driver_repository::DriverRepository;
+use crate::ranks::rank_queues::RankRules;
+use crate::shared::config::{env_or, Config};
+use crate::shared::spatial_index::Nearby;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::model::Trip;
+use crate::trips::repository::trip_repository::{
+  TripRepository, TripRepositoryError,
+};
+
+// Statuses in which a driver is busy with a trip
+pub const ACTIVE_STATUSES: [TripStatus; 4] = [
+  TripStatus::Offered,
+  TripStatus::Accepted,
+  TripStatus::DriverArrived,
+  TripStatus::InProgress,
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DispatchRules {
+  // How long a driver has to accept an offer
+  pub offer_timeout_secs: i64,
+  // Drivers further away from the pickup are never offered the trip
+  pub max_pickup_distance_km: f64,
+  // Requested trips nobody accepted in time expire
+  pub request_ttl_secs: i64,
+  // Closest drivers considered for each offer
+  pub max_candidates: usize,
+}
+
+impl Default for DispatchRules {
+  fn default() -> Self {
+    Self {
+      offer_timeout_secs: env_or("DISPATCH_OFFER_TIMEOUT_SECS", 20),
+      max_pickup_distance_km: env_or("DISPATCH_MAX_PICKUP_DISTANCE_KM", 10.0),
+      request_ttl_secs: env_or("DISPATCH_REQUEST_TTL_SECS", 600),
+      max_candidates: env_or("DISPATCH_MAX_CANDIDATES", 10),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+  pub driver_uuid: String,
+  pub distance_km: f64,
+}
+
+// Available drivers near the pickup with a suitable vehicle, closest
+// first, after the drivers queued at a rank serving the pickup in queue
+// order. Drivers who already had an offer for the trip are skipped so a
+// decline or timeout moves on to the next one. Only drivers on the
+// location feed are indexed, their status is checked again against the
+// repository since it is the source of truth.
+pub async fn find_candidates<DR: DriverRepository>(
+  driver_repository: &DR,
+  location_store: &DriverLocationStore,
+  rules: &DispatchRules,
+  ranks: &RankRules,
+  trip: &Trip,
+) -> Vec<Candidate> {
+  let queued = location_store
+    .queued_for(ranks, &trip.start_coords)
+    .into_iter()
+    .filter_map(|driver_uuid| {
+      let fix = location_store.latest(&driver_uuid)?;
+      Some(Nearby {
+        distance_km: fix.coords.distance_km(&trip.start_coords),
+        id: driver_uuid,
+      })
+    });
+  let nearby = location_store.nearest_available(
+    &trip.start_coords,
+    rules.max_candidates + trip.offers.len(),
+    rules.max_pickup_distance_km,
+  );
+  let mut candidates: Vec<Candidate> = Vec::new();
+  for nearby in queued.chain(nearby) {
+    let is_candidate = candidates
+      .iter()
+      .any(|candidate| candidate.driver_uuid == nearby.id);
+    if is_candidate
+      || trip.was_offered_to(&nearby.id)
+      || !is_eligible(driver_repository, &nearby.id, &trip.requirements).await
+    {
+      continue;
+    }
+    candidates.push(Candidate {
+      driver_uuid: nearby.id,
+      distance_km: nearby.distance_km,
+    });
+  }
+  candidates.truncate(rules.max_candidates);
+  candidates
+}
+
+// Drivers with a pending offer or on another trip
+pub async fn is_busy<TR: TripRepository>(
+  trip_repository: &TR,
+  driver_uuid: &str,
+) -> bool {
+  !trip_repository
+    .find_for_driver(driver_uuid, &ACTIVE_STATUSES)
+    .await
+    .is_empty()
+}
+
+// Drivers on another trip or over their driving hours get no offers
+pub async fn can_take_offer<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: &TR,
+  driver_repository: &DR,
+  config: &Config,
+  driver_uuid: &str,
+  now: DateTime<Utc>,
+) -> bool {
+  if is_busy(trip_repository, driver_uuid).await {
+    return false;
+  }
+  compliance_report(driver_repository, &config.compliance, driver_uuid, now)
+    .await
+    .can_take_trips()
+}
+
+// Offers a requested trip to the closest available driver. The trip is
+// left requested when nobody is available, the dispatch monitor retries
+// until it expires.
+pub async fn offer_next<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: &TR,
+  driver_repository: &DR,
+  location_store: &DriverLocationStore,
+  config: &Config,
+  trip: Trip,
+  now: DateTime<Utc>,
+) -> Result<Trip, TripRepositoryError> {
+  if trip.status != TripStatus::Requested {
+    return Ok(trip);
+  }
+  let rules = &config.dispatch;
+  let waiting = now - trip.dispatch_started_at();
+  if waiting > Duration::seconds(rules.request_ttl_secs) {
+    return match trip.clone().transition(TripStatus::Expired, None, now) {
+      Ok(expired) => {
+        trip_repository.update(expired, TripStatus::Requested).await
+      }
+      Err(_) => Ok(trip),
+    };
+  }
+  let candidates = find_candidates(
+    driver_repository,
+    location_store,
+    rules,
+    &config.ranks,
+    &trip,
+  )
+  .await;
+  for candidate in candidates {
+    if !can_take_offer(
+      trip_repository,
+      driver_repository,
+      config,
+      &candidate.driver_uuid,
+      now,
+    )
+    .await
+    {
+      continue;
+    }
+    let expires_at = now + Duration::seconds(rules.offer_timeout_secs);
+    return match trip.clone().offer(
+      &candidate.driver_uuid,
+      None,
+      now,
+      expires_at,
+    ) {
+      Ok(offered) => {
+        trip_repository.update(offered, TripStatus::Requested).await
+      }
+      Err(_) => Ok(trip),
+    };
+  }
+  Ok(trip)
+}
+
+// Expires unanswered offers and re-offers every requested trip
+pub async fn run_dispatch<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: &TR,
+  driver_repository: &DR,
+  location_store: &DriverLocationStore,
+  config: &Config,
+  now: DateTime<Utc>,
+) -> usize {
+  let mut expired_offers = 0;
+  for trip in trip_repository.find_by_status(&[TripStatus::Offered]).await {
+    let timed_out = trip
+      .pending_offer()
+      .is_some_and(|offer| offer.expires_at <= now);
+    if !timed_out {
+      continue;
+    }
+    let Ok(trip) = trip.expire_offer(now) else {
+      continue;
+    };
+    if trip_repository
+      .update(trip, TripStatus::Offered)
+      .await
+      .is_ok()
+    {
+      expired_offers += 1;
+    }
+  }
+  for trip in trip_repository
+    .find_by_status(&[TripStatus::Requested])
+    .await
+  {
+    // Errors are retried on the next run
+    let _ = offer_next(
+      trip_repository,
+      driver_repository,
+      location_store,
+      config,
+      trip,
+      now,
+    )
+    .await;
+  }
+  expired_offers
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::drivers::model::availability::DriverAvailability;
+  use crate::drivers::model::driver_status::DriverStatus;
+  use crate::drivers::model::location_fix::LocationFix;
+  use crate::drivers::model::vehicle::Vehicle;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+  use crate::ranks::model::taxi_rank::TaxiRank;
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::ride_requirements::RideRequirements;
+  use crate::trips::model::trip_offer::OfferOutcome;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+  use crate::trips::repository::trip_repository::CreateTrip;
+
+  // O'Connell Bridge, Dublin
+  const PICKUP: GeoPoint = GeoPoint {
+    lat: 53.3472,
+    lng: -6.2592,
+  };
+
+  async fn driver_repository(
+    drivers: &[(&str, Option<GeoPoint>)],
+  ) -> InMemoryDriverRepository {
+    let now = Utc::now();
+    let driver_repository = InMemoryDriverRepository::from(
+      drivers
+        .iter()
+        .map(|(driver_uuid, location)| DriverAvailability {
+          location: *location,
+          ..DriverAvailability::offline(driver_uuid, now)
+            .transition(DriverStatus::Online, now)
+            .unwrap()
+        })
+        .collect::<Vec<DriverAvailability>>(),
+    );
+    for (driver_uuid, _) in drivers {
+      driver_repository
+        .save_vehicle(Vehicle {
+          driver_uuid: String::from(*driver_uuid),
+          plate: String::from("241-D-12345"),
+          wheelchair_accessible: false,
+          assistance_dog: true,
+          child_seat: false,
+          spsv_licence: None,
+        })
+        .await
+        .unwrap();
+    }
+    driver_repository
+  }
+
+  // Drivers as they would be after going online and sending a fix
+  fn location_store(
+    drivers: &[(&str, Option<GeoPoint>)],
+  ) -> DriverLocationStore {
+    let location_store = DriverLocationStore::new();
+    for (driver_uuid, location) in drivers {
+      location_store.set_available(driver_uuid, true);
+      if let Some(coords) = location {
+        location_store.record(LocationFix {
+          driver_uuid: String::from(*driver_uuid),
+          coords: *coords,
+          accuracy_m: 10.0,
+          heading_deg: None,
+          speed_mps: None,
+          recorded_at: Utc::now(),
+        });
+      }
+    }
+    location_store
+  }
+
+  async fn requested_trip(trip_repository: &InMemoryTripRepository) -> Trip {
+    trip_repository
+      .create(CreateTrip {
+        uuid: String::from("trip"),
+        start_coords: PICKUP,
+        end_coords: GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        },
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: String::from("customer"),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+        booking: None,
+      })
+      .await
+      .unwrap()
+  }
+
+  fn near(km_north: f64) -> Option<GeoPoint> {
+    // One degree of latitude is about 111 km
+    Some(GeoPoint {
+      lat: PICKUP.lat + km_north / 111.0,
+      lng: PICKUP.lng,
+    })
+  }
+
+  #[actix_web::test]
+  async fn test_candidates_are_sorted_by_distance() {
+    let drivers = [
+      ("far", near(5.0)),
+      ("close", near(1.0)),
+      ("too_far", near(25.0)),
+      ("unknown", None),
+    ];
+    let driver_repository = driver_repository(&drivers).await;
+    let location_store = location_store(&drivers);
+    let trip = requested_trip(&InMemoryTripRepository::new()).await;
+    let rules = DispatchRules::default();
+
+    let candidates = find_candidates(
+      &driver_repository,
+      &location_store,
+      &rules,
+      &RankRules::default(),
+      &trip,
+    )
+    .await;
+    let driver_uuids: Vec<&str> = candidates
+      .iter()
+      .map(|candidate| candidate.driver_uuid.as_str())
+      .collect();
+    assert_eq!(driver_uuids, vec!["close", "far"]);
+  }
+
+  #[actix_web::test]
+  async fn test_drivers_gone_offline_are_skipped() {
+    let drivers = [("online", near(3.0)), ("went_offline", near(1.0))];
+    let driver_repository = driver_repository(&drivers).await;
+    // The store has not caught up with the status change yet
+    let location_store = location_store(&drivers);
+    let availability = driver_repository.find_one("went_offline").await;
+    driver_repository
+      .save(
+        availability
+          .unwrap()
+          .transition(DriverStatus::Offline, Utc::now())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    let trip = requested_trip(&InMemoryTripRepository::new()).await;
+    let rules = DispatchRules::default();
+
+    let candidates = find_candidates(
+      &driver_repository,
+      &location_store,
+      &rules,
+      &RankRules::default(),
+      &trip,
+    )
+    .await;
+    let driver_uuids: Vec<&str> = candidates
+      .iter()
+      .map(|candidate| candidate.driver_uuid.as_str())
+      .collect();
+    assert_eq!(driver_uuids, vec!["online"]);
+  }
+
+  #[actix_web::test]
+  async fn test_rank_queue_goes_before_closer_drivers() {
+    let drivers = [
+      ("passing", near(0.01)),
+      ("queued_second", near(0.08)),
+      ("queued_first", near(0.08)),
+    ];
+    let driver_repository = driver_repository(&drivers).await;
+    let location_store = location_store(&[drivers[0]]);
+    let ranks = RankRules {
+      join_after_secs: 60,
+      leave_margin_m: 50.0,
+      taxi_ranks: vec![TaxiRank {
+        code: String::from("oconnell"),
+        name: String::from("O'Connell Street"),
+        center: PICKUP,
+        radius_m: 100.0,
+        pickup_radius_m: 300.0,
+      }],
+    };
+    for (minutes_ago, (driver_uuid, location)) in
+      [(5, drivers[2]), (3, drivers[1])]
+    {
+      location_store.set_available(driver_uuid, true);
+      for recorded_at in
+        [Utc::now() - Duration::minutes(minutes_ago), Utc::now()]
+      {
+        location_store.record(LocationFix {
+          driver_uuid: String::from(driver_uuid),
+          coords: location.unwrap(),
+          accuracy_m: 10.0,
+          heading_deg: None,
+          speed_mps: None,
+          recorded_at,
+        });
+        location_store.track_ranks(&ranks, driver_uuid, true);
+      }
+    }
+    location_store.track_ranks(&ranks, "passing", true);
+    let trip = requested_trip(&InMemoryTripRepository::new()).await;
+    let rules = DispatchRules::default();
+
+    let candidates = find_candidates(
+      &driver_repository,
+      &location_store,
+      &rules,
+      &ranks,
+      &trip,
+    )
+    .await;
+    let driver_uuids: Vec<&str> = candidates
+      .iter()
+      .map(|candidate| candidate.driver_uuid.as_str())
+      .collect();
+    assert_eq!(
+      driver_uuids,
+      vec!["queued_first", "queued_second", "passing"]
+    );
+  }
+
+  #[actix_web::test]
+  async fn test_unanswered_offer_moves_to_next_driver() {
+    let drivers = [("far", near(5.0)), ("close", near(1.0))];
+    let driver_repository = driver_repository(&drivers).await;
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = requested_trip(&trip_repository).await;
+    let location_store = location_store(&drivers);
+    let config = Config::default();
+    let now = trip.created_at;
+
+    let trip = offer_next(
+      &trip_repository,
+      &driver_repository,
+      &location_store,
+      &config,
+      trip,
+      now,
+    )
+    .await
+    .unwrap();
+    assert_eq!(trip.status, TripStatus::Offered);
+    assert!(trip.is_assigned_to("close"));
+
+    // Nothing happens until the offer times out
+    assert_eq!(
+      run_dispatch(
+        &trip_repository,
+        &driver_repository,
+        &location_store,
+        &config,
+        now
+      )
+      .await,
+      0
+    );
+    let later = now + Duration::seconds(config.dispatch.offer_timeout_secs);
+    assert_eq!(
+      run_dispatch(
+        &trip_repository,
+        &driver_repository,
+        &location_store,
+        &config,
+        later
+      )
+      .await,
+      1
+    );
+
+    let trip = trip_repository.find_one("trip").await.unwrap();
+    assert_eq!(trip.status, TripStatus::Offered);
+    assert!(trip.is_assigned_to("far"));
+    let outcomes: Vec<OfferOutcome> =
+      trip.offers.iter().map(|offer| offer.outcome).collect();
+    assert_eq!(outcomes, vec![OfferOutcome::Expired, OfferOutcome::Pending]);
+  }
+
+  #[actix_web::test]
+  async fn test_busy_drivers_are_skipped() {
+    let drivers = [("busy", near(1.0)), ("idle", near(3.0))];
+    let driver_repository = driver_repository(&drivers).await;
+    let trip_repository = InMemoryTripRepository::new();
+    let location_store = location_store(&drivers);
+    let config = Config::default();
+    let now = Utc::now();
+    let busy_trip = trip_repository
+      .create(CreateTrip {
+        uuid: String::from("busy_trip"),
+        start_coords: PICKUP,
+        end_coords: PICKUP,
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: String::from("other"),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+        booking: None,
+      })
+      .await
+      .unwrap();
+    let busy_trip = busy_trip
+      .offer("busy", None, now, now + Duration::seconds(20))
+      .unwrap();
+    trip_repository
+      .update(busy_trip, TripStatus::Requested)
+      .await
+      .unwrap();
+    let trip = requested_trip(&trip_repository).await;
+
+    let trip = offer_next(
+      &trip_repository,
+      &driver_repository,
+      &location_store,
+      &config,
+      trip,
+      now,
+    )
+    .await
+    .unwrap();
+    assert!(trip.is_assigned_to("idle"));
+  }
+
+  #[actix_web::test]
+  async fn test_unserved_trip_expires() {
+    let driver_repository = driver_repository(&[]).await;
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = requested_trip(&trip_repository).await;
+    let location_store = DriverLocationStore::new();
+    let config = Config::default();
+    let later =
+      trip.created_at + Duration::seconds(config.dispatch.request_ttl_secs + 1);
+
+    run_dispatch(
+      &trip_repository,
+      &driver_repository,
+      &location_store,
+      &config,
+      later,
+    )
+    .await;
+    let trip = trip_repository.find_one("trip").await.unwrap();
+    assert_eq!(trip.status, TripStatus::Expired);
+  }
+}
@@ -0,0 +1,164 @@
+pub mod dispatch_monitor;
+pub mod dispatcher;
+pub mod rto;
+pub mod scheduler;
+
+use actix_web::{web, HttpResponse, Responder};
+use rto::driver_offer_rto::DriverOfferRto;
+use rto::trip_offer_rto::TripOfferRto;
+use validator::Validate;
+
+use crate::shared::http_error::HttpError;
+use crate::shared::role::Role;
+use crate::trips::dto::get_trip_dto::GetTripDto;
+use crate::trips::model::trip_offer::TripOffer;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::repository::trip_repository::TripRepository;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+// Offer history of a trip, for dispatchers looking into unserved trips
+pub async fn get_trip_offers<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Admin && auth.role != Role::Manager {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let Some(trip) = trip_repository.find_one(&path.uuid).await else {
+    return HttpResponse::NotFound()
+      .content_type("application/json")
+      .json(HttpError::from("Trip not found"));
+  };
+  let offers: Vec<TripOfferRto> =
+    trip.offers.into_iter().map(TripOfferRto::from).collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(offers)
+}
+
+// The trip currently offered to the calling driver
+pub async fn get_current_offer<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  trip_repository
+    .find_for_driver(&auth.uuid, &[TripStatus::Offered])
+    .await
+    .into_iter()
+    .find_map(|trip| {
+      let offer = trip.pending_offer()?.clone();
+      Some(DriverOfferRto {
+        trip_uuid: trip.uuid,
+        start_coords: trip.start_coords,
+        end_coords: trip.end_coords,
+        requirements: trip.requirements,
+        offered_at: offer.offered_at,
+        expires_at: offer.expires_at,
+      })
+    })
+    .map(|rto| {
+      HttpResponse::Ok()
+        .content_type("application/json")
+        .json(rto)
+    })
+    .unwrap_or_else(|| {
+      HttpResponse::NotFound()
+        .content_type("application/json")
+        .json(HttpError::from("No pending offer"))
+    })
+}
+
+// Transform TripOffer domain to RTO
+impl From<TripOffer> for TripOfferRto {
+  fn from(offer: TripOffer) -> Self {
+    Self {
+      driver_uuid: offer.driver_uuid,
+      offered_at: offer.offered_at,
+      expires_at: offer.expires_at,
+      outcome: offer.outcome,
+      responded_at: offer.responded_at,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::{Duration, Utc};
+
+  use super::*;
+  use crate::custom_nanoid;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::ride_requirements::RideRequirements;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+  use crate::trips::repository::trip_repository::CreateTrip;
+
+  #[actix_web::test]
+  async fn test_get_current_offer() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = trip_repository
+      .create(CreateTrip {
+        uuid: custom_nanoid(),
+        start_coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+        end_coords: GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        },
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: custom_nanoid(),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+        booking: None,
+      })
+      .await
+      .unwrap();
+    let now = Utc::now();
+    let expires_at = now + Duration::seconds(20);
+    let trip = trip.offer(&driver.uuid, None, now, expires_at).unwrap();
+    let trip = trip_repository
+      .update(trip, TripStatus::Requested)
+      .await
+      .unwrap();
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+
+    let responder =
+      get_current_offer(trip_repository.clone(), driver.clone()).await;
+    let rto: DriverOfferRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.trip_uuid, trip.uuid);
+    assert_eq!(rto.expires_at, expires_at);
+
+    let other_driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let responder = get_current_offer(trip_repository, other_driver).await;
+    let _: HttpError =
+      parse_http_response(responder, &request, StatusCode::NOT_FOUND).await;
+  }
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+
+// What a driver sees before accepting or declining a trip
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverOfferRto {
+  #[serde(rename = "tripUuid")]
+  pub trip_uuid: String,
+  #[serde(rename = "startCoords")]
+  pub start_coords: GeoPoint,
+  #[serde(rename = "endCoords")]
+  pub end_coords: GeoPoint,
+  pub requirements: RideRequirements,
+  #[serde(rename = "offeredAt")]
+  pub offered_at: DateTime<Utc>,
+  #[serde(rename = "expiresAt")]
+  pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,2 @@
+pub mod driver_offer_rto;
+pub mod trip_offer_rto;
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::trips::model::trip_offer::OfferOutcome;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripOfferRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  #[serde(rename = "offeredAt")]
+  pub offered_at: DateTime<Utc>,
+  #[serde(rename = "expiresAt")]
+  pub expires_at: DateTime<Utc>,
+  pub outcome: OfferOutcome,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "respondedAt")]
+  pub responded_at: Option<DateTime<Utc>>,
+}
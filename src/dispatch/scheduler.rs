@@ -0,0 +1,424 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Europe::Dublin;
+use serde::{Deserialize, Serialize};
+
+use super::dispatcher::{can_take_offer, offer_next};
+use crate::drivers::location_store::DriverLocationStore;
+use crate::drivers::matching::is_eligible;
+use crate::drivers::repository::driver_repository::DriverRepository;
+use crate::shared::config::{env_or, Config};
+use crate::shared::notifier::{Notification, Notifier};
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::model::Trip;
+use crate::trips::repository::trip_repository::{
+  TripRepository, TripRepositoryError,
+};
+
+const SCHEDULER_INTERVAL_SECS: u64 = 30;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SchedulingRules {
+  // Pre-booked pickups must be at least this far ahead
+  pub min_lead_secs: i64,
+  // and at most this far ahead
+  pub max_ahead_secs: i64,
+  // Scheduled trips go into dispatch this long before pickup
+  pub dispatch_lead_secs: i64,
+  // Customers and pre-assigned drivers are reminded this long before
+  pub reminder_lead_secs: i64,
+  // Trips of recurring bookings are created this far ahead
+  pub materialise_ahead_secs: i64,
+}
+
+impl Default for SchedulingRules {
+  fn default() -> Self {
+    Self {
+      min_lead_secs: env_or("SCHEDULING_MIN_LEAD_SECS", 1800),
+      max_ahead_secs: env_or("SCHEDULING_MAX_AHEAD_SECS", 30 * 24 * 3600),
+      dispatch_lead_secs: env_or("SCHEDULING_DISPATCH_LEAD_SECS", 900),
+      reminder_lead_secs: env_or("SCHEDULING_REMINDER_LEAD_SECS", 3600),
+      materialise_ahead_secs: env_or(
+        "SCHEDULING_MATERIALISE_AHEAD_SECS",
+        7 * 24 * 3600,
+      ),
+    }
+  }
+}
+
+impl SchedulingRules {
+  pub fn is_within_booking_window(
+    &self,
+    pickup_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+  ) -> bool {
+    pickup_at >= now + Duration::seconds(self.min_lead_secs)
+      && pickup_at <= now + Duration::seconds(self.max_ahead_secs)
+  }
+}
+
+pub fn spawn_trip_scheduler<
+  TR: TripRepository + 'static,
+  DR: DriverRepository + 'static,
+  N: Notifier + 'static,
+>(
+  trip_repository: Arc<TR>,
+  driver_repository: Arc<DR>,
+  location_store: Arc<DriverLocationStore>,
+  notifier: Arc<N>,
+  config: Config,
+) {
+  actix_web::rt::spawn(async move {
+    let mut interval = actix_web::rt::time::interval(
+      std::time::Duration::from_secs(SCHEDULER_INTERVAL_SECS),
+    );
+    loop {
+      interval.tick().await;
+      run_scheduler(
+        &*trip_repository,
+        &*driver_repository,
+        &location_store,
+        &*notifier,
+        &config,
+        Utc::now(),
+      )
+      .await;
+    }
+  });
+}
+
+// Sends pickup reminders and releases scheduled trips into dispatch once
+// the pickup is close, returns the number of trips released
+pub async fn run_scheduler<
+  TR: TripRepository,
+  DR: DriverRepository,
+  N: Notifier,
+>(
+  trip_repository: &TR,
+  driver_repository: &DR,
+  location_store: &DriverLocationStore,
+  notifier: &N,
+  config: &Config,
+  now: DateTime<Utc>,
+) -> usize {
+  let rules = &config.scheduling;
+  let mut released = 0;
+  for trip in trip_repository
+    .find_by_status(&[TripStatus::Scheduled])
+    .await
+  {
+    let Some(pickup_at) = trip.pickup_at else {
+      continue;
+    };
+    let mut trip = trip;
+    let remind_from = pickup_at - Duration::seconds(rules.reminder_lead_secs);
+    if trip.reminded_at.is_none() && remind_from <= now {
+      // Errors are retried on the next run
+      let Ok(reminded) = remind(trip_repository, notifier, trip, now).await
+      else {
+        continue;
+      };
+      trip = reminded;
+    }
+    if pickup_at - Duration::seconds(rules.dispatch_lead_secs) > now {
+      continue;
+    }
+    let Some(trip) =
+      release(trip_repository, driver_repository, config, trip, now).await
+    else {
+      continue;
+    };
+    released += 1;
+    // Requested trips are offered right away instead of waiting for the
+    // dispatch monitor
+    let _ = offer_next(
+      trip_repository,
+      driver_repository,
+      location_store,
+      config,
+      trip,
+      now,
+    )
+    .await;
+  }
+  released
+}
+
+async fn remind<TR: TripRepository, N: Notifier>(
+  trip_repository: &TR,
+  notifier: &N,
+  trip: Trip,
+  now: DateTime<Utc>,
+) -> Result<Trip, TripRepositoryError> {
+  // Saved first so a failed update never sends the reminder twice
+  let trip = trip_repository
+    .update(
+      Trip {
+        reminded_at: Some(now),
+        ..trip
+      },
+      TripStatus::Scheduled,
+    )
+    .await?;
+  let pickup = trip
+    .pickup_at
+    .unwrap_or(now)
+    .with_timezone(&Dublin)
+    .format("%H:%M on %-d %B");
+  notifier
+    .notify(Notification {
+      user_uuid: trip.consumer_uuid.clone(),
+      message: format!("Reminder: your taxi is booked for {}", pickup),
+    })
+    .await;
+  if let Some(driver_uuid) = &trip.driver_uuid {
+    notifier
+      .notify(Notification {
+        user_uuid: driver_uuid.clone(),
+        message: format!(
+          "Reminder: you have a pre-booked pickup at {}",
+          pickup
+        ),
+      })
+      .await;
+  }
+  Ok(trip)
+}
+
+// The pre-assigned driver gets the first offer when they can take it,
+// otherwise the trip is dispatched like any other
+async fn release<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: &TR,
+  driver_repository: &DR,
+  config: &Config,
+  trip: Trip,
+  now: DateTime<Utc>,
+) -> Option<Trip> {
+  let mut available_driver = None;
+  if let Some(driver_uuid) = &trip.driver_uuid {
+    if is_eligible(driver_repository, driver_uuid, &trip.requirements).await
+      && can_take_offer(
+        trip_repository,
+        driver_repository,
+        config,
+        driver_uuid,
+        now,
+      )
+      .await
+    {
+      available_driver = Some(driver_uuid.clone());
+    }
+  }
+  let released = match available_driver {
+    Some(driver_uuid) => trip.offer(
+      &driver_uuid,
+      None,
+      now,
+      now + Duration::seconds(config.dispatch.offer_timeout_secs),
+    ),
+    None => trip
+      .transition(TripStatus::Requested, None, now)
+      .map(|trip| Trip {
+        driver_uuid: None,
+        ..trip
+      }),
+  };
+  trip_repository
+    .update(released.ok()?, TripStatus::Scheduled)
+    .await
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dispatch::dispatcher::run_dispatch;
+  use crate::drivers::model::availability::DriverAvailability;
+  use crate::drivers::model::driver_status::DriverStatus;
+  use crate::drivers::model::vehicle::Vehicle;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::notifier::tests::RecordingNotifier;
+  use crate::shared::ride_requirements::RideRequirements;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+  use crate::trips::repository::trip_repository::CreateTrip;
+
+  async fn online_driver(driver_uuid: &str) -> InMemoryDriverRepository {
+    let now = Utc::now();
+    let driver_repository =
+      InMemoryDriverRepository::from(vec![DriverAvailability::offline(
+        driver_uuid,
+        now,
+      )
+      .transition(DriverStatus::Online, now)
+      .unwrap()]);
+    driver_repository
+      .save_vehicle(Vehicle {
+        driver_uuid: String::from(driver_uuid),
+        plate: String::from("241-D-12345"),
+        wheelchair_accessible: false,
+        assistance_dog: true,
+        child_seat: false,
+        spsv_licence: None,
+      })
+      .await
+      .unwrap();
+    driver_repository
+  }
+
+  async fn scheduled_trip(
+    trip_repository: &InMemoryTripRepository,
+    pickup_at: DateTime<Utc>,
+  ) -> Trip {
+    trip_repository
+      .create(CreateTrip {
+        uuid: String::from("trip"),
+        start_coords: GeoPoint {
+          lat: 53.3472,
+          lng: -6.2592,
+        },
+        end_coords: GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        },
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: String::from("customer"),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: Some(pickup_at),
+        booking: None,
+      })
+      .await
+      .unwrap()
+  }
+
+  #[test]
+  fn test_booking_window() {
+    let rules = SchedulingRules::default();
+    let now = Utc::now();
+    assert!(!rules.is_within_booking_window(now + Duration::minutes(10), now));
+    assert!(rules.is_within_booking_window(now + Duration::hours(2), now));
+    assert!(!rules.is_within_booking_window(now + Duration::days(31), now));
+  }
+
+  #[actix_web::test]
+  async fn test_scheduled_trip_is_released_before_pickup() {
+    let trip_repository = InMemoryTripRepository::new();
+    let driver_repository = InMemoryDriverRepository::new();
+    let location_store = DriverLocationStore::new();
+    let notifier = RecordingNotifier::new();
+    let config = Config::default();
+    let now = Utc::now();
+    let pickup_at = now + Duration::hours(2);
+    let trip = scheduled_trip(&trip_repository, pickup_at).await;
+    assert_eq!(trip.status, TripStatus::Scheduled);
+
+    let run = |now| {
+      run_scheduler(
+        &trip_repository,
+        &driver_repository,
+        &location_store,
+        &notifier,
+        &config,
+        now,
+      )
+    };
+    assert_eq!(run(now).await, 0);
+    let released_at =
+      pickup_at - Duration::seconds(config.scheduling.dispatch_lead_secs);
+    assert_eq!(run(released_at).await, 1);
+    let trip = trip_repository.find_one("trip").await.unwrap();
+    assert_eq!(trip.status, TripStatus::Requested);
+    assert_eq!(trip.dispatch_started_at(), released_at);
+
+    // The request TTL runs from the release, not from the booking
+    run_dispatch(
+      &trip_repository,
+      &driver_repository,
+      &location_store,
+      &config,
+      released_at + Duration::seconds(1),
+    )
+    .await;
+    let trip = trip_repository.find_one("trip").await.unwrap();
+    assert_eq!(trip.status, TripStatus::Requested);
+  }
+
+  #[actix_web::test]
+  async fn test_pre_assigned_driver_gets_first_offer() {
+    let trip_repository = InMemoryTripRepository::new();
+    let driver_repository = online_driver("driver").await;
+    let location_store = DriverLocationStore::new();
+    let notifier = RecordingNotifier::new();
+    let config = Config::default();
+    let now = Utc::now();
+    let pickup_at = now + Duration::minutes(10);
+    let trip = scheduled_trip(&trip_repository, pickup_at)
+      .await
+      .pre_assign("driver", now)
+      .unwrap();
+    trip_repository
+      .update(trip, TripStatus::Scheduled)
+      .await
+      .unwrap();
+
+    let released = run_scheduler(
+      &trip_repository,
+      &driver_repository,
+      &location_store,
+      &notifier,
+      &config,
+      now,
+    )
+    .await;
+    assert_eq!(released, 1);
+    let trip = trip_repository.find_one("trip").await.unwrap();
+    assert_eq!(trip.status, TripStatus::Offered);
+    assert!(trip.is_assigned_to("driver"));
+    assert_eq!(trip.offers.len(), 1);
+  }
+
+  #[actix_web::test]
+  async fn test_reminders_are_sent_once() {
+    let trip_repository = InMemoryTripRepository::new();
+    let driver_repository = online_driver("driver").await;
+    let location_store = DriverLocationStore::new();
+    let notifier = RecordingNotifier::new();
+    let config = Config::default();
+    let now = Utc::now();
+    let trip = scheduled_trip(&trip_repository, now + Duration::minutes(50))
+      .await
+      .pre_assign("driver", now)
+      .unwrap();
+    trip_repository
+      .update(trip, TripStatus::Scheduled)
+      .await
+      .unwrap();
+
+    for _ in 0..2 {
+      run_scheduler(
+        &trip_repository,
+        &driver_repository,
+        &location_store,
+        &notifier,
+        &config,
+        now,
+      )
+      .await;
+    }
+    let recipients: Vec<String> = notifier
+      .sent
+      .read()
+      .unwrap()
+      .iter()
+      .map(|notification| notification.user_uuid.clone())
+      .collect();
+    assert_eq!(recipients, vec!["customer", "driver"]);
+    let trip = trip_repository.find_one("trip").await.unwrap();
+    assert_eq!(trip.status, TripStatus::Scheduled);
+    assert_eq!(trip.reminded_at, Some(now));
+  }
+}
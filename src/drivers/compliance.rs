@@ -0,0 +1,312 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::model::shift::Shift;
+use super::repository::driver_repository::DriverRepository;
+use crate::shared::config::env_or;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ComplianceRules {
+  pub max_continuous_driving_mins: i64,
+  // Shorter pauses do not reset continuous driving time
+  pub min_break_mins: i64,
+  pub max_driving_24h_mins: i64,
+  pub max_driving_7d_mins: i64,
+  // Drivers are warned when this close to any limit
+  pub warning_threshold_mins: i64,
+}
+
+impl Default for ComplianceRules {
+  fn default() -> Self {
+    Self {
+      max_continuous_driving_mins: env_or(
+        "COMPLIANCE_MAX_CONTINUOUS_DRIVING_MINS",
+        270,
+      ),
+      min_break_mins: env_or("COMPLIANCE_MIN_BREAK_MINS", 45),
+      max_driving_24h_mins: env_or("COMPLIANCE_MAX_DRIVING_24H_MINS", 660),
+      max_driving_7d_mins: env_or("COMPLIANCE_MAX_DRIVING_7D_MINS", 3600),
+      warning_threshold_mins: env_or("COMPLIANCE_WARNING_THRESHOLD_MINS", 30),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum ComplianceLimit {
+  #[serde(rename = "continuous_driving")]
+  ContinuousDriving,
+  #[serde(rename = "driving_24h")]
+  Driving24h,
+  #[serde(rename = "driving_7d")]
+  Driving7d,
+}
+
+// Ordered from no concern to over the limit
+#[derive(
+  Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum LimitState {
+  #[serde(rename = "ok")]
+  Ok,
+  #[serde(rename = "warning")]
+  Warning,
+  #[serde(rename = "exceeded")]
+  Exceeded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitUsage {
+  pub limit: ComplianceLimit,
+  pub used: Duration,
+  pub max: Duration,
+  pub state: LimitState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplianceReport {
+  pub driver_uuid: String,
+  pub generated_at: DateTime<Utc>,
+  pub limits: Vec<LimitUsage>,
+}
+
+impl ComplianceReport {
+  pub fn can_take_trips(&self) -> bool {
+    self
+      .limits
+      .iter()
+      .all(|usage| usage.state != LimitState::Exceeded)
+  }
+
+  pub fn warnings(&self) -> Vec<LimitUsage> {
+    self
+      .limits
+      .iter()
+      .filter(|usage| usage.state != LimitState::Ok)
+      .cloned()
+      .collect()
+  }
+}
+
+// Periods actually spent working: shifts minus their breaks, open
+// shifts and breaks running until `now`.
+fn working_periods(
+  shifts: &[Shift],
+  now: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+  let mut periods = Vec::new();
+  for shift in shifts {
+    let shift_end = shift.ended_at.unwrap_or(now);
+    let mut cursor = shift.started_at;
+    for shift_break in &shift.breaks {
+      let break_end = shift_break.ended_at.unwrap_or(shift_end);
+      if shift_break.started_at > cursor {
+        periods.push((cursor, shift_break.started_at));
+      }
+      cursor = cursor.max(break_end);
+    }
+    if shift_end > cursor {
+      periods.push((cursor, shift_end));
+    }
+  }
+  periods.sort();
+  periods
+}
+
+fn continuous_driving(
+  periods: &[(DateTime<Utc>, DateTime<Utc>)],
+  min_break: Duration,
+  now: DateTime<Utc>,
+) -> Duration {
+  let mut total = Duration::zero();
+  let mut resumed_at = now;
+  for (start, end) in periods.iter().rev() {
+    if resumed_at - *end >= min_break {
+      break;
+    }
+    total += *end - *start;
+    resumed_at = *start;
+  }
+  total
+}
+
+fn driving_since(
+  periods: &[(DateTime<Utc>, DateTime<Utc>)],
+  since: DateTime<Utc>,
+) -> Duration {
+  periods
+    .iter()
+    .filter(|(_, end)| *end > since)
+    .map(|(start, end)| *end - (*start).max(since))
+    .fold(Duration::zero(), |total, period| total + period)
+}
+
+pub fn evaluate(
+  rules: &ComplianceRules,
+  driver_uuid: &str,
+  shifts: &[Shift],
+  now: DateTime<Utc>,
+) -> ComplianceReport {
+  let periods = working_periods(shifts, now);
+  let warning_threshold = Duration::minutes(rules.warning_threshold_mins);
+  let usage = |limit: ComplianceLimit, used: Duration, max_mins: i64| {
+    let max = Duration::minutes(max_mins);
+    let state = if used >= max {
+      LimitState::Exceeded
+    } else if max - used <= warning_threshold {
+      LimitState::Warning
+    } else {
+      LimitState::Ok
+    };
+    LimitUsage {
+      limit,
+      used,
+      max,
+      state,
+    }
+  };
+  ComplianceReport {
+    driver_uuid: String::from(driver_uuid),
+    generated_at: now,
+    limits: vec![
+      usage(
+        ComplianceLimit::ContinuousDriving,
+        continuous_driving(
+          &periods,
+          Duration::minutes(rules.min_break_mins),
+          now,
+        ),
+        rules.max_continuous_driving_mins,
+      ),
+      usage(
+        ComplianceLimit::Driving24h,
+        driving_since(&periods, now - Duration::hours(24)),
+        rules.max_driving_24h_mins,
+      ),
+      usage(
+        ComplianceLimit::Driving7d,
+        driving_since(&periods, now - Duration::days(7)),
+        rules.max_driving_7d_mins,
+      ),
+    ],
+  }
+}
+
+// Loads the shifts covering the longest rolling window and evaluates them
+pub async fn compliance_report<DR: DriverRepository>(
+  driver_repository: &DR,
+  rules: &ComplianceRules,
+  driver_uuid: &str,
+  now: DateTime<Utc>,
+) -> ComplianceReport {
+  let shifts = driver_repository
+    .find_shifts(driver_uuid, now - Duration::days(7))
+    .await;
+  evaluate(rules, driver_uuid, &shifts, now)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::drivers::model::shift::ShiftBreak;
+
+  fn rules() -> ComplianceRules {
+    ComplianceRules {
+      max_continuous_driving_mins: 270,
+      min_break_mins: 45,
+      max_driving_24h_mins: 660,
+      max_driving_7d_mins: 3600,
+      warning_threshold_mins: 30,
+    }
+  }
+
+  fn shift(
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    breaks: Vec<ShiftBreak>,
+  ) -> Shift {
+    Shift {
+      uuid: String::from("shift"),
+      driver_uuid: String::from("driver"),
+      started_at,
+      ended_at,
+      breaks,
+    }
+  }
+
+  fn used(report: &ComplianceReport, limit: ComplianceLimit) -> LimitUsage {
+    report
+      .limits
+      .iter()
+      .find(|usage| usage.limit == limit)
+      .cloned()
+      .unwrap()
+  }
+
+  #[test]
+  fn test_short_break_does_not_reset_continuous_driving() {
+    let now = Utc::now();
+    let shifts = vec![shift(
+      now - Duration::minutes(260),
+      None,
+      vec![ShiftBreak {
+        started_at: now - Duration::hours(2),
+        ended_at: Some(now - Duration::hours(2) + Duration::minutes(10)),
+      }],
+    )];
+
+    let report = evaluate(&rules(), "driver", &shifts, now);
+
+    let continuous = used(&report, ComplianceLimit::ContinuousDriving);
+    assert_eq!(continuous.used, Duration::minutes(250));
+    assert_eq!(continuous.state, LimitState::Warning);
+    assert!(report.can_take_trips());
+    assert_eq!(report.warnings().len(), 1);
+  }
+
+  #[test]
+  fn test_long_break_resets_continuous_driving() {
+    let now = Utc::now();
+    let shifts = vec![shift(
+      now - Duration::hours(6),
+      None,
+      vec![ShiftBreak {
+        started_at: now - Duration::hours(2),
+        ended_at: Some(now - Duration::hours(1)),
+      }],
+    )];
+
+    let report = evaluate(&rules(), "driver", &shifts, now);
+
+    let continuous = used(&report, ComplianceLimit::ContinuousDriving);
+    assert_eq!(continuous.used, Duration::hours(1));
+    assert_eq!(
+      used(&report, ComplianceLimit::Driving24h).used,
+      Duration::hours(5)
+    );
+    assert!(report.can_take_trips());
+  }
+
+  #[test]
+  fn test_rolling_window_limit_exceeded() {
+    let now = Utc::now();
+    let shifts = vec![
+      shift(
+        now - Duration::hours(20),
+        Some(now - Duration::hours(14)),
+        vec![],
+      ),
+      shift(
+        now - Duration::hours(7),
+        Some(now - Duration::hours(1)),
+        vec![],
+      ),
+    ];
+
+    let report = evaluate(&rules(), "driver", &shifts, now);
+
+    let daily = used(&report, ComplianceLimit::Driving24h);
+    assert_eq!(daily.used, Duration::hours(12));
+    assert_eq!(daily.state, LimitState::Exceeded);
+    assert!(!report.can_take_trips());
+  }
+}
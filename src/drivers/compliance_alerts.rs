@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::compliance::{
+  ComplianceLimit, ComplianceReport, LimitState, LimitUsage,
+};
+
+// Limit states each driver was last seen in, so a warning is pushed once
+// when a threshold is crossed instead of on every heartbeat. Kept in
+// memory, a restart warns again at most once.
+#[derive(Default)]
+pub struct ComplianceAlerts {
+  states: RwLock<HashMap<String, HashMap<ComplianceLimit, LimitState>>>,
+}
+
+impl ComplianceAlerts {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // Limits that reached a worse state since the last report. Dropping back
+  // below a threshold is remembered so crossing it again warns again.
+  pub fn crossed(&self, report: &ComplianceReport) -> Vec<LimitUsage> {
+    let mut states = self.states.write().unwrap(); // Acquire write lock
+    let driver_states = states.entry(report.driver_uuid.clone()).or_default();
+    let mut crossed = Vec::new();
+    for usage in &report.limits {
+      let previous = driver_states
+        .insert(usage.limit, usage.state)
+        .unwrap_or(LimitState::Ok);
+      if usage.state > previous {
+        crossed.push(usage.clone());
+      }
+    }
+    crossed
+  }
+}
+
+// Text pushed to the driver's device
+pub fn warning_message(usage: &LimitUsage) -> String {
+  let limit = match usage.limit {
+    ComplianceLimit::ContinuousDriving => "continuous driving",
+    ComplianceLimit::Driving24h => "24 hour driving",
+    ComplianceLimit::Driving7d => "7 day driving",
+  };
+  match usage.state {
+    LimitState::Exceeded => {
+      format!("You have reached your {} limit, take a break", limit)
+    }
+    _ => format!(
+      "{} minutes left before your {} limit",
+      (usage.max - usage.used).num_minutes(),
+      limit
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{Duration, Utc};
+
+  use super::*;
+
+  fn report(state: LimitState) -> ComplianceReport {
+    ComplianceReport {
+      driver_uuid: String::from("driver"),
+      generated_at: Utc::now(),
+      limits: vec![LimitUsage {
+        limit: ComplianceLimit::ContinuousDriving,
+        used: Duration::minutes(250),
+        max: Duration::minutes(270),
+        state,
+      }],
+    }
+  }
+
+  #[test]
+  fn test_each_threshold_is_crossed_once() {
+    let alerts = ComplianceAlerts::new();
+
+    assert!(alerts.crossed(&report(LimitState::Ok)).is_empty());
+    let crossed = alerts.crossed(&report(LimitState::Warning));
+    assert_eq!(crossed.len(), 1);
+    assert_eq!(
+      warning_message(&crossed[0]),
+      "20 minutes left before your continuous driving limit"
+    );
+    assert!(alerts.crossed(&report(LimitState::Warning)).is_empty());
+    assert_eq!(alerts.crossed(&report(LimitState::Exceeded)).len(), 1);
+    assert!(alerts.crossed(&report(LimitState::Exceeded)).is_empty());
+
+    // A break resets the limit, the next crossing warns again
+    assert!(alerts.crossed(&report(LimitState::Ok)).is_empty());
+    assert_eq!(alerts.crossed(&report(LimitState::Warning)).len(), 1);
+  }
+}
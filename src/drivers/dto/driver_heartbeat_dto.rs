@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use validator::Validate;
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct DriverHeartbeatDto {
+  #[validate(nested)]
+  pub location: Option<GeoPoint>,
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+
+// Serialize is needed to report the batch in length validation errors
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct LocationFixDto {
+  #[serde(flatten)]
+  #[validate(nested)]
+  pub coords: GeoPoint,
+  // Meters
+  #[validate(range(min = 0.0, max = 10000.0))]
+  pub accuracy: f64,
+  // Degrees clockwise from true north
+  #[validate(range(min = 0.0, max = 360.0))]
+  pub heading: Option<f64>,
+  // Meters per second
+  #[validate(range(min = 0.0, max = 100.0))]
+  pub speed: Option<f64>,
+  // Device time the fix was taken at
+  pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct DriverLocationsDto {
+  #[validate(length(min = 1, max = 100), nested)]
+  pub fixes: Vec<LocationFixDto>,
+}
@@ -0,0 +1,5 @@
+pub mod driver_heartbeat_dto;
+pub mod driver_locations_dto;
+pub mod get_driver_compliance_dto;
+pub mod update_driver_status_dto;
+pub mod update_vehicle_dto;
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+use crate::drivers::model::driver_status::DriverStatus;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateDriverStatusDto {
+  pub status: DriverStatus,
+}
@@ -0,0 +1,20 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateVehicleDto {
+  #[validate(length(min = 1, max = 16))]
+  pub plate: String,
+  #[serde(default)]
+  #[serde(rename = "wheelchairAccessible")]
+  pub wheelchair_accessible: bool,
+  #[serde(default)]
+  #[serde(rename = "assistanceDog")]
+  pub assistance_dog: bool,
+  #[serde(default)]
+  #[serde(rename = "childSeat")]
+  pub child_seat: bool,
+  #[validate(length(min = 1, max = 16))]
+  #[serde(rename = "spsvLicence")]
+  pub spsv_licence: Option<String>,
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::location_store::DriverLocationStore;
+use super::model::driver_status::DriverStatus;
+use super::repository::driver_repository::DriverRepository;
+
+// Puts drivers who missed their heartbeat offline. The shift is closed
+// at the last heartbeat, since nothing proves the driver kept working.
+pub async fn expire_stale_drivers<DR: DriverRepository>(
+  driver_repository: &DR,
+  location_store: &DriverLocationStore,
+  heartbeat_timeout: chrono::Duration,
+  now: DateTime<Utc>,
+) -> usize {
+  let mut expired = 0;
+  for availability in
+    driver_repository.find_stale(now - heartbeat_timeout).await
+  {
+    let last_heartbeat_at = availability.last_heartbeat_at;
+    let Ok(availability) =
+      availability.transition(DriverStatus::Offline, last_heartbeat_at)
+    else {
+      continue;
+    };
+    let driver_uuid = availability.driver_uuid.clone();
+    if driver_repository.save(availability).await.is_ok() {
+      location_store.set_available(&driver_uuid, false);
+      expired += 1;
+    }
+  }
+  expired
+}
+
+pub fn spawn_heartbeat_monitor<DR: DriverRepository + 'static>(
+  driver_repository: Arc<DR>,
+  location_store: Arc<DriverLocationStore>,
+  heartbeat_timeout_secs: u64,
+) {
+  let heartbeat_timeout =
+    chrono::Duration::seconds(heartbeat_timeout_secs as i64);
+  actix_web::rt::spawn(async move {
+    // Check at twice the timeout frequency so drivers go offline close
+    // to the moment they miss a heartbeat.
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(
+      (heartbeat_timeout_secs / 2).max(1),
+    ));
+    loop {
+      interval.tick().await;
+      expire_stale_drivers(
+        &*driver_repository,
+        &location_store,
+        heartbeat_timeout,
+        Utc::now(),
+      )
+      .await;
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::Duration;
+
+  use super::*;
+  use crate::drivers::model::availability::DriverAvailability;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+
+  #[actix_web::test]
+  async fn test_expire_stale_drivers() {
+    let now = Utc::now();
+    let stale_at = now - Duration::minutes(5);
+    let stale = DriverAvailability::offline("stale", stale_at)
+      .transition(DriverStatus::Online, stale_at)
+      .unwrap();
+    let fresh = DriverAvailability::offline("fresh", now)
+      .transition(DriverStatus::Online, now)
+      .unwrap();
+    let driver_repository = InMemoryDriverRepository::from(vec![stale, fresh]);
+
+    let expired = expire_stale_drivers(
+      &driver_repository,
+      &DriverLocationStore::new(),
+      Duration::seconds(90),
+      now,
+    )
+    .await;
+    assert_eq!(expired, 1);
+
+    let stale = driver_repository.find_one("stale").await.unwrap();
+    assert_eq!(stale.status, DriverStatus::Offline);
+    assert_eq!(stale.shift.unwrap().ended_at, Some(stale_at));
+    let fresh = driver_repository.find_one("fresh").await.unwrap();
+    assert_eq!(fresh.status, DriverStatus::Online);
+  }
+}
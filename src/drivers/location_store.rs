@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use super::model::location_fix::LocationFix;
+use crate::ranks::rank_queues::{QueuedDriver, RankQueues, RankRules};
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::spatial_index::{Nearby, SpatialIndex};
+
+// Latest fix of every driver, kept in memory so dispatch never hits the
+// database for positions. The history lives in the driver repository.
+#[derive(Default)]
+pub struct DriverLocationStore {
+  locations: RwLock<Locations>,
+}
+
+#[derive(Default)]
+struct Locations {
+  latest: HashMap<String, LocationFix>,
+  available: HashSet<String>,
+  // Positions of available drivers only
+  index: SpatialIndex,
+  ranks: RankQueues,
+}
+
+impl DriverLocationStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // Out of order fixes never replace a newer position
+  pub fn record(&self, fix: LocationFix) -> bool {
+    let mut locations = self.locations.write().unwrap(); // Acquire write lock
+    let is_newer = locations
+      .latest
+      .get(&fix.driver_uuid)
+      .is_none_or(|current| current.recorded_at < fix.recorded_at);
+    if !is_newer {
+      return false;
+    }
+    if locations.available.contains(&fix.driver_uuid) {
+      locations.index.insert(&fix.driver_uuid, fix.coords);
+    }
+    locations.latest.insert(fix.driver_uuid.clone(), fix);
+    true
+  }
+
+  // Follows the driver status, only online drivers can be found nearby
+  pub fn set_available(&self, driver_uuid: &str, available: bool) {
+    let mut locations = self.locations.write().unwrap(); // Acquire write lock
+    if !available {
+      locations.available.remove(driver_uuid);
+      locations.index.remove(driver_uuid);
+      locations.ranks.leave(driver_uuid);
+      return;
+    }
+    locations.available.insert(String::from(driver_uuid));
+    if let Some(coords) =
+      locations.latest.get(driver_uuid).map(|fix| fix.coords)
+    {
+      locations.index.insert(driver_uuid, coords);
+    }
+  }
+
+  pub fn latest(&self, driver_uuid: &str) -> Option<LocationFix> {
+    let locations = self.locations.read().unwrap(); // Acquire read lock
+    locations.latest.get(driver_uuid).cloned()
+  }
+
+  // Queues the driver at the rank they wait at from their latest fix,
+  // or takes them out of it. Only available drivers free to take a trip
+  // are queued.
+  pub fn track_ranks(&self, rules: &RankRules, driver_uuid: &str, free: bool) {
+    let mut locations = self.locations.write().unwrap(); // Acquire write lock
+    let Some(fix) = locations.latest.get(driver_uuid).cloned() else {
+      return;
+    };
+    let free = free && locations.available.contains(driver_uuid);
+    locations.ranks.observe(rules, &fix, free);
+  }
+
+  pub fn rank_queue(&self, rank_code: &str) -> Vec<QueuedDriver> {
+    let locations = self.locations.read().unwrap(); // Acquire read lock
+    locations.ranks.queue(rank_code)
+  }
+
+  pub fn queued_for(
+    &self,
+    rules: &RankRules,
+    pickup: &GeoPoint,
+  ) -> Vec<String> {
+    let locations = self.locations.read().unwrap(); // Acquire read lock
+    locations.ranks.queued_for(rules, pickup)
+  }
+
+  pub fn nearest_available(
+    &self,
+    point: &GeoPoint,
+    k: usize,
+    radius_km: f64,
+  ) -> Vec<Nearby> {
+    let locations = self.locations.read().unwrap(); // Acquire read lock
+    locations.index.nearest(point, k, radius_km)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{Duration, Utc};
+
+  use super::*;
+
+  fn fix() -> LocationFix {
+    LocationFix {
+      driver_uuid: String::from("driver"),
+      coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      accuracy_m: 5.0,
+      heading_deg: Some(90.0),
+      speed_mps: Some(8.3),
+      recorded_at: Utc::now(),
+    }
+  }
+
+  #[test]
+  fn test_keeps_newest_fix() {
+    let fix = fix();
+    let store = DriverLocationStore::new();
+    store.set_available("driver", true);
+    assert!(store.record(fix.clone()));
+    assert!(!store.record(LocationFix {
+      coords: GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      },
+      recorded_at: fix.recorded_at - Duration::seconds(10),
+      ..fix.clone()
+    }));
+    let nearby = store.nearest_available(&fix.coords, 1, 100.0);
+    assert_eq!(nearby[0].distance_km, 0.0);
+  }
+
+  #[test]
+  fn test_only_available_drivers_are_nearby() {
+    let fix = fix();
+    let store = DriverLocationStore::new();
+    store.record(fix.clone());
+    assert!(store.nearest_available(&fix.coords, 5, 1.0).is_empty());
+
+    store.set_available("driver", true);
+    let nearby = store.nearest_available(&fix.coords, 5, 1.0);
+    assert_eq!(nearby.len(), 1);
+    assert_eq!(nearby[0].id, "driver");
+
+    store.set_available("driver", false);
+    assert!(store.nearest_available(&fix.coords, 5, 1.0).is_empty());
+  }
+}
@@ -0,0 +1,109 @@
+use super::model::availability::DriverAvailability;
+use super::model::driver_status::DriverStatus;
+use super::model::vehicle::Vehicle;
+use super::repository::driver_repository::DriverRepository;
+use crate::shared::ride_requirements::RideRequirements;
+
+// Online drivers whose vehicle meets the accessibility needs of a ride.
+// Drivers without a registered vehicle are never offered trips.
+pub async fn eligible_drivers<DR: DriverRepository>(
+  driver_repository: &DR,
+  requirements: &RideRequirements,
+) -> Vec<(DriverAvailability, Vehicle)> {
+  let mut eligible = Vec::new();
+  for availability in driver_repository.find_online().await {
+    let Some(vehicle) = driver_repository
+      .find_vehicle(&availability.driver_uuid)
+      .await
+    else {
+      continue;
+    };
+    if vehicle.satisfies(requirements) {
+      eligible.push((availability, vehicle));
+    }
+  }
+  eligible
+}
+
+// Same checks as `eligible_drivers` for a single driver
+pub async fn is_eligible<DR: DriverRepository>(
+  driver_repository: &DR,
+  driver_uuid: &str,
+  requirements: &RideRequirements,
+) -> bool {
+  let online = driver_repository
+    .find_one(driver_uuid)
+    .await
+    .is_some_and(|availability| availability.status == DriverStatus::Online);
+  online
+    && has_suitable_vehicle(driver_repository, driver_uuid, requirements).await
+}
+
+// Vehicle check alone, for work assigned while the driver is offline
+pub async fn has_suitable_vehicle<DR: DriverRepository>(
+  driver_repository: &DR,
+  driver_uuid: &str,
+  requirements: &RideRequirements,
+) -> bool {
+  driver_repository
+    .find_vehicle(driver_uuid)
+    .await
+    .is_some_and(|vehicle| vehicle.satisfies(requirements))
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::Utc;
+
+  use super::*;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+
+  fn online(driver_uuid: &str) -> DriverAvailability {
+    DriverAvailability::offline(driver_uuid, Utc::now())
+      .transition(DriverStatus::Online, Utc::now())
+      .unwrap()
+  }
+
+  fn vehicle(driver_uuid: &str, wheelchair_accessible: bool) -> Vehicle {
+    Vehicle {
+      driver_uuid: String::from(driver_uuid),
+      plate: String::from("241-D-12345"),
+      wheelchair_accessible,
+      assistance_dog: true,
+      child_seat: false,
+      spsv_licence: None,
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_only_matching_vehicles_are_eligible() {
+    let driver_repository = InMemoryDriverRepository::from(vec![
+      online("accessible"),
+      online("saloon"),
+      online("no_vehicle"),
+    ]);
+    driver_repository
+      .save_vehicle(vehicle("accessible", true))
+      .await
+      .unwrap();
+    driver_repository
+      .save_vehicle(vehicle("saloon", false))
+      .await
+      .unwrap();
+
+    let eligible = eligible_drivers(
+      &driver_repository,
+      &RideRequirements {
+        wheelchair_accessible: true,
+        ..RideRequirements::default()
+      },
+    )
+    .await;
+    assert_eq!(eligible.len(), 1);
+    assert_eq!(eligible[0].0.driver_uuid, "accessible");
+
+    let eligible =
+      eligible_drivers(&driver_repository, &RideRequirements::default()).await;
+    assert_eq!(eligible.len(), 2);
+  }
+}
@@ -0,0 +1,780 @@
+pub mod compliance;
+pub mod compliance_alerts;
+pub mod dto;
+pub mod heartbeat_monitor;
+pub mod location_store;
+pub mod matching;
+pub mod model;
+pub mod repository;
+pub mod rto;
+
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use compliance::{compliance_report, ComplianceReport, LimitUsage};
+use compliance_alerts::{warning_message, ComplianceAlerts};
+use dto::driver_heartbeat_dto::DriverHeartbeatDto;
+use dto::driver_locations_dto::{DriverLocationsDto, LocationFixDto};
+use dto::get_driver_compliance_dto::GetDriverComplianceDto;
+use dto::update_driver_status_dto::UpdateDriverStatusDto;
+use dto::update_vehicle_dto::UpdateVehicleDto;
+use model::availability::{AvailabilityError, DriverAvailability};
+use location_store::DriverLocationStore;
+use model::driver_status::DriverStatus;
+use model::location_fix::{clean_batch, LocationFix};
+use model::shift::Shift;
+use model::vehicle::Vehicle;
+use repository::driver_repository::{DriverRepository, DriverRepositoryError};
+use rto::compliance_report_rto::{ComplianceReportRto, LimitUsageRto};
+use rto::driver_availability_rto::{DriverAvailabilityRto, ShiftRto};
+use rto::location_batch_rto::LocationBatchRto;
+use rto::online_driver_rto::OnlineDriverRto;
+use rto::vehicle_rto::VehicleRto;
+use validator::Validate;
+
+use crate::ranks::rank_queues::ON_TRIP_STATUSES;
+use crate::shared::config::Config;
+use crate::shared::http_error::HttpError;
+use crate::shared::notifier::{Notification, Notifier};
+use crate::shared::role::Role;
+use crate::trips::repository::trip_repository::TripRepository;
+use crate::trips::trip_event_hub::TripEventHub;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+pub async fn get_driver_status<DR: DriverRepository>(
+  driver_repository: web::Data<DR>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let availability = driver_repository
+    .find_one(&auth.uuid)
+    .await
+    .unwrap_or_else(|| DriverAvailability::offline(&auth.uuid, Utc::now()));
+  availability_found(availability)
+}
+
+pub async fn update_driver_status<DR: DriverRepository>(
+  driver_repository: web::Data<DR>,
+  location_store: web::Data<DriverLocationStore>,
+  config: web::Data<Config>,
+  dto: web::Json<UpdateDriverStatusDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let now = Utc::now();
+  let availability = driver_repository
+    .find_one(&auth.uuid)
+    .await
+    .unwrap_or_else(|| DriverAvailability::offline(&auth.uuid, now));
+  // Drivers over their working-time limits cannot take trips
+  if dto.status == DriverStatus::Online {
+    let report = compliance_report(
+      &**driver_repository,
+      &config.compliance,
+      &auth.uuid,
+      now,
+    )
+    .await;
+    if !report.can_take_trips() {
+      return HttpResponse::Conflict()
+        .content_type("application/json")
+        .json(HttpError::with_code(
+          "compliance_limit_exceeded",
+          "Working-time limit exceeded, take a break",
+        ));
+    }
+  }
+  let availability = match availability.transition(dto.status, now) {
+    Ok(availability) => availability,
+    Err(error) => return invalid_status_transition(error),
+  };
+  match driver_repository.save(availability).await {
+    Ok(availability) => {
+      location_store.set_available(
+        &availability.driver_uuid,
+        availability.status == DriverStatus::Online,
+      );
+      availability_found(availability)
+    }
+    Err(error) => failed_save_driver(error),
+  }
+}
+
+// The body is optional, older apps send heartbeats without a location.
+// Drivers are notified once as they get close to or go over a limit.
+pub async fn driver_heartbeat<DR: DriverRepository, N: Notifier>(
+  driver_repository: web::Data<DR>,
+  compliance_alerts: web::Data<ComplianceAlerts>,
+  notifier: web::Data<N>,
+  config: web::Data<Config>,
+  dto: Option<web::Json<DriverHeartbeatDto>>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let location = match dto {
+    Some(dto) => {
+      // Perform validation
+      if let Err(validation_errors) = dto.validate() {
+        // If validation fails, return a 400 error with details
+        return HttpResponse::BadRequest().json(validation_errors);
+      }
+      dto.into_inner().location
+    }
+    None => None,
+  };
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let availability = driver_repository
+    .find_one(&auth.uuid)
+    .await
+    .filter(|availability| availability.status != DriverStatus::Offline);
+  let Some(availability) = availability else {
+    return HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code("driver_offline", "Driver is offline"));
+  };
+  let now = Utc::now();
+  let report = compliance_report(
+    &**driver_repository,
+    &config.compliance,
+    &auth.uuid,
+    now,
+  )
+  .await;
+  let availability = match driver_repository
+    .save(DriverAvailability {
+      last_heartbeat_at: now,
+      location: location.or(availability.location),
+      ..availability
+    })
+    .await
+  {
+    Ok(availability) => availability,
+    Err(error) => return failed_save_driver(error),
+  };
+  for usage in compliance_alerts.crossed(&report) {
+    notifier
+      .notify(Notification {
+        user_uuid: auth.uuid.clone(),
+        message: warning_message(&usage),
+      })
+      .await;
+  }
+  // Every current warning is also in the response, for apps showing
+  // them next to the status
+  let mut rto = DriverAvailabilityRto::from(availability);
+  rto.warnings = report
+    .warnings()
+    .into_iter()
+    .map(LimitUsageRto::from)
+    .collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(rto)
+}
+
+// Batched GPS fixes from the driver app. The newest fix becomes the
+// driver's position for dispatch and rank queues, all of them go to the
+// history.
+pub async fn record_driver_locations<
+  TR: TripRepository,
+  DR: DriverRepository,
+>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  location_store: web::Data<DriverLocationStore>,
+  trip_events: web::Data<TripEventHub>,
+  config: web::Data<Config>,
+  dto: web::Json<DriverLocationsDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  // Drivers are not tracked while off duty
+  let status = driver_repository
+    .find_one(&auth.uuid)
+    .await
+    .map(|availability| availability.status)
+    .filter(|status| *status != DriverStatus::Offline);
+  let Some(status) = status else {
+    return HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code("driver_offline", "Driver is offline"));
+  };
+  let fixes = dto
+    .into_inner()
+    .fixes
+    .into_iter()
+    .map(|fix| LocationFix::from_dto(&auth, fix))
+    .collect();
+  let (fixes, batch) = clean_batch(fixes, Utc::now());
+  let stored = match driver_repository.save_locations(&fixes).await {
+    Ok(stored) => stored,
+    Err(error) => return failed_save_driver(error),
+  };
+  // Also restores the store after a restart, drivers keep sending fixes
+  location_store.set_available(&auth.uuid, status == DriverStatus::Online);
+  if let Some(latest) = fixes.last() {
+    if location_store.record(latest.clone()) {
+      trip_events.publish_position(latest);
+    }
+  }
+  // Drivers already on a trip give up their place at the rank
+  let on_trip = !config.ranks.taxi_ranks.is_empty()
+    && !trip_repository
+      .find_for_driver(&auth.uuid, &ON_TRIP_STATUSES)
+      .await
+      .is_empty();
+  location_store.track_ranks(&config.ranks, &auth.uuid, !on_trip);
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(LocationBatchRto {
+      stored,
+      duplicates: (fixes.len() as u64 - stored) + batch.duplicates as u64,
+      rejected: batch.rejected as u64,
+    })
+}
+
+pub async fn get_driver_compliance<DR: DriverRepository>(
+  driver_repository: web::Data<DR>,
+  config: web::Data<Config>,
+  path: web::Path<GetDriverComplianceDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.uuid != path.uuid
+    && auth.role != Role::Admin
+    && auth.role != Role::Manager
+  {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let report = compliance_report(
+    &**driver_repository,
+    &config.compliance,
+    &path.uuid,
+    Utc::now(),
+  )
+  .await;
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(ComplianceReportRto::from(report))
+}
+
+pub async fn get_online_drivers<DR: DriverRepository>(
+  driver_repository: web::Data<DR>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Admin && auth.role != Role::Manager {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let now = Utc::now();
+  let online_drivers: Vec<OnlineDriverRto> = driver_repository
+    .find_online()
+    .await
+    .into_iter()
+    .filter_map(|availability| {
+      let shift = availability.current_shift()?;
+      Some(OnlineDriverRto {
+        driver_uuid: availability.driver_uuid.clone(),
+        shift_started_at: shift.started_at,
+        shift_duration_secs: shift.duration(now).num_seconds(),
+        last_heartbeat_at: availability.last_heartbeat_at,
+      })
+    })
+    .collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(online_drivers)
+}
+
+pub async fn get_vehicle<DR: DriverRepository>(
+  driver_repository: web::Data<DR>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  driver_repository
+    .find_vehicle(&auth.uuid)
+    .await
+    .ok_or_else(vehicle_not_found)
+    .map(vehicle_found)
+    .unwrap_or_else(|err| err)
+}
+
+pub async fn update_vehicle<DR: DriverRepository>(
+  driver_repository: web::Data<DR>,
+  dto: web::Json<UpdateVehicleDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  driver_repository
+    .save_vehicle(Vehicle::from_dto(auth, dto.into_inner()))
+    .await
+    .map(vehicle_found)
+    .unwrap_or_else(failed_save_driver)
+}
+
+fn vehicle_found(vehicle: Vehicle) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(VehicleRto::from(vehicle))
+}
+
+fn vehicle_not_found() -> HttpResponse {
+  HttpResponse::NotFound()
+    .content_type("application/json")
+    .json(HttpError::from("Vehicle not found"))
+}
+
+fn availability_found(availability: DriverAvailability) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(DriverAvailabilityRto::from(availability))
+}
+
+fn invalid_status_transition(error: AvailabilityError) -> HttpResponse {
+  HttpResponse::Conflict()
+    .content_type("application/json")
+    .json(HttpError::with_code(
+      "invalid_status_transition",
+      &error.to_string(),
+    ))
+}
+
+fn failed_save_driver(_error: DriverRepositoryError) -> HttpResponse {
+  HttpResponse::InternalServerError().finish()
+}
+
+// Transform DriverAvailability domain to RTO
+impl From<DriverAvailability> for DriverAvailabilityRto {
+  fn from(availability: DriverAvailability) -> Self {
+    Self {
+      driver_uuid: availability.driver_uuid,
+      status: availability.status,
+      last_heartbeat_at: availability.last_heartbeat_at,
+      shift: availability.shift.map(ShiftRto::from),
+      warnings: Vec::new(),
+    }
+  }
+}
+
+impl LocationFix {
+  fn from_dto(auth: &AccessTokenClaims, dto: LocationFixDto) -> Self {
+    Self {
+      driver_uuid: auth.uuid.clone(),
+      coords: dto.coords,
+      accuracy_m: dto.accuracy,
+      heading_deg: dto.heading,
+      speed_mps: dto.speed,
+      recorded_at: dto.timestamp,
+    }
+  }
+}
+
+impl Vehicle {
+  fn from_dto(auth: AccessTokenClaims, dto: UpdateVehicleDto) -> Self {
+    Self {
+      driver_uuid: auth.uuid,
+      plate: dto.plate.to_uppercase(),
+      wheelchair_accessible: dto.wheelchair_accessible,
+      assistance_dog: dto.assistance_dog,
+      child_seat: dto.child_seat,
+      spsv_licence: dto.spsv_licence,
+    }
+  }
+}
+
+// Transform Vehicle domain to RTO
+impl From<Vehicle> for VehicleRto {
+  fn from(vehicle: Vehicle) -> Self {
+    Self {
+      driver_uuid: vehicle.driver_uuid,
+      plate: vehicle.plate,
+      wheelchair_accessible: vehicle.wheelchair_accessible,
+      assistance_dog: vehicle.assistance_dog,
+      child_seat: vehicle.child_seat,
+      spsv_licence: vehicle.spsv_licence,
+    }
+  }
+}
+
+impl From<LimitUsage> for LimitUsageRto {
+  fn from(usage: LimitUsage) -> Self {
+    Self {
+      limit: usage.limit,
+      used_secs: usage.used.num_seconds(),
+      max_secs: usage.max.num_seconds(),
+      remaining_secs: (usage.max - usage.used).num_seconds().max(0),
+      state: usage.state,
+    }
+  }
+}
+
+impl From<ComplianceReport> for ComplianceReportRto {
+  fn from(report: ComplianceReport) -> Self {
+    Self {
+      can_take_trips: report.can_take_trips(),
+      driver_uuid: report.driver_uuid,
+      generated_at: report.generated_at,
+      limits: report.limits.into_iter().map(LimitUsageRto::from).collect(),
+    }
+  }
+}
+
+impl From<Shift> for ShiftRto {
+  fn from(shift: Shift) -> Self {
+    Self {
+      duration_secs: shift.duration(Utc::now()).num_seconds(),
+      uuid: shift.uuid,
+      started_at: shift.started_at,
+      ended_at: shift.ended_at,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::{DateTime, Duration};
+  use repository::driver_repository::tests::InMemoryDriverRepository;
+
+  use crate::custom_nanoid;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::notifier::tests::RecordingNotifier;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+
+  use super::*;
+
+  fn driver_claims() -> AccessTokenClaims {
+    AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_driver_goes_online_and_shows_in_online_list() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver_repository =
+      web::Data::from(Arc::new(InMemoryDriverRepository::new()));
+    let auth = driver_claims();
+
+    let responder = update_driver_status(
+      driver_repository.clone(),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(UpdateDriverStatusDto {
+        status: DriverStatus::Online,
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: DriverAvailabilityRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.status, DriverStatus::Online);
+    assert!(rto.shift.is_some());
+
+    let responder =
+      get_online_drivers(driver_repository, create_fake_access_token_claims())
+        .await;
+    let rtos: Vec<OnlineDriverRto> =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rtos.len(), 1);
+    assert_eq!(rtos[0].driver_uuid, auth.uuid);
+  }
+
+  #[actix_web::test]
+  async fn test_break_while_offline_is_conflict() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+
+    let responder = update_driver_status(
+      web::Data::from(Arc::new(InMemoryDriverRepository::new())),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(UpdateDriverStatusDto {
+        status: DriverStatus::OnBreak,
+      }),
+      driver_claims(),
+    )
+    .await;
+
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("invalid_status_transition"));
+  }
+
+  #[actix_web::test]
+  async fn test_heartbeat_refreshes_last_heartbeat() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = driver_claims();
+    let started_at = Utc::now() - Duration::minutes(10);
+    let availability = DriverAvailability::offline(&auth.uuid, started_at)
+      .transition(DriverStatus::Online, started_at)
+      .unwrap();
+
+    let responder = driver_heartbeat(
+      web::Data::from(Arc::new(InMemoryDriverRepository::from(vec![
+        availability,
+      ]))),
+      web::Data::new(ComplianceAlerts::new()),
+      web::Data::new(RecordingNotifier::new()),
+      web::Data::new(Config::default()),
+      None,
+      auth,
+    )
+    .await;
+
+    let rto: DriverAvailabilityRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert!(rto.last_heartbeat_at > started_at);
+  }
+
+  #[actix_web::test]
+  async fn test_heartbeat_pushes_limit_warning_once() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = driver_claims();
+    // Close to the continuous driving limit
+    let started_at = Utc::now() - Duration::minutes(250);
+    let availability = DriverAvailability::offline(&auth.uuid, started_at)
+      .transition(DriverStatus::Online, started_at)
+      .unwrap();
+    let driver_repository =
+      web::Data::from(Arc::new(InMemoryDriverRepository::from(vec![
+        availability,
+      ])));
+    let compliance_alerts = web::Data::new(ComplianceAlerts::new());
+    let notifier = web::Data::new(RecordingNotifier::new());
+
+    for _ in 0..3 {
+      let responder = driver_heartbeat(
+        driver_repository.clone(),
+        compliance_alerts.clone(),
+        notifier.clone(),
+        web::Data::new(Config::default()),
+        None,
+        auth.clone(),
+      )
+      .await;
+      let rto: DriverAvailabilityRto =
+        parse_http_response(responder, &request, StatusCode::OK).await;
+      assert_eq!(rto.warnings.len(), 1);
+    }
+
+    let sent = notifier.sent.read().unwrap(); // Acquire read lock
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].user_uuid, auth.uuid);
+    assert!(sent[0].message.contains("continuous driving"));
+  }
+
+  #[actix_web::test]
+  async fn test_going_online_refused_over_daily_limit() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = driver_claims();
+    let started_at = Utc::now() - Duration::hours(13);
+    let availability = DriverAvailability::offline(&auth.uuid, started_at)
+      .transition(DriverStatus::Online, started_at)
+      .unwrap()
+      .transition(DriverStatus::Offline, Utc::now() - Duration::hours(1))
+      .unwrap();
+
+    let responder = update_driver_status(
+      web::Data::from(Arc::new(InMemoryDriverRepository::from(vec![
+        availability,
+      ]))),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(UpdateDriverStatusDto {
+        status: DriverStatus::Online,
+      }),
+      auth,
+    )
+    .await;
+
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("compliance_limit_exceeded"));
+  }
+
+  #[actix_web::test]
+  async fn test_driver_compliance_report() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = driver_claims();
+    let started_at = Utc::now() - Duration::hours(2);
+    let availability = DriverAvailability::offline(&auth.uuid, started_at)
+      .transition(DriverStatus::Online, started_at)
+      .unwrap();
+
+    let responder = get_driver_compliance(
+      web::Data::from(Arc::new(InMemoryDriverRepository::from(vec![
+        availability,
+      ]))),
+      web::Data::new(Config::default()),
+      web::Path::from(GetDriverComplianceDto {
+        uuid: auth.uuid.clone(),
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+
+    let rto: ComplianceReportRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.driver_uuid, auth.uuid);
+    assert!(rto.can_take_trips);
+    assert_eq!(rto.limits.len(), 3);
+    assert!(rto.limits.iter().all(|usage| usage.used_secs >= 7200));
+  }
+
+  #[actix_web::test]
+  async fn test_update_and_get_vehicle() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver_repository =
+      web::Data::from(Arc::new(InMemoryDriverRepository::new()));
+    let auth = driver_claims();
+
+    let responder = get_vehicle(driver_repository.clone(), auth.clone()).await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::NOT_FOUND).await;
+    assert_eq!(rto.message, "Vehicle not found");
+
+    let responder = update_vehicle(
+      driver_repository.clone(),
+      web::Json(UpdateVehicleDto {
+        plate: String::from("241-d-12345"),
+        wheelchair_accessible: true,
+        assistance_dog: true,
+        child_seat: false,
+        spsv_licence: Some(String::from("SPSV-12345")),
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: VehicleRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.plate, "241-D-12345");
+
+    let responder = get_vehicle(driver_repository, auth).await;
+    let rto: VehicleRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert!(rto.wheelchair_accessible);
+    assert_eq!(rto.spsv_licence.as_deref(), Some("SPSV-12345"));
+  }
+
+  #[actix_web::test]
+  async fn test_online_drivers_forbidden_for_drivers() {
+    let responder = get_online_drivers(
+      web::Data::from(Arc::new(InMemoryDriverRepository::new())),
+      driver_claims(),
+    )
+    .await;
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+  }
+
+  fn location_fix_dto(timestamp: DateTime<Utc>) -> LocationFixDto {
+    LocationFixDto {
+      coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      accuracy: 8.0,
+      heading: Some(180.0),
+      speed: Some(11.5),
+      timestamp,
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_location_batch_is_cleaned_and_stored() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = driver_claims();
+    let now = Utc::now();
+    let availability = DriverAvailability::offline(&auth.uuid, now)
+      .transition(DriverStatus::Online, now)
+      .unwrap();
+    let location_store = web::Data::new(DriverLocationStore::new());
+    let older = now - Duration::seconds(10);
+
+    let responder = record_driver_locations(
+      web::Data::new(InMemoryTripRepository::new()),
+      web::Data::from(Arc::new(InMemoryDriverRepository::from(vec![
+        availability,
+      ]))),
+      location_store.clone(),
+      web::Data::new(TripEventHub::new()),
+      web::Data::new(Config::default()),
+      web::Json(DriverLocationsDto {
+        fixes: vec![
+          location_fix_dto(now),
+          location_fix_dto(older),
+          location_fix_dto(older),
+          location_fix_dto(now + Duration::hours(1)),
+        ],
+      }),
+      auth.clone(),
+    )
+    .await;
+
+    let rto: LocationBatchRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.stored, 2);
+    assert_eq!(rto.duplicates, 1);
+    assert_eq!(rto.rejected, 1);
+    let nearby =
+      location_store.nearest_available(&location_fix_dto(now).coords, 1, 1.0);
+    assert_eq!(nearby[0].id, auth.uuid);
+  }
+
+  #[actix_web::test]
+  async fn test_locations_refused_while_offline() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+
+    let responder = record_driver_locations(
+      web::Data::new(InMemoryTripRepository::new()),
+      web::Data::from(Arc::new(InMemoryDriverRepository::new())),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(TripEventHub::new()),
+      web::Data::new(Config::default()),
+      web::Json(DriverLocationsDto {
+        fixes: vec![location_fix_dto(Utc::now())],
+      }),
+      driver_claims(),
+    )
+    .await;
+
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("driver_offline"));
+  }
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::custom_nanoid;
+use crate::shared::geo_point::GeoPoint;
+
+use super::driver_status::DriverStatus;
+use super::shift::{Shift, ShiftBreak};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AvailabilityError {
+  #[error("Driver is already {0:?}")]
+  AlreadyInStatus(DriverStatus),
+
+  #[error("Cannot go from {from:?} to {to:?}")]
+  InvalidTransition {
+    from: DriverStatus,
+    to: DriverStatus,
+  },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriverAvailability {
+  pub driver_uuid: String,
+  pub status: DriverStatus,
+  pub last_heartbeat_at: DateTime<Utc>,
+  // Current shift while working, otherwise the last finished one
+  pub shift: Option<Shift>,
+  // Last position reported by the driver's device
+  pub location: Option<GeoPoint>,
+}
+
+impl DriverAvailability {
+  pub fn offline(driver_uuid: &str, now: DateTime<Utc>) -> Self {
+    Self {
+      driver_uuid: String::from(driver_uuid),
+      status: DriverStatus::Offline,
+      last_heartbeat_at: now,
+      shift: None,
+      location: None,
+    }
+  }
+
+  pub fn current_shift(&self) -> Option<&Shift> {
+    self.shift.as_ref().filter(|shift| shift.is_open())
+  }
+
+  // Going online opens a shift, going offline closes it. Breaks are
+  // recorded inside the open shift.
+  pub fn transition(
+    mut self,
+    status: DriverStatus,
+    at: DateTime<Utc>,
+  ) -> Result<Self, AvailabilityError> {
+    match (self.status, status) {
+      (from, to) if from == to => {
+        return Err(AvailabilityError::AlreadyInStatus(to));
+      }
+      (DriverStatus::Offline, DriverStatus::Online) => {
+        self.shift = Some(Shift {
+          uuid: custom_nanoid(),
+          driver_uuid: self.driver_uuid.clone(),
+          started_at: at,
+          ended_at: None,
+          breaks: Vec::new(),
+        });
+      }
+      (DriverStatus::Online, DriverStatus::OnBreak) => {
+        if let Some(shift) = self.shift.as_mut() {
+          shift.breaks.push(ShiftBreak {
+            started_at: at,
+            ended_at: None,
+          });
+        }
+      }
+      (DriverStatus::OnBreak, DriverStatus::Online) => {
+        self.close_break(at);
+      }
+      (_, DriverStatus::Offline) => {
+        self.close_break(at);
+        if let Some(shift) = self.shift.as_mut() {
+          shift.ended_at = Some(at);
+        }
+      }
+      (from, to) => {
+        return Err(AvailabilityError::InvalidTransition { from, to });
+      }
+    }
+    self.status = status;
+    self.last_heartbeat_at = self.last_heartbeat_at.max(at);
+    Ok(self)
+  }
+
+  fn close_break(&mut self, at: DateTime<Utc>) {
+    let open_break = self
+      .shift
+      .as_mut()
+      .and_then(|shift| shift.breaks.last_mut())
+      .filter(|shift_break| shift_break.ended_at.is_none());
+    if let Some(shift_break) = open_break {
+      shift_break.ended_at = Some(at);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::Duration;
+
+  use super::*;
+
+  #[test]
+  fn test_shift_lifecycle() {
+    let start = Utc::now();
+    let availability = DriverAvailability::offline("driver", start)
+      .transition(DriverStatus::Online, start)
+      .unwrap()
+      .transition(DriverStatus::OnBreak, start + Duration::hours(2))
+      .unwrap()
+      .transition(DriverStatus::Online, start + Duration::hours(3))
+      .unwrap();
+
+    let shift = availability.current_shift().unwrap();
+    assert_eq!(shift.started_at, start);
+    assert_eq!(shift.breaks.len(), 1);
+    assert_eq!(shift.breaks[0].ended_at, Some(start + Duration::hours(3)));
+
+    let availability = availability
+      .transition(DriverStatus::Offline, start + Duration::hours(5))
+      .unwrap();
+    assert!(availability.current_shift().is_none());
+    let shift = availability.shift.unwrap();
+    assert_eq!(shift.duration(Utc::now()), Duration::hours(5));
+  }
+
+  #[test]
+  fn test_invalid_transitions() {
+    let now = Utc::now();
+    let availability = DriverAvailability::offline("driver", now);
+    assert_eq!(
+      availability.clone().transition(DriverStatus::Offline, now),
+      Err(AvailabilityError::AlreadyInStatus(DriverStatus::Offline))
+    );
+    assert_eq!(
+      availability.transition(DriverStatus::OnBreak, now),
+      Err(AvailabilityError::InvalidTransition {
+        from: DriverStatus::Offline,
+        to: DriverStatus::OnBreak,
+      })
+    );
+  }
+}
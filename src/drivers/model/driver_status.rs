@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum DriverStatus {
+  #[serde(rename = "online")]
+  Online,
+  #[serde(rename = "offline")]
+  Offline,
+  #[serde(rename = "on_break")]
+  OnBreak,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_serialization() {
+    let serialized = serde_json::to_string(&DriverStatus::OnBreak)
+      .expect("Failed to serialize");
+    assert_eq!(serialized, "\"on_break\"");
+
+    let status: DriverStatus =
+      serde_json::from_str("\"online\"").expect("Failed to deserialize");
+    assert_eq!(status, DriverStatus::Online);
+  }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationFix {
+  pub driver_uuid: String,
+  pub coords: GeoPoint,
+  // Radius of the confidence circle reported by the device, in meters
+  pub accuracy_m: f64,
+  pub heading_deg: Option<f64>,
+  pub speed_mps: Option<f64>,
+  // Device clock, fixes are buffered while the phone has no signal
+  pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FixBatch {
+  pub rejected: usize,
+  pub duplicates: usize,
+}
+
+// Devices with a wrong clock send fixes from the future, and phones that
+// were off for days flush stale ones. Neither is worth storing.
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+const MAX_FIX_AGE_HOURS: i64 = 24;
+
+impl LocationFix {
+  pub fn is_plausible(&self, now: DateTime<Utc>) -> bool {
+    self.recorded_at <= now + Duration::seconds(MAX_CLOCK_SKEW_SECS)
+      && self.recorded_at >= now - Duration::hours(MAX_FIX_AGE_HOURS)
+  }
+}
+
+// Drops implausible fixes and repeated device timestamps, oldest first
+pub fn clean_batch(
+  mut fixes: Vec<LocationFix>,
+  now: DateTime<Utc>,
+) -> (Vec<LocationFix>, FixBatch) {
+  let total = fixes.len();
+  fixes.retain(|fix| fix.is_plausible(now));
+  let rejected = total - fixes.len();
+  fixes.sort_by_key(|fix| fix.recorded_at);
+  let plausible = fixes.len();
+  fixes.dedup_by_key(|fix| fix.recorded_at);
+  let duplicates = plausible - fixes.len();
+  (
+    fixes,
+    FixBatch {
+      rejected,
+      duplicates,
+    },
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fix(recorded_at: DateTime<Utc>) -> LocationFix {
+    LocationFix {
+      driver_uuid: String::from("driver"),
+      coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      accuracy_m: 5.0,
+      heading_deg: None,
+      speed_mps: None,
+      recorded_at,
+    }
+  }
+
+  #[test]
+  fn test_clean_batch() {
+    let now = Utc::now();
+    let (fixes, batch) = clean_batch(
+      vec![
+        fix(now),
+        fix(now - Duration::seconds(5)),
+        fix(now),
+        fix(now + Duration::minutes(10)),
+        fix(now - Duration::days(3)),
+      ],
+      now,
+    );
+    assert_eq!(
+      batch,
+      FixBatch {
+        rejected: 2,
+        duplicates: 1,
+      }
+    );
+    let timestamps: Vec<DateTime<Utc>> =
+      fixes.iter().map(|fix| fix.recorded_at).collect();
+    assert_eq!(timestamps, vec![now - Duration::seconds(5), now]);
+  }
+}
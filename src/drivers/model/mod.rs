@@ -0,0 +1,5 @@
+pub mod availability;
+pub mod driver_status;
+pub mod location_fix;
+pub mod shift;
+pub mod vehicle;
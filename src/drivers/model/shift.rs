@@ -0,0 +1,27 @@
+use chrono::{DateTime, Duration, Utc};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShiftBreak {
+  pub started_at: DateTime<Utc>,
+  pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shift {
+  pub uuid: String,
+  pub driver_uuid: String,
+  pub started_at: DateTime<Utc>,
+  pub ended_at: Option<DateTime<Utc>>,
+  pub breaks: Vec<ShiftBreak>,
+}
+
+impl Shift {
+  pub fn is_open(&self) -> bool {
+    self.ended_at.is_none()
+  }
+
+  // Time elapsed since the shift started, breaks included
+  pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+    self.ended_at.unwrap_or(now) - self.started_at
+  }
+}
@@ -0,0 +1,49 @@
+use crate::shared::ride_requirements::RideRequirements;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vehicle {
+  pub driver_uuid: String,
+  pub plate: String,
+  pub wheelchair_accessible: bool,
+  pub assistance_dog: bool,
+  pub child_seat: bool,
+  // SPSV driver licence number, printed on receipts
+  pub spsv_licence: Option<String>,
+}
+
+impl Vehicle {
+  // Only the accessibility needs are checked, language is a preference
+  pub fn satisfies(&self, requirements: &RideRequirements) -> bool {
+    (!requirements.wheelchair_accessible || self.wheelchair_accessible)
+      && (!requirements.assistance_dog || self.assistance_dog)
+      && (!requirements.child_seat || self.child_seat)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_satisfies() {
+    let vehicle = Vehicle {
+      driver_uuid: String::from("driver"),
+      plate: String::from("241-D-12345"),
+      wheelchair_accessible: true,
+      assistance_dog: true,
+      child_seat: false,
+      spsv_licence: None,
+    };
+
+    assert!(vehicle.satisfies(&RideRequirements::default()));
+    assert!(vehicle.satisfies(&RideRequirements {
+      wheelchair_accessible: true,
+      assistance_dog: true,
+      ..RideRequirements::default()
+    }));
+    assert!(!vehicle.satisfies(&RideRequirements {
+      child_seat: true,
+      ..RideRequirements::default()
+    }));
+  }
+}
@@ -0,0 +1,539 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use sqlx::{postgres::PgRow, Pool, Postgres};
+use thiserror::Error;
+
+use crate::drivers::model::availability::DriverAvailability;
+use crate::drivers::model::location_fix::LocationFix;
+use crate::drivers::model::shift::{Shift, ShiftBreak};
+use crate::drivers::model::vehicle::Vehicle;
+use crate::shared::database::Database;
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Error)]
+pub enum DriverRepositoryError {
+  #[error("Database error: {0}")]
+  DatabaseError(#[from] sqlx::Error),
+
+  #[error("Serialization error: {0}")]
+  SerializationError(#[from] serde_json::Error),
+}
+
+pub trait DriverRepository {
+  async fn find_one(&self, driver_uuid: &str) -> Option<DriverAvailability>;
+  async fn find_online(&self) -> Vec<DriverAvailability>;
+  // Drivers still working whose last heartbeat is older than `before`
+  async fn find_stale(&self, before: DateTime<Utc>) -> Vec<DriverAvailability>;
+  // Shifts still open or that ended after `since`, oldest first
+  async fn find_shifts(
+    &self,
+    driver_uuid: &str,
+    since: DateTime<Utc>,
+  ) -> Vec<Shift>;
+  async fn save(
+    &self,
+    availability: DriverAvailability,
+  ) -> Result<DriverAvailability, DriverRepositoryError>;
+  async fn find_vehicle(&self, driver_uuid: &str) -> Option<Vehicle>;
+  async fn save_vehicle(
+    &self,
+    vehicle: Vehicle,
+  ) -> Result<Vehicle, DriverRepositoryError>;
+  // Appends to the location history, returns how many fixes were new
+  async fn save_locations(
+    &self,
+    fixes: &[LocationFix],
+  ) -> Result<u64, DriverRepositoryError>;
+  // Location history between the two instants, oldest first
+  async fn find_locations(
+    &self,
+    driver_uuid: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  ) -> Vec<LocationFix>;
+}
+
+pub struct DriverRepositoryImpl {
+  pool: Arc<Pool<Postgres>>,
+}
+
+impl DriverRepositoryImpl {
+  pub fn new(database: Arc<Database>) -> Self {
+    Self {
+      pool: database.pool.clone(),
+    }
+  }
+
+  async fn load(
+    &self,
+    rows: Vec<PgRow>,
+  ) -> Result<Vec<DriverAvailability>, DriverRepositoryError> {
+    let mut availabilities = Vec::with_capacity(rows.len());
+    for row in rows {
+      let shift_uuid: Option<String> = row.get("shift_uuid");
+      let shift = match shift_uuid {
+        Some(shift_uuid) => self.load_shift(&shift_uuid).await?,
+        None => None,
+      };
+      availabilities.push(DriverAvailability {
+        driver_uuid: row.get("driver_uuid"),
+        status: serde_json::from_str(row.get("status"))?,
+        last_heartbeat_at: row.get::<DateTime<Utc>, _>("last_heartbeat_at"),
+        shift,
+        location: GeoPoint::from_columns(row.get("lat"), row.get("lng")),
+      });
+    }
+    Ok(availabilities)
+  }
+
+  async fn load_shift(
+    &self,
+    shift_uuid: &str,
+  ) -> Result<Option<Shift>, DriverRepositoryError> {
+    let breaks = sqlx::query(
+      "SELECT * FROM shift_breaks WHERE shift_uuid = $1 ORDER BY started_at",
+    )
+    .bind(shift_uuid)
+    .map(|row: PgRow| ShiftBreak::from(row))
+    .fetch_all(&*self.pool)
+    .await?;
+    let shift = sqlx::query("SELECT * FROM shifts WHERE uuid = $1 LIMIT 1")
+      .bind(shift_uuid)
+      .map(|row: PgRow| Shift::from(row))
+      .fetch_optional(&*self.pool)
+      .await?;
+    Ok(shift.map(|shift| Shift { breaks, ..shift }))
+  }
+}
+
+impl DriverRepository for DriverRepositoryImpl {
+  async fn find_one(&self, driver_uuid: &str) -> Option<DriverAvailability> {
+    let rows = sqlx::query(
+      "SELECT * FROM driver_availabilities WHERE driver_uuid = $1 LIMIT 1",
+    )
+    .bind(driver_uuid)
+    .fetch_all(&*self.pool)
+    .await
+    .ok()?;
+    self.load(rows).await.ok()?.pop()
+  }
+
+  async fn find_online(&self) -> Vec<DriverAvailability> {
+    let rows =
+      sqlx::query("SELECT * FROM driver_availabilities WHERE status = $1")
+        .bind("\"online\"")
+        .fetch_all(&*self.pool)
+        .await
+        .unwrap_or_default();
+    self.load(rows).await.unwrap_or_default()
+  }
+
+  async fn find_stale(&self, before: DateTime<Utc>) -> Vec<DriverAvailability> {
+    let query = r#"
+      SELECT * FROM driver_availabilities
+      WHERE status <> $1 AND last_heartbeat_at < $2
+    "#;
+    let rows = sqlx::query(query)
+      .bind("\"offline\"")
+      .bind(before)
+      .fetch_all(&*self.pool)
+      .await
+      .unwrap_or_default();
+    self.load(rows).await.unwrap_or_default()
+  }
+
+  async fn find_shifts(
+    &self,
+    driver_uuid: &str,
+    since: DateTime<Utc>,
+  ) -> Vec<Shift> {
+    let query = r#"
+      SELECT uuid FROM shifts
+      WHERE driver_uuid = $1 AND (ended_at IS NULL OR ended_at >= $2)
+      ORDER BY started_at
+    "#;
+    let shift_uuids: Vec<String> = sqlx::query(query)
+      .bind(driver_uuid)
+      .bind(since)
+      .map(|row: PgRow| row.get("uuid"))
+      .fetch_all(&*self.pool)
+      .await
+      .unwrap_or_default();
+    let mut shifts = Vec::with_capacity(shift_uuids.len());
+    for shift_uuid in shift_uuids {
+      if let Ok(Some(shift)) = self.load_shift(&shift_uuid).await {
+        shifts.push(shift);
+      }
+    }
+    shifts
+  }
+
+  async fn save(
+    &self,
+    availability: DriverAvailability,
+  ) -> Result<DriverAvailability, DriverRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
+    if let Some(shift) = &availability.shift {
+      let query = r#"
+        INSERT INTO shifts (uuid, driver_uuid, started_at, ended_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (uuid) DO UPDATE SET ended_at = EXCLUDED.ended_at
+      "#;
+      sqlx::query(query)
+        .bind(&shift.uuid)
+        .bind(&shift.driver_uuid)
+        .bind(shift.started_at)
+        .bind(shift.ended_at)
+        .execute(&mut *transaction)
+        .await?;
+      for shift_break in &shift.breaks {
+        let query = r#"
+          INSERT INTO shift_breaks (shift_uuid, started_at, ended_at)
+          VALUES ($1, $2, $3)
+          ON CONFLICT (shift_uuid, started_at)
+          DO UPDATE SET ended_at = EXCLUDED.ended_at
+        "#;
+        sqlx::query(query)
+          .bind(&shift.uuid)
+          .bind(shift_break.started_at)
+          .bind(shift_break.ended_at)
+          .execute(&mut *transaction)
+          .await?;
+      }
+    }
+    let query = r#"
+      INSERT INTO driver_availabilities
+        (driver_uuid, status, last_heartbeat_at, shift_uuid, lat, lng)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      ON CONFLICT (driver_uuid) DO UPDATE SET
+        status = EXCLUDED.status,
+        last_heartbeat_at = EXCLUDED.last_heartbeat_at,
+        shift_uuid = EXCLUDED.shift_uuid,
+        lat = EXCLUDED.lat,
+        lng = EXCLUDED.lng
+    "#;
+    sqlx::query(query)
+      .bind(&availability.driver_uuid)
+      .bind(serde_json::to_string(&availability.status)?)
+      .bind(availability.last_heartbeat_at)
+      .bind(availability.shift.as_ref().map(|shift| &shift.uuid))
+      .bind(availability.location.map(|location| location.lat))
+      .bind(availability.location.map(|location| location.lng))
+      .execute(&mut *transaction)
+      .await?;
+    transaction.commit().await?;
+    Ok(availability)
+  }
+
+  async fn find_vehicle(&self, driver_uuid: &str) -> Option<Vehicle> {
+    sqlx::query("SELECT * FROM vehicles WHERE driver_uuid = $1 LIMIT 1")
+      .bind(driver_uuid)
+      .map(|row: PgRow| Vehicle::from(row))
+      .fetch_optional(&*self.pool)
+      .await
+      .ok()
+      .flatten()
+  }
+
+  async fn save_vehicle(
+    &self,
+    vehicle: Vehicle,
+  ) -> Result<Vehicle, DriverRepositoryError> {
+    let query = r#"
+      INSERT INTO vehicles (
+        driver_uuid, plate, wheelchair_accessible, assistance_dog, child_seat,
+        spsv_licence
+      )
+      VALUES ($1, $2, $3, $4, $5, $6)
+      ON CONFLICT (driver_uuid) DO UPDATE SET
+        plate = EXCLUDED.plate,
+        wheelchair_accessible = EXCLUDED.wheelchair_accessible,
+        assistance_dog = EXCLUDED.assistance_dog,
+        child_seat = EXCLUDED.child_seat,
+        spsv_licence = EXCLUDED.spsv_licence
+      RETURNING *
+    "#;
+    sqlx::query(query)
+      .bind(&vehicle.driver_uuid)
+      .bind(&vehicle.plate)
+      .bind(vehicle.wheelchair_accessible)
+      .bind(vehicle.assistance_dog)
+      .bind(vehicle.child_seat)
+      .bind(&vehicle.spsv_licence)
+      .map(|row: PgRow| Vehicle::from(row))
+      .fetch_one(&*self.pool)
+      .await
+      .map_err(DriverRepositoryError::from)
+  }
+
+  async fn save_locations(
+    &self,
+    fixes: &[LocationFix],
+  ) -> Result<u64, DriverRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
+    let mut stored = 0;
+    for fix in fixes {
+      let query = r#"
+        INSERT INTO driver_locations (
+          driver_uuid, recorded_at, lat, lng, accuracy_m, heading_deg,
+          speed_mps
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (driver_uuid, recorded_at) DO NOTHING
+      "#;
+      stored += sqlx::query(query)
+        .bind(&fix.driver_uuid)
+        .bind(fix.recorded_at)
+        .bind(fix.coords.lat)
+        .bind(fix.coords.lng)
+        .bind(fix.accuracy_m)
+        .bind(fix.heading_deg)
+        .bind(fix.speed_mps)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    }
+    transaction.commit().await?;
+    Ok(stored)
+  }
+
+  async fn find_locations(
+    &self,
+    driver_uuid: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  ) -> Vec<LocationFix> {
+    let query = r#"
+      SELECT * FROM driver_locations
+      WHERE driver_uuid = $1 AND recorded_at BETWEEN $2 AND $3
+      ORDER BY recorded_at
+    "#;
+    sqlx::query(query)
+      .bind(driver_uuid)
+      .bind(from)
+      .bind(to)
+      .fetch_all(&*self.pool)
+      .await
+      .unwrap_or_default()
+      .into_iter()
+      .map(LocationFix::from)
+      .collect()
+  }
+}
+
+impl From<PgRow> for LocationFix {
+  fn from(row: PgRow) -> Self {
+    Self {
+      driver_uuid: row.get("driver_uuid"),
+      coords: GeoPoint {
+        lat: row.get("lat"),
+        lng: row.get("lng"),
+      },
+      accuracy_m: row.get("accuracy_m"),
+      heading_deg: row.get("heading_deg"),
+      speed_mps: row.get("speed_mps"),
+      recorded_at: row.get::<DateTime<Utc>, _>("recorded_at"),
+    }
+  }
+}
+
+impl From<PgRow> for Vehicle {
+  fn from(row: PgRow) -> Self {
+    Self {
+      driver_uuid: row.get("driver_uuid"),
+      plate: row.get("plate"),
+      wheelchair_accessible: row.get("wheelchair_accessible"),
+      assistance_dog: row.get("assistance_dog"),
+      child_seat: row.get("child_seat"),
+      spsv_licence: row.get("spsv_licence"),
+    }
+  }
+}
+
+impl From<PgRow> for Shift {
+  fn from(row: PgRow) -> Self {
+    Self {
+      uuid: row.get("uuid"),
+      driver_uuid: row.get("driver_uuid"),
+      started_at: row.get::<DateTime<Utc>, _>("started_at"),
+      ended_at: row.get::<Option<DateTime<Utc>>, _>("ended_at"),
+      breaks: Vec::new(),
+    }
+  }
+}
+
+impl From<PgRow> for ShiftBreak {
+  fn from(row: PgRow) -> Self {
+    Self {
+      started_at: row.get::<DateTime<Utc>, _>("started_at"),
+      ended_at: row.get::<Option<DateTime<Utc>>, _>("ended_at"),
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use chrono::{DateTime, Utc};
+  use std::sync::RwLock;
+
+  use super::{DriverRepository, DriverRepositoryError};
+  use crate::drivers::model::availability::DriverAvailability;
+  use crate::drivers::model::driver_status::DriverStatus;
+  use crate::drivers::model::location_fix::LocationFix;
+  use crate::drivers::model::shift::Shift;
+  use crate::drivers::model::vehicle::Vehicle;
+
+  pub struct InMemoryDriverRepository {
+    pub availabilities: RwLock<Vec<DriverAvailability>>,
+    pub shifts: RwLock<Vec<Shift>>,
+    pub vehicles: RwLock<Vec<Vehicle>>,
+    pub locations: RwLock<Vec<LocationFix>>,
+  }
+
+  impl InMemoryDriverRepository {
+    pub fn new() -> Self {
+      Self::from(Vec::new())
+    }
+  }
+
+  impl From<Vec<DriverAvailability>> for InMemoryDriverRepository {
+    fn from(availabilities: Vec<DriverAvailability>) -> Self {
+      let shifts = availabilities
+        .iter()
+        .filter_map(|availability| availability.shift.clone())
+        .collect();
+      Self {
+        availabilities: RwLock::new(availabilities),
+        shifts: RwLock::new(shifts),
+        vehicles: RwLock::new(Vec::new()),
+        locations: RwLock::new(Vec::new()),
+      }
+    }
+  }
+
+  impl DriverRepository for InMemoryDriverRepository {
+    async fn find_one(&self, driver_uuid: &str) -> Option<DriverAvailability> {
+      let availabilities = self.availabilities.read().unwrap(); // Acquire read lock
+      availabilities
+        .iter()
+        .find(|availability| availability.driver_uuid == driver_uuid)
+        .cloned()
+    }
+
+    async fn find_online(&self) -> Vec<DriverAvailability> {
+      let availabilities = self.availabilities.read().unwrap(); // Acquire read lock
+      availabilities
+        .iter()
+        .filter(|availability| availability.status == DriverStatus::Online)
+        .cloned()
+        .collect()
+    }
+
+    async fn find_stale(
+      &self,
+      before: DateTime<Utc>,
+    ) -> Vec<DriverAvailability> {
+      let availabilities = self.availabilities.read().unwrap(); // Acquire read lock
+      availabilities
+        .iter()
+        .filter(|availability| {
+          availability.status != DriverStatus::Offline
+            && availability.last_heartbeat_at < before
+        })
+        .cloned()
+        .collect()
+    }
+
+    async fn find_shifts(
+      &self,
+      driver_uuid: &str,
+      since: DateTime<Utc>,
+    ) -> Vec<Shift> {
+      let shifts = self.shifts.read().unwrap(); // Acquire read lock
+      let mut shifts: Vec<Shift> = shifts
+        .iter()
+        .filter(|shift| {
+          shift.driver_uuid == driver_uuid
+            && shift.ended_at.is_none_or(|ended_at| ended_at >= since)
+        })
+        .cloned()
+        .collect();
+      shifts.sort_by_key(|shift| shift.started_at);
+      shifts
+    }
+
+    async fn save(
+      &self,
+      availability: DriverAvailability,
+    ) -> Result<DriverAvailability, DriverRepositoryError> {
+      if let Some(shift) = &availability.shift {
+        let mut shifts = self.shifts.write().unwrap(); // Acquire write lock
+        shifts.retain(|existing| existing.uuid != shift.uuid);
+        shifts.push(shift.clone());
+      }
+      let mut availabilities = self.availabilities.write().unwrap(); // Acquire write lock
+      availabilities
+        .retain(|existing| existing.driver_uuid != availability.driver_uuid);
+      availabilities.push(availability.clone());
+      Ok(availability)
+    }
+
+    async fn find_vehicle(&self, driver_uuid: &str) -> Option<Vehicle> {
+      let vehicles = self.vehicles.read().unwrap(); // Acquire read lock
+      vehicles
+        .iter()
+        .find(|vehicle| vehicle.driver_uuid == driver_uuid)
+        .cloned()
+    }
+
+    async fn save_vehicle(
+      &self,
+      vehicle: Vehicle,
+    ) -> Result<Vehicle, DriverRepositoryError> {
+      let mut vehicles = self.vehicles.write().unwrap(); // Acquire write lock
+      vehicles.retain(|existing| existing.driver_uuid != vehicle.driver_uuid);
+      vehicles.push(vehicle.clone());
+      Ok(vehicle)
+    }
+
+    async fn save_locations(
+      &self,
+      fixes: &[LocationFix],
+    ) -> Result<u64, DriverRepositoryError> {
+      let mut locations = self.locations.write().unwrap(); // Acquire write lock
+      let mut stored = 0;
+      for fix in fixes {
+        let exists = locations.iter().any(|location| {
+          location.driver_uuid == fix.driver_uuid
+            && location.recorded_at == fix.recorded_at
+        });
+        if !exists {
+          locations.push(fix.clone());
+          stored += 1;
+        }
+      }
+      Ok(stored)
+    }
+
+    async fn find_locations(
+      &self,
+      driver_uuid: &str,
+      from: DateTime<Utc>,
+      to: DateTime<Utc>,
+    ) -> Vec<LocationFix> {
+      let locations = self.locations.read().unwrap(); // Acquire read lock
+      let mut trace: Vec<LocationFix> = locations
+        .iter()
+        .filter(|fix| {
+          fix.driver_uuid == driver_uuid
+            && (from..=to).contains(&fix.recorded_at)
+        })
+        .cloned()
+        .collect();
+      trace.sort_by_key(|fix| fix.recorded_at);
+      trace
+    }
+  }
+}
@@ -0,0 +1 @@
+pub mod driver_repository;
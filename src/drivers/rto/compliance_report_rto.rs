@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::drivers::compliance::{ComplianceLimit, LimitState};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LimitUsageRto {
+  pub limit: ComplianceLimit,
+  #[serde(rename = "usedSecs")]
+  pub used_secs: i64,
+  #[serde(rename = "maxSecs")]
+  pub max_secs: i64,
+  #[serde(rename = "remainingSecs")]
+  pub remaining_secs: i64,
+  pub state: LimitState,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComplianceReportRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  #[serde(rename = "generatedAt")]
+  pub generated_at: DateTime<Utc>,
+  #[serde(rename = "canTakeTrips")]
+  pub can_take_trips: bool,
+  pub limits: Vec<LimitUsageRto>,
+}
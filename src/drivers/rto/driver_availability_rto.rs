@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::drivers::model::driver_status::DriverStatus;
+
+use super::compliance_report_rto::LimitUsageRto;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShiftRto {
+  pub uuid: String,
+  #[serde(rename = "startedAt")]
+  pub started_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "endedAt")]
+  pub ended_at: Option<DateTime<Utc>>,
+  #[serde(rename = "durationSecs")]
+  pub duration_secs: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverAvailabilityRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  pub status: DriverStatus,
+  #[serde(rename = "lastHeartbeatAt")]
+  pub last_heartbeat_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub shift: Option<ShiftRto>,
+  // Working-time limits that are close or exceeded
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<LimitUsageRto>,
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+// Lets apps drop fixes from their local buffer once they are stored
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocationBatchRto {
+  pub stored: u64,
+  pub duplicates: u64,
+  pub rejected: u64,
+}
@@ -0,0 +1,5 @@
+pub mod compliance_report_rto;
+pub mod driver_availability_rto;
+pub mod location_batch_rto;
+pub mod online_driver_rto;
+pub mod vehicle_rto;
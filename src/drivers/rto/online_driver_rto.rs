@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OnlineDriverRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  #[serde(rename = "shiftStartedAt")]
+  pub shift_started_at: DateTime<Utc>,
+  #[serde(rename = "shiftDurationSecs")]
+  pub shift_duration_secs: i64,
+  #[serde(rename = "lastHeartbeatAt")]
+  pub last_heartbeat_at: DateTime<Utc>,
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VehicleRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  pub plate: String,
+  #[serde(rename = "wheelchairAccessible")]
+  pub wheelchair_accessible: bool,
+  #[serde(rename = "assistanceDog")]
+  pub assistance_dog: bool,
+  #[serde(rename = "childSeat")]
+  pub child_seat: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "spsvLicence")]
+  pub spsv_licence: Option<String>,
+}
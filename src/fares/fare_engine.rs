@@ -0,0 +1,303 @@
+use chrono::{DateTime, Utc};
+
+use super::model::fare_breakdown::{FareBreakdown, FareItem};
+use super::model::fare_range::FareRange;
+use super::model::tariff::RateTable;
+use super::tariff_table::TariffTable;
+
+// Detours and traffic on top of the expected route, for the upper bound
+// of a quote
+const MAX_DISTANCE_PERCENT: i64 = 130;
+const MAX_DURATION_PERCENT: i64 = 150;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FareInput {
+  // Decides the tariff and the rate for the whole trip
+  pub started_at: DateTime<Utc>,
+  pub distance_m: i64,
+  // Includes the time spent at intermediate stops
+  pub duration_secs: i64,
+  pub additional_passengers: i64,
+  pub stop_waits: Vec<StopWait>,
+  // Fixed charges on top of the meter, e.g. for an airport pickup
+  pub surcharges: Vec<FareItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopWait {
+  // Distance driven when the car stopped, decides the band
+  pub at_m: i64,
+  pub secs: i64,
+}
+
+// None when no tariff was in force when the trip started
+pub fn calculate_fare(
+  tariffs: &TariffTable,
+  input: &FareInput,
+) -> Option<FareBreakdown> {
+  let tariff = tariffs.in_effect(input.started_at)?;
+  let rate = tariffs.rate_period(tariff, input.started_at);
+  let rates = tariff.rates(rate);
+  // Waiting at stops is charged on its own, whatever the distance
+  let waiting_secs: i64 =
+    input.stop_waits.iter().map(|wait| wait.secs.max(0)).sum();
+  let mut items = metered_items(
+    rates,
+    input.distance_m.max(0),
+    (input.duration_secs - waiting_secs).max(0),
+  );
+  let waiting_cents = waiting_cents(rates, &input.stop_waits);
+  if waiting_cents > 0 {
+    items.push(FareItem::new("waiting_time", waiting_cents));
+  }
+  // Every trip is booked through the app
+  items.push(FareItem::new("booking_fee", tariff.booking_fee_cents));
+  if input.additional_passengers > 0 {
+    items.push(FareItem::new(
+      "additional_passengers",
+      input.additional_passengers * tariff.additional_passenger_cents,
+    ));
+  }
+  items.extend(input.surcharges.iter().cloned());
+  Some(FareBreakdown {
+    tariff_version: tariff.version.clone(),
+    rate,
+    total_cents: items.iter().map(|item| item.amount_cents).sum(),
+    items,
+    distance_m: Some(input.distance_m.max(0)),
+  })
+}
+
+// From the expected route up to a longer and slower one
+pub fn estimate_fare_range(
+  tariffs: &TariffTable,
+  input: &FareInput,
+) -> Option<FareRange> {
+  let min = calculate_fare(tariffs, input)?;
+  let max = calculate_fare(
+    tariffs,
+    &FareInput {
+      distance_m: input.distance_m * MAX_DISTANCE_PERCENT / 100,
+      duration_secs: input.duration_secs * MAX_DURATION_PERCENT / 100,
+      ..input.clone()
+    },
+  )?;
+  Some(FareRange {
+    tariff_version: min.tariff_version,
+    rate: min.rate,
+    min_cents: min.total_cents,
+    max_cents: max.total_cents,
+  })
+}
+
+// A meter charges by time while the car is slower than the changeover
+// speed and by distance otherwise. Without the speed profile each band
+// charges the greater of the two, what the meter would show at least.
+fn metered_items(
+  rates: &RateTable,
+  distance_m: i64,
+  duration_secs: i64,
+) -> Vec<FareItem> {
+  let mut items =
+    vec![FareItem::new("initial_charge", rates.initial_charge_cents)];
+  let metered_m = (distance_m - rates.initial_distance_m).max(0);
+  let metered_secs = (duration_secs - rates.initial_duration_secs).max(0);
+  let mut from_m = rates.initial_distance_m;
+  let mut secs_left = metered_secs;
+  for band in &rates.bands {
+    let to_m = band.up_to_m.unwrap_or(i64::MAX).max(from_m);
+    let band_m = (distance_m.min(to_m) - from_m).max(0);
+    let is_last = distance_m <= to_m;
+    // Time is spread over the bands like the distance, the band where
+    // the trip ends takes the rest
+    let band_secs = if is_last {
+      secs_left
+    } else {
+      metered_secs * band_m / metered_m
+    };
+    let amount_cents = divide_rounded(band_m * band.cents_per_km, 1000)
+      .max(divide_rounded(band_secs * band.cents_per_minute, 60));
+    if amount_cents > 0 {
+      items.push(FareItem::new(&band.name, amount_cents));
+    }
+    if is_last {
+      break;
+    }
+    from_m = to_m;
+    secs_left -= band_secs;
+  }
+  items
+}
+
+// By the minute, at the rate of the band the car was in when it stopped
+fn waiting_cents(rates: &RateTable, stop_waits: &[StopWait]) -> i64 {
+  let cents_per_minute_secs: i64 = stop_waits
+    .iter()
+    .filter_map(|wait| {
+      let band = rates
+        .bands
+        .iter()
+        .find(|band| band.up_to_m.is_none_or(|up_to_m| wait.at_m < up_to_m))?;
+      Some(wait.secs.max(0) * band.cents_per_minute)
+    })
+    .sum();
+  divide_rounded(cents_per_minute_secs, 60)
+}
+
+// Half a cent and more rounds up
+fn divide_rounded(value: i64, divisor: i64) -> i64 {
+  (value + divisor / 2) / divisor
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+  use crate::fares::model::rate_period::RatePeriod;
+
+  fn weekday_noon() -> DateTime<Utc> {
+    // Wednesday 5 November 2025
+    Utc.with_ymd_and_hms(2025, 11, 5, 12, 0, 0).unwrap()
+  }
+
+  fn amount(fare: &FareBreakdown, code: &str) -> Option<i64> {
+    fare
+      .items
+      .iter()
+      .find(|item| item.code == code)
+      .map(|item| item.amount_cents)
+  }
+
+  #[test]
+  fn test_short_trip_is_initial_charge_and_booking_fee() {
+    let fare = calculate_fare(
+      &TariffTable::nta(),
+      &FareInput {
+        started_at: weekday_noon(),
+        distance_m: 400,
+        duration_secs: 60,
+        additional_passengers: 0,
+        stop_waits: Vec::new(),
+        surcharges: Vec::new(),
+      },
+    )
+    .unwrap();
+    assert_eq!(fare.tariff_version, "nta-2022");
+    assert_eq!(fare.rate, RatePeriod::Standard);
+    assert_eq!(
+      fare.items,
+      vec![
+        FareItem::new("initial_charge", 420),
+        FareItem::new("booking_fee", 200),
+      ]
+    );
+    assert_eq!(fare.total_cents, 620);
+  }
+
+  #[test]
+  fn test_fare_crosses_distance_bands() {
+    // 20.5 km in 25 minutes, the distance charge wins in both bands
+    let fare = calculate_fare(
+      &TariffTable::nta(),
+      &FareInput {
+        started_at: weekday_noon(),
+        distance_m: 20_500,
+        duration_secs: 1500,
+        additional_passengers: 2,
+        stop_waits: Vec::new(),
+        surcharges: Vec::new(),
+      },
+    )
+    .unwrap();
+    // 14.5 km at 132 cents, then 5.5 km at 182 cents
+    assert_eq!(amount(&fare, "tariff_a"), Some(1914));
+    assert_eq!(amount(&fare, "tariff_b"), Some(1001));
+    assert_eq!(amount(&fare, "additional_passengers"), Some(200));
+    assert_eq!(fare.total_cents, 420 + 1914 + 1001 + 200 + 200);
+  }
+
+  #[test]
+  fn test_estimate_covers_longer_route() {
+    let input = FareInput {
+      started_at: weekday_noon(),
+      distance_m: 8000,
+      duration_secs: 1200,
+      additional_passengers: 0,
+      stop_waits: Vec::new(),
+      surcharges: Vec::new(),
+    };
+    let tariffs = TariffTable::nta();
+    let range = estimate_fare_range(&tariffs, &input).unwrap();
+    assert_eq!(range.rate, RatePeriod::Standard);
+    assert_eq!(
+      range.min_cents,
+      calculate_fare(&tariffs, &input).unwrap().total_cents
+    );
+    // 10.4 km in 30 minutes, slow enough to be charged by time
+    assert_eq!(range.max_cents, 420 + 1315 + 200);
+  }
+
+  #[test]
+  fn test_slow_trip_is_charged_by_time() {
+    // 2.5 km stuck in traffic for half an hour, at night
+    let fare = calculate_fare(
+      &TariffTable::nta(),
+      &FareInput {
+        started_at: Utc.with_ymd_and_hms(2025, 11, 5, 22, 0, 0).unwrap(),
+        distance_m: 2500,
+        duration_secs: 1800,
+        additional_passengers: 0,
+        stop_waits: Vec::new(),
+        surcharges: Vec::new(),
+      },
+    )
+    .unwrap();
+    assert_eq!(fare.rate, RatePeriod::Premium);
+    assert_eq!(amount(&fare, "initial_charge"), Some(480));
+    // 1715 seconds at 63 cents a minute
+    assert_eq!(amount(&fare, "tariff_a"), Some(1801));
+  }
+
+  #[test]
+  fn test_waiting_at_stops_is_charged_by_time() {
+    // 6 km in 20 minutes, 8 of them waiting at a stop after 3 km
+    let fare = calculate_fare(
+      &TariffTable::nta(),
+      &FareInput {
+        started_at: weekday_noon(),
+        distance_m: 6000,
+        duration_secs: 1200,
+        additional_passengers: 0,
+        stop_waits: vec![StopWait {
+          at_m: 3000,
+          secs: 480,
+        }],
+        surcharges: Vec::new(),
+      },
+    )
+    .unwrap();
+    // 5.5 km at 132 cents beats 635 seconds at 46 cents a minute
+    assert_eq!(amount(&fare, "tariff_a"), Some(726));
+    assert_eq!(amount(&fare, "waiting_time"), Some(368));
+    assert_eq!(fare.total_cents, 420 + 726 + 368 + 200);
+  }
+
+  #[test]
+  fn test_surcharges_are_itemised() {
+    let fare = calculate_fare(
+      &TariffTable::nta(),
+      &FareInput {
+        started_at: weekday_noon(),
+        distance_m: 500,
+        duration_secs: 60,
+        additional_passengers: 0,
+        stop_waits: Vec::new(),
+        surcharges: vec![FareItem::new("dublin_airport_surcharge", 300)],
+      },
+    )
+    .unwrap();
+    assert_eq!(amount(&fare, "dublin_airport_surcharge"), Some(300));
+    assert_eq!(fare.total_cents, 420 + 200 + 300);
+  }
+}
@@ -0,0 +1,30 @@
+pub mod fare_engine;
+pub mod model;
+pub mod rto;
+pub mod tariff_table;
+
+use model::fare_breakdown::{FareBreakdown, FareItem};
+use rto::fare_breakdown_rto::{FareBreakdownRto, FareItemRto};
+
+// Transform FareBreakdown domain to RTO
+impl From<FareBreakdown> for FareBreakdownRto {
+  fn from(fare: FareBreakdown) -> Self {
+    Self {
+      tariff_version: fare.tariff_version,
+      rate: fare.rate,
+      currency: String::from("EUR"),
+      items: fare.items.into_iter().map(FareItemRto::from).collect(),
+      total_cents: fare.total_cents,
+    }
+  }
+}
+
+// Transform FareItem domain to RTO
+impl From<FareItem> for FareItemRto {
+  fn from(item: FareItem) -> Self {
+    Self {
+      code: item.code,
+      amount_cents: item.amount_cents,
+    }
+  }
+}
@@ -0,0 +1,28 @@
+use super::rate_period::RatePeriod;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FareBreakdown {
+  pub tariff_version: String,
+  pub rate: RatePeriod,
+  // In the order they appear on a receipt
+  pub items: Vec<FareItem>,
+  pub total_cents: i64,
+  // Distance the meter charged, unknown for fares kept before it was
+  pub distance_m: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FareItem {
+  // Band name from the tariff or one of the fixed charges
+  pub code: String,
+  pub amount_cents: i64,
+}
+
+impl FareItem {
+  pub fn new(code: &str, amount_cents: i64) -> Self {
+    Self {
+      code: String::from(code),
+      amount_cents,
+    }
+  }
+}
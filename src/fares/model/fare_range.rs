@@ -0,0 +1,10 @@
+use super::rate_period::RatePeriod;
+
+// Quoted before booking, the metered fare should fall inside
+#[derive(Debug, Clone, PartialEq)]
+pub struct FareRange {
+  pub tariff_version: String,
+  pub rate: RatePeriod,
+  pub min_cents: i64,
+  pub max_cents: i64,
+}
@@ -0,0 +1,4 @@
+pub mod fare_breakdown;
+pub mod fare_range;
+pub mod rate_period;
+pub mod tariff;
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RatePeriod {
+  // Daytime from Monday to Saturday
+  #[serde(rename = "standard")]
+  Standard,
+  // Nights, Sundays and bank holidays
+  #[serde(rename = "premium")]
+  Premium,
+}
@@ -0,0 +1,60 @@
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use super::rate_period::RatePeriod;
+
+// One version of the maximum fare order, amounts in euro cents
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tariff {
+  pub version: String,
+  // Local date from which the tariff applies
+  #[serde(rename = "effectiveFrom")]
+  pub effective_from: NaiveDate,
+  // The premium rate runs overnight, from this local time to
+  // `premium_until` the next morning
+  #[serde(rename = "premiumFrom")]
+  pub premium_from: NaiveTime,
+  #[serde(rename = "premiumUntil")]
+  pub premium_until: NaiveTime,
+  pub standard: RateTable,
+  pub premium: RateTable,
+  #[serde(rename = "bookingFeeCents")]
+  pub booking_fee_cents: i64,
+  // For each passenger after the first
+  #[serde(rename = "additionalPassengerCents")]
+  pub additional_passenger_cents: i64,
+}
+
+impl Tariff {
+  pub fn rates(&self, rate: RatePeriod) -> &RateTable {
+    match rate {
+      RatePeriod::Standard => &self.standard,
+      RatePeriod::Premium => &self.premium,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateTable {
+  // Covers the first metres or seconds, whichever comes first
+  #[serde(rename = "initialChargeCents")]
+  pub initial_charge_cents: i64,
+  #[serde(rename = "initialDistanceM")]
+  pub initial_distance_m: i64,
+  #[serde(rename = "initialDurationSecs")]
+  pub initial_duration_secs: i64,
+  // Ordered by distance
+  pub bands: Vec<TariffBand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TariffBand {
+  pub name: String,
+  // Trip distance at which the next band starts, none for the last one
+  #[serde(rename = "upToM")]
+  pub up_to_m: Option<i64>,
+  #[serde(rename = "centsPerKm")]
+  pub cents_per_km: i64,
+  #[serde(rename = "centsPerMinute")]
+  pub cents_per_minute: i64,
+}
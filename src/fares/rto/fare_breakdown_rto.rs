@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fares::model::rate_period::RatePeriod;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FareItemRto {
+  pub code: String,
+  #[serde(rename = "amountCents")]
+  pub amount_cents: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FareBreakdownRto {
+  #[serde(rename = "tariffVersion")]
+  pub tariff_version: String,
+  pub rate: RatePeriod,
+  pub currency: String,
+  pub items: Vec<FareItemRto>,
+  #[serde(rename = "totalCents")]
+  pub total_cents: i64,
+}
@@ -0,0 +1 @@
+pub mod fare_breakdown_rto;
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use chrono_tz::Europe::Dublin;
+use serde::Deserialize;
+
+use super::model::rate_period::RatePeriod;
+use super::model::tariff::Tariff;
+
+// Every published tariff, rates switch over by themselves when a new
+// order comes into force
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TariffTable {
+  #[serde(rename = "bankHolidays")]
+  pub bank_holidays: Vec<NaiveDate>,
+  pub tariffs: Vec<Tariff>,
+}
+
+impl TariffTable {
+  pub fn nta() -> Self {
+    serde_json::from_str(include_str!("nta_tariffs.json"))
+      .expect("NTA tariff table is invalid")
+  }
+
+  // Latest tariff in force on the local date
+  pub fn in_effect(&self, at: DateTime<Utc>) -> Option<&Tariff> {
+    let date = at.with_timezone(&Dublin).date_naive();
+    self
+      .tariffs
+      .iter()
+      .filter(|tariff| tariff.effective_from <= date)
+      .max_by_key(|tariff| tariff.effective_from)
+  }
+
+  // Rate for a trip starting at `at`, decided on the Irish wall clock
+  pub fn rate_period(&self, tariff: &Tariff, at: DateTime<Utc>) -> RatePeriod {
+    let local = at.with_timezone(&Dublin);
+    let time = local.time();
+    let is_night = time >= tariff.premium_from || time < tariff.premium_until;
+    if is_night
+      || local.weekday() == Weekday::Sun
+      || self.bank_holidays.contains(&local.date_naive())
+    {
+      RatePeriod::Premium
+    } else {
+      RatePeriod::Standard
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn test_rate_period_follows_dublin_time() {
+    let tariffs = TariffTable::nta();
+    let rate_at = |at: DateTime<Utc>| {
+      let tariff = tariffs.in_effect(at).unwrap();
+      tariffs.rate_period(tariff, at)
+    };
+    // Tuesday 14 October 2025, 19:30 UTC is 20:30 in Dublin (IST)
+    let summer_evening = Utc.with_ymd_and_hms(2025, 10, 14, 19, 30, 0);
+    assert_eq!(rate_at(summer_evening.unwrap()), RatePeriod::Premium);
+    // Tuesday 4 November 2025, 19:30 UTC is still 19:30 (GMT)
+    let winter_evening = Utc.with_ymd_and_hms(2025, 11, 4, 19, 30, 0);
+    assert_eq!(rate_at(winter_evening.unwrap()), RatePeriod::Standard);
+    // Sunday afternoon
+    let sunday = Utc.with_ymd_and_hms(2025, 11, 2, 14, 0, 0);
+    assert_eq!(rate_at(sunday.unwrap()), RatePeriod::Premium);
+    // October bank holiday Monday
+    let bank_holiday = Utc.with_ymd_and_hms(2025, 10, 27, 12, 0, 0);
+    assert_eq!(rate_at(bank_holiday.unwrap()), RatePeriod::Premium);
+    // Early Monday morning, before the standard rate starts
+    let early = Utc.with_ymd_and_hms(2025, 11, 3, 7, 59, 0);
+    assert_eq!(rate_at(early.unwrap()), RatePeriod::Premium);
+  }
+
+  #[test]
+  fn test_in_effect_picks_latest_version() {
+    let mut tariffs = TariffTable::nta();
+    let next = Tariff {
+      version: String::from("next"),
+      effective_from: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+      ..tariffs.tariffs[0].clone()
+    };
+    tariffs.tariffs.push(next);
+
+    let before = Utc.with_ymd_and_hms(2029, 12, 31, 23, 59, 0).unwrap();
+    assert_eq!(tariffs.in_effect(before).unwrap().version, "nta-2022");
+    let after = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(tariffs.in_effect(after).unwrap().version, "next");
+    let too_early = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(tariffs.in_effect(too_early), None);
+  }
+}
@@ -20,9 +20,16 @@ pub mod tests {
   }
 
   pub fn create_fake_access_token(jwt_secret: &str) -> String {
+    create_access_token(&create_fake_access_token_claims(), jwt_secret)
+  }
+
+  pub fn create_access_token(
+    claims: &AccessTokenClaims,
+    jwt_secret: &str,
+  ) -> String {
     encode(
       &Header::new(Algorithm::HS256),
-      &create_fake_access_token_claims(),
+      claims,
       &EncodingKey::from_secret(jwt_secret.as_ref()),
     )
     .unwrap()
@@ -1,18 +1,80 @@
+mod admin;
+mod bookings;
+mod customers;
+mod dispatch;
+mod drivers;
+mod fares;
 mod helpers;
+mod ranks;
+mod receipts;
+mod routing;
 mod shared;
 mod trips;
 mod users;
+mod zones;
 
 use std::sync::Arc;
 
 use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::{middleware, web, App, HttpServer};
+use admin::{get_trip_details, search_trips};
+use bookings::booking_materialiser::spawn_booking_materialiser;
+use bookings::repository::booking_repository::{
+  BookingRepository, BookingRepositoryImpl,
+};
+use bookings::{
+  change_occurrence, create_recurring_booking, get_recurring_booking,
+  skip_occurrence,
+};
+use customers::repository::customer_repository::{
+  CustomerRepository, CustomerRepositoryImpl,
+};
+use customers::{
+  create_saved_place, delete_saved_place, get_ride_preferences,
+  get_saved_place, get_saved_places, update_ride_preferences,
+  update_saved_place,
+};
+use dispatch::dispatch_monitor::spawn_dispatch_monitor;
+use dispatch::scheduler::spawn_trip_scheduler;
+use dispatch::{get_current_offer, get_trip_offers};
+use drivers::compliance_alerts::ComplianceAlerts;
+use drivers::heartbeat_monitor::spawn_heartbeat_monitor;
+use drivers::location_store::DriverLocationStore;
+use drivers::repository::driver_repository::{
+  DriverRepository, DriverRepositoryImpl,
+};
+use drivers::{
+  driver_heartbeat, get_driver_compliance, get_driver_status,
+  get_online_drivers, get_vehicle, record_driver_locations,
+  update_driver_status, update_vehicle,
+};
+use fares::tariff_table::TariffTable;
+use receipts::repository::receipt_repository::{
+  ReceiptRepository, ReceiptRepositoryImpl,
+};
+use ranks::{get_rank_queue, get_rank_queues};
+use receipts::{email_receipt, get_receipt};
+use routing::load_router;
+use routing::router::Router;
 use shared::config::Config;
 use shared::database::Database;
+use shared::mailer::{ConsoleMailer, Mailer};
+use shared::middleware::bearer_key_extractor::BearerKeyExtractor;
+use shared::notifier::{ConsoleNotifier, Notifier};
+use trips::repository::publishing_trip_repository::PublishingTripRepository;
 use trips::repository::trip_repository::{TripRepository, TripRepositoryImpl};
-use trips::{create_trip, get_trip};
+use trips::trip_event_hub::TripEventHub;
+use trips::{
+  accept_trip, add_trip_stop, arrive_at_stop, arrive_trip, assign_trip,
+  cancel_trip, complete_trip, create_trip, decline_trip, depart_from_stop,
+  estimate_trip, get_eligible_drivers, get_trip, get_trip_events,
+  get_shared_trip, get_trip_summary, list_trips, no_show_trip, offer_trip,
+  remove_trip_stop, revoke_trip_share, share_trip, start_trip,
+  subscribe_trip,
+};
 use users::repository::user_repository::{UserRepository, UserRepositoryImpl};
 use users::{create_user, get_user};
+use zones::get_zones;
 use nanoid::nanoid;
 
 #[actix_web::main]
@@ -27,14 +89,78 @@ async fn main() -> std::io::Result<()> {
   let database = Arc::new(database);
 
   let user_repository = Arc::new(UserRepositoryImpl::new(database.clone()));
-  let trip_repository = Arc::new(TripRepositoryImpl::new(database.clone()));
+  let trip_events = Arc::new(TripEventHub::new());
+  let trip_repository = Arc::new(PublishingTripRepository::new(
+    TripRepositoryImpl::new(database.clone()),
+    Arc::clone(&trip_events),
+  ));
+  let driver_repository = Arc::new(DriverRepositoryImpl::new(database.clone()));
+  let customer_repository =
+    Arc::new(CustomerRepositoryImpl::new(database.clone()));
+  let booking_repository =
+    Arc::new(BookingRepositoryImpl::new(database.clone()));
+  let receipt_repository =
+    Arc::new(ReceiptRepositoryImpl::new(database.clone()));
+  let mailer = Arc::new(ConsoleMailer);
+  let location_store = Arc::new(DriverLocationStore::new());
+  let compliance_alerts = Arc::new(ComplianceAlerts::new());
+  let notifier = Arc::new(ConsoleNotifier);
+  let router = load_router(Config::default().road_graph_path.as_deref());
+
+  spawn_heartbeat_monitor(
+    Arc::clone(&driver_repository),
+    Arc::clone(&location_store),
+    Config::default().driver_heartbeat_timeout_secs,
+  );
+  spawn_dispatch_monitor(
+    Arc::clone(&trip_repository),
+    Arc::clone(&driver_repository),
+    Arc::clone(&location_store),
+    Config::default(),
+  );
+  spawn_trip_scheduler(
+    Arc::clone(&trip_repository),
+    Arc::clone(&driver_repository),
+    Arc::clone(&location_store),
+    Arc::clone(&notifier),
+    Config::default(),
+  );
+  spawn_booking_materialiser(
+    Arc::clone(&booking_repository),
+    Arc::clone(&trip_repository),
+    Config::default(),
+  );
 
   HttpServer::new({
     let user_repository = Arc::clone(&user_repository);
     let trip_repository = Arc::clone(&trip_repository);
+    let driver_repository = Arc::clone(&driver_repository);
+    let customer_repository = Arc::clone(&customer_repository);
+    let booking_repository = Arc::clone(&booking_repository);
+    let receipt_repository = Arc::clone(&receipt_repository);
+    let mailer = Arc::clone(&mailer);
+    let location_store = Arc::clone(&location_store);
+    let compliance_alerts = Arc::clone(&compliance_alerts);
+    let notifier = Arc::clone(&notifier);
+    let trip_events = Arc::clone(&trip_events);
+    let router = Arc::clone(&router);
     move || {
       App::new().configure(|cfg| {
-        apply_service_config(cfg, &user_repository, &trip_repository)
+        apply_service_config(
+          cfg,
+          &user_repository,
+          &trip_repository,
+          &driver_repository,
+          &customer_repository,
+          &booking_repository,
+          &receipt_repository,
+          &mailer,
+          &location_store,
+          &compliance_alerts,
+          &notifier,
+          &trip_events,
+          &router,
+        )
       })
     }
   })
@@ -44,13 +170,30 @@ async fn main() -> std::io::Result<()> {
 }
 
 // Function to initialize the App
+#[allow(clippy::too_many_arguments)]
 fn apply_service_config<
   UR: UserRepository + 'static,
   TR: TripRepository + 'static,
+  DR: DriverRepository + 'static,
+  CR: CustomerRepository + 'static,
+  BR: BookingRepository + 'static,
+  RR: ReceiptRepository + 'static,
+  M: Mailer + 'static,
+  N: Notifier + 'static,
 >(
   service_config: &mut web::ServiceConfig,
   user_repository: &Arc<UR>,
   trip_repository: &Arc<TR>,
+  driver_repository: &Arc<DR>,
+  customer_repository: &Arc<CR>,
+  booking_repository: &Arc<BR>,
+  receipt_repository: &Arc<RR>,
+  mailer: &Arc<M>,
+  location_store: &Arc<DriverLocationStore>,
+  compliance_alerts: &Arc<ComplianceAlerts>,
+  notifier: &Arc<N>,
+  trip_events: &Arc<TripEventHub>,
+  router: &Arc<dyn Router>,
 ) {
   // Rate limit
   // Allow bursts with up to five requests per IP address
@@ -61,6 +204,15 @@ fn apply_service_config<
     .finish()
     .unwrap();
 
+  // Driver apps send a batch of fixes every few seconds and flush their
+  // buffer after losing signal, limited per driver token
+  let location_governor_config = GovernorConfigBuilder::default()
+    .key_extractor(BearerKeyExtractor)
+    .requests_per_second(1)
+    .burst_size(10)
+    .finish()
+    .unwrap();
+
   let config = Config::default();
   let config = Arc::new(config);
 
@@ -68,6 +220,17 @@ fn apply_service_config<
     .app_data(web::Data::from(config.clone()))
     .app_data(web::Data::from(user_repository.clone()))
     .app_data(web::Data::from(trip_repository.clone()))
+    .app_data(web::Data::from(driver_repository.clone()))
+    .app_data(web::Data::from(customer_repository.clone()))
+    .app_data(web::Data::from(booking_repository.clone()))
+    .app_data(web::Data::from(receipt_repository.clone()))
+    .app_data(web::Data::from(mailer.clone()))
+    .app_data(web::Data::from(location_store.clone()))
+    .app_data(web::Data::from(compliance_alerts.clone()))
+    .app_data(web::Data::from(notifier.clone()))
+    .app_data(web::Data::from(trip_events.clone()))
+    .app_data(web::Data::from(router.clone()))
+    .app_data(web::Data::new(TariffTable::nta()))
     .service(
       web::scope("/v1")
         .wrap(middleware::Logger::default())
@@ -80,8 +243,128 @@ fn apply_service_config<
         .service(
           web::scope("/trips")
             .wrap(Governor::new(&governor_config))
+            .route("/summary", web::get().to(get_trip_summary::<TR>))
             .route("/{uuid}", web::get().to(get_trip::<TR>))
-            .route("", web::post().to(create_trip::<TR>)),
+            .route("/{uuid}/ws", web::get().to(subscribe_trip::<TR>))
+            .route("/{uuid}/events", web::get().to(get_trip_events::<TR>))
+            .route(
+              "/{uuid}/eligible-drivers",
+              web::get().to(get_eligible_drivers::<TR, DR>),
+            )
+            .route("/{uuid}/offer", web::post().to(offer_trip::<TR, DR>))
+            .route("/{uuid}/assign", web::post().to(assign_trip::<TR, DR>))
+            .route("/{uuid}/accept", web::post().to(accept_trip::<TR, DR>))
+            .route("/{uuid}/decline", web::post().to(decline_trip::<TR, DR>))
+            .route("/{uuid}/arrive", web::post().to(arrive_trip::<TR>))
+            .route("/{uuid}/start", web::post().to(start_trip::<TR>))
+            .route("/{uuid}/complete", web::post().to(complete_trip::<TR>))
+            .route("/{uuid}/no-show", web::post().to(no_show_trip::<TR>))
+            .route("/{uuid}/cancel", web::post().to(cancel_trip::<TR>))
+            .route("/{uuid}/stops", web::post().to(add_trip_stop::<TR>))
+            .route(
+              "/{uuid}/stops/{stop_uuid}",
+              web::delete().to(remove_trip_stop::<TR>),
+            )
+            .route(
+              "/{uuid}/stops/{stop_uuid}/arrive",
+              web::post().to(arrive_at_stop::<TR>),
+            )
+            .route(
+              "/{uuid}/stops/{stop_uuid}/depart",
+              web::post().to(depart_from_stop::<TR>),
+            )
+            .route("/{uuid}/shares", web::post().to(share_trip::<TR>))
+            .route(
+              "/{uuid}/shares/{token}",
+              web::delete().to(revoke_trip_share::<TR>),
+            )
+            .route("/{uuid}/offers", web::get().to(get_trip_offers::<TR>))
+            .route("/{uuid}/receipt", web::get().to(get_receipt::<TR, DR, RR>))
+            .route(
+              "/{uuid}/receipt/email",
+              web::post().to(email_receipt::<TR, DR, RR, M>),
+            )
+            .route("/estimate", web::post().to(estimate_trip::<TR>))
+            .route("", web::get().to(list_trips::<TR>))
+            .route("", web::post().to(create_trip::<TR, CR, DR>)),
+        )
+        // Public, followers of a shared trip have no account
+        .service(
+          web::scope("/shared-trips")
+            .wrap(Governor::new(&governor_config))
+            .route("/{token}", web::get().to(get_shared_trip::<TR, UR>)),
+        )
+        // Registered before "/drivers" so only its own rate limit applies
+        .service(
+          web::scope("/drivers/me/locations")
+            .wrap(Governor::new(&location_governor_config))
+            .route("", web::post().to(record_driver_locations::<TR, DR>)),
+        )
+        .service(
+          web::scope("/drivers")
+            .wrap(Governor::new(&governor_config))
+            .route("/online", web::get().to(get_online_drivers::<DR>))
+            .route("/me/offer", web::get().to(get_current_offer::<TR>))
+            .route("/me/status", web::get().to(get_driver_status::<DR>))
+            .route("/me/status", web::put().to(update_driver_status::<DR>))
+            .route("/me/heartbeat", web::post().to(driver_heartbeat::<DR, N>))
+            .route("/me/vehicle", web::get().to(get_vehicle::<DR>))
+            .route("/me/vehicle", web::put().to(update_vehicle::<DR>))
+            .route(
+              "/{uuid}/compliance",
+              web::get().to(get_driver_compliance::<DR>),
+            ),
+        )
+        .service(
+          web::scope("/zones")
+            .wrap(Governor::new(&governor_config))
+            .route("", web::get().to(get_zones)),
+        )
+        .service(
+          web::scope("/ranks")
+            .wrap(Governor::new(&governor_config))
+            .route("", web::get().to(get_rank_queues))
+            .route("/{code}", web::get().to(get_rank_queue)),
+        )
+        .service(
+          web::scope("/recurring-bookings")
+            .wrap(Governor::new(&governor_config))
+            .route("/{uuid}", web::get().to(get_recurring_booking::<BR>))
+            .route(
+              "/{uuid}/occurrences/{date}",
+              web::put().to(change_occurrence::<BR, TR>),
+            )
+            .route(
+              "/{uuid}/occurrences/{date}/skip",
+              web::post().to(skip_occurrence::<BR, TR>),
+            )
+            .route("", web::post().to(create_recurring_booking::<BR, TR, CR>)),
+        )
+        .service(
+          web::scope("/admin")
+            .wrap(Governor::new(&governor_config))
+            .route("/trips", web::get().to(search_trips::<TR>))
+            .route(
+              "/trips/{uuid}",
+              web::get().to(get_trip_details::<TR, DR>),
+            ),
+        )
+        .service(
+          web::scope("/customers")
+            .wrap(Governor::new(&governor_config))
+            .route("/me/preferences", web::get().to(get_ride_preferences::<CR>))
+            .route(
+              "/me/preferences",
+              web::put().to(update_ride_preferences::<CR>),
+            )
+            .route("/me/places", web::get().to(get_saved_places::<CR>))
+            .route("/me/places", web::post().to(create_saved_place::<CR>))
+            .route("/me/places/{uuid}", web::get().to(get_saved_place::<CR>))
+            .route("/me/places/{uuid}", web::put().to(update_saved_place::<CR>))
+            .route(
+              "/me/places/{uuid}",
+              web::delete().to(delete_saved_place::<CR>),
+            ),
         ),
     );
 }
@@ -103,7 +386,12 @@ fn custom_nanoid() -> String {
 mod tests {
   use super::*;
   use actix_web::{http::header::HeaderValue, test, App};
+  use bookings::repository::booking_repository::tests::InMemoryBookingRepository;
+  use customers::repository::customer_repository::tests::InMemoryCustomerRepository;
+  use drivers::repository::driver_repository::tests::InMemoryDriverRepository;
   use helpers::tests::create_fake_access_token;
+  use receipts::repository::receipt_repository::tests::InMemoryReceiptRepository;
+  use routing::haversine_router::HaversineRouter;
   use shared::{role::Role, rto::created_rto::CreatedRto};
   use std::{env, net::SocketAddr, str::FromStr};
   use trips::repository::trip_repository::tests::InMemoryTripRepository;
@@ -124,13 +412,47 @@ mod tests {
 
     let user_repository = Arc::new(InMemoryUserRepository::new());
     let trip_repository = Arc::new(InMemoryTripRepository::new());
+    let driver_repository = Arc::new(InMemoryDriverRepository::new());
+    let customer_repository = Arc::new(InMemoryCustomerRepository::new());
+    let booking_repository = Arc::new(InMemoryBookingRepository::new());
+    let receipt_repository = Arc::new(InMemoryReceiptRepository::new());
+    let mailer = Arc::new(ConsoleMailer);
+    let location_store = Arc::new(DriverLocationStore::new());
+    let compliance_alerts = Arc::new(ComplianceAlerts::new());
+    let notifier = Arc::new(ConsoleNotifier);
+    let trip_events = Arc::new(TripEventHub::new());
+    let router: Arc<dyn Router> = Arc::new(HaversineRouter);
 
     // Initialize the service in-memory
     let app = test::init_service({
       let user_repository = Arc::clone(&user_repository);
       let trip_repository = Arc::clone(&trip_repository);
+      let driver_repository = Arc::clone(&driver_repository);
+      let customer_repository = Arc::clone(&customer_repository);
+      let booking_repository = Arc::clone(&booking_repository);
+      let receipt_repository = Arc::clone(&receipt_repository);
+      let mailer = Arc::clone(&mailer);
+      let location_store = Arc::clone(&location_store);
+      let compliance_alerts = Arc::clone(&compliance_alerts);
+      let notifier = Arc::clone(&notifier);
+      let trip_events = Arc::clone(&trip_events);
+      let router = Arc::clone(&router);
       App::new().configure(|cfg| {
-        apply_service_config(cfg, &user_repository, &trip_repository)
+        apply_service_config(
+          cfg,
+          &user_repository,
+          &trip_repository,
+          &driver_repository,
+          &customer_repository,
+          &booking_repository,
+          &receipt_repository,
+          &mailer,
+          &location_store,
+          &compliance_alerts,
+          &notifier,
+          &trip_events,
+          &router,
+        )
       })
     })
     .await;
@@ -0,0 +1,201 @@
+pub mod dto;
+pub mod model;
+pub mod rank_queues;
+pub mod rto;
+
+use actix_web::{web, HttpResponse, Responder};
+use dto::get_rank_dto::GetRankDto;
+use model::taxi_rank::TaxiRank;
+use rank_queues::QueuedDriver;
+use rto::rank_queue_rto::{QueuedDriverRto, RankQueueRto};
+use validator::Validate;
+
+use crate::drivers::location_store::DriverLocationStore;
+use crate::shared::config::Config;
+use crate::shared::http_error::HttpError;
+use crate::shared::role::Role;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+// Every rank with its queue as it is right now. Managers see who is
+// waiting, drivers only how long each queue is and their own place.
+pub async fn get_rank_queues(
+  config: web::Data<Config>,
+  location_store: web::Data<DriverLocationStore>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if !can_view_queues(&auth) {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let queues: Vec<RankQueueRto> = config
+    .ranks
+    .taxi_ranks
+    .iter()
+    .map(|rank| {
+      rank_queue_rto(rank, location_store.rank_queue(&rank.code), &auth)
+    })
+    .collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(queues)
+}
+
+pub async fn get_rank_queue(
+  config: web::Data<Config>,
+  location_store: web::Data<DriverLocationStore>,
+  path: web::Path<GetRankDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if !can_view_queues(&auth) {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let Some(rank) = config.ranks.find(&path.code) else {
+    return HttpResponse::NotFound()
+      .content_type("application/json")
+      .json(HttpError::from("Rank not found"));
+  };
+  let queue = location_store.rank_queue(&rank.code);
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(rank_queue_rto(rank, queue, &auth))
+}
+
+fn can_view_queues(auth: &AccessTokenClaims) -> bool {
+  auth.role == Role::Driver
+    || auth.role == Role::Admin
+    || auth.role == Role::Manager
+}
+
+fn rank_queue_rto(
+  rank: &TaxiRank,
+  queue: Vec<QueuedDriver>,
+  auth: &AccessTokenClaims,
+) -> RankQueueRto {
+  let is_staff = auth.role == Role::Admin || auth.role == Role::Manager;
+  let position = queue
+    .iter()
+    .position(|queued| queued.driver_uuid == auth.uuid)
+    .map(|index| index + 1);
+  RankQueueRto {
+    code: rank.code.clone(),
+    name: rank.name.clone(),
+    center: rank.center,
+    radius_m: rank.radius_m,
+    length: queue.len(),
+    position,
+    drivers: queue
+      .into_iter()
+      .filter(|_| is_staff)
+      .enumerate()
+      .map(|(index, queued)| QueuedDriverRto {
+        driver_uuid: queued.driver_uuid,
+        position: index + 1,
+        joined_at: queued.joined_at,
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::{Duration, Utc};
+
+  use crate::custom_nanoid;
+  use crate::drivers::model::location_fix::LocationFix;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::shared::geo_point::GeoPoint;
+
+  use super::*;
+
+  #[actix_web::test]
+  async fn test_rank_queue_views() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let heuston = GeoPoint {
+      lat: 53.3464,
+      lng: -6.2927,
+    };
+    let mut config = Config::default();
+    config.ranks.taxi_ranks = vec![TaxiRank {
+      code: String::from("heuston"),
+      name: String::from("Heuston Station"),
+      center: heuston,
+      radius_m: 100.0,
+      pickup_radius_m: 300.0,
+    }];
+    let config = web::Data::new(config);
+    let location_store = web::Data::new(DriverLocationStore::new());
+    let drivers: Vec<AccessTokenClaims> = (0..2)
+      .map(|_| AccessTokenClaims {
+        role: Role::Driver,
+        ..create_fake_access_token_claims()
+      })
+      .collect();
+    let arrived_at = Utc::now() - Duration::minutes(5);
+    for (index, driver) in drivers.iter().enumerate() {
+      location_store.set_available(&driver.uuid, true);
+      for recorded_at in [arrived_at, Utc::now()] {
+        location_store.record(LocationFix {
+          driver_uuid: driver.uuid.clone(),
+          coords: heuston,
+          accuracy_m: 5.0,
+          heading_deg: None,
+          speed_mps: None,
+          recorded_at: recorded_at + Duration::seconds(index as i64),
+        });
+        location_store.track_ranks(&config.ranks, &driver.uuid, true);
+      }
+    }
+
+    let responder = get_rank_queue(
+      config.clone(),
+      location_store.clone(),
+      web::Path::from(GetRankDto {
+        code: String::from("heuston"),
+      }),
+      drivers[1].clone(),
+    )
+    .await;
+    let rto: RankQueueRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.length, 2);
+    assert_eq!(rto.position, Some(2));
+    assert!(rto.drivers.is_empty());
+
+    let responder = get_rank_queues(
+      config.clone(),
+      location_store.clone(),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rtos: Vec<RankQueueRto> =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    let driver_uuids: Vec<&str> = rtos[0]
+      .drivers
+      .iter()
+      .map(|queued| queued.driver_uuid.as_str())
+      .collect();
+    assert_eq!(
+      driver_uuids,
+      vec![drivers[0].uuid.as_str(), drivers[1].uuid.as_str()]
+    );
+
+    let responder = get_rank_queues(
+      config,
+      location_store,
+      AccessTokenClaims {
+        role: Role::Customer,
+        ..create_fake_access_token_claims()
+      },
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+  }
+}
@@ -0,0 +1 @@
+pub mod taxi_rank;
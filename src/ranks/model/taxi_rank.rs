@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+
+// Physical rank drivers queue at, e.g. a train station or the airport
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TaxiRank {
+  // Short stable code, e.g. "heuston"
+  pub code: String,
+  pub name: String,
+  pub center: GeoPoint,
+  // Drivers inside the circle are at the rank
+  pub radius_m: f64,
+  // Pickups this close to the rank are offered to its queue first
+  pub pickup_radius_m: f64,
+}
+
+impl TaxiRank {
+  pub fn distance_m(&self, point: &GeoPoint) -> f64 {
+    self.center.distance_km(point) * 1000.0
+  }
+
+  pub fn contains(&self, point: &GeoPoint) -> bool {
+    self.distance_m(point) <= self.radius_m
+  }
+
+  pub fn serves(&self, pickup: &GeoPoint) -> bool {
+    self.distance_m(pickup) <= self.pickup_radius_m
+  }
+}
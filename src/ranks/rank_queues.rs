@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::model::taxi_rank::TaxiRank;
+use crate::drivers::model::location_fix::LocationFix;
+use crate::shared::config::env_or;
+use crate::shared::geo_point::GeoPoint;
+use crate::trips::model::trip_status::TripStatus;
+
+// Drivers with a pending offer keep their place until they accept it
+pub const ON_TRIP_STATUSES: [TripStatus; 3] = [
+  TripStatus::Accepted,
+  TripStatus::DriverArrived,
+  TripStatus::InProgress,
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RankRules {
+  // Drivers only passing through a rank are not queued
+  pub join_after_secs: i64,
+  // Slack beyond the rank radius so GPS jitter does not drop drivers
+  pub leave_margin_m: f64,
+  pub taxi_ranks: Vec<TaxiRank>,
+}
+
+impl RankRules {
+  pub fn find(&self, code: &str) -> Option<&TaxiRank> {
+    self.taxi_ranks.iter().find(|rank| rank.code == code)
+  }
+}
+
+impl Default for RankRules {
+  fn default() -> Self {
+    Self {
+      join_after_secs: env_or("RANK_JOIN_AFTER_SECS", 60),
+      leave_margin_m: env_or("RANK_LEAVE_MARGIN_M", 50.0),
+      taxi_ranks: env::var("TAXI_RANKS_PATH")
+        .map(|path| load_taxi_ranks(&path))
+        .unwrap_or_default(),
+    }
+  }
+}
+
+// JSON array of ranks. A missing or broken file is reported and nobody
+// is queued.
+fn load_taxi_ranks(path: &str) -> Vec<TaxiRank> {
+  let ranks = fs::read_to_string(path)
+    .map_err(|error| error.to_string())
+    .and_then(|json| {
+      serde_json::from_str(&json).map_err(|error| error.to_string())
+    });
+  match ranks {
+    Ok(ranks) => ranks,
+    Err(error) => {
+      println!("Cannot load taxi ranks from {}: {}", path, error);
+      Vec::new()
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedDriver {
+  pub driver_uuid: String,
+  // When the driver entered the rank, the queue is in this order
+  pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct Arrival {
+  rank_code: String,
+  entered_at: DateTime<Utc>,
+}
+
+// First in first out queue of every rank. Only kept in memory, drivers
+// queue again from their next fixes after a restart.
+#[derive(Debug, Default)]
+pub struct RankQueues {
+  // Drivers inside a rank who have not stayed long enough to join
+  arrivals: HashMap<String, Arrival>,
+  queues: HashMap<String, Vec<QueuedDriver>>,
+}
+
+impl RankQueues {
+  // Moves the driver in or out of the queues from their latest position.
+  // Drivers who cannot take a trip are never queued.
+  pub fn observe(&mut self, rules: &RankRules, fix: &LocationFix, free: bool) {
+    let driver_uuid = &fix.driver_uuid;
+    if !free {
+      self.leave(driver_uuid);
+      return;
+    }
+    if let Some(rank_code) = self.rank_of(driver_uuid) {
+      let stays = rules.find(&rank_code).is_some_and(|rank| {
+        rank.distance_m(&fix.coords) <= rank.radius_m + rules.leave_margin_m
+      });
+      if stays {
+        return;
+      }
+      self.leave(driver_uuid);
+    }
+    let Some(rank) = rules
+      .taxi_ranks
+      .iter()
+      .find(|rank| rank.contains(&fix.coords))
+    else {
+      self.arrivals.remove(driver_uuid);
+      return;
+    };
+    let joined_at = match self.arrivals.get(driver_uuid) {
+      Some(arrival) if arrival.rank_code == rank.code => arrival.entered_at,
+      _ => {
+        let arrival = Arrival {
+          rank_code: rank.code.clone(),
+          entered_at: fix.recorded_at,
+        };
+        self.arrivals.insert(driver_uuid.clone(), arrival);
+        fix.recorded_at
+      }
+    };
+    if fix.recorded_at - joined_at < Duration::seconds(rules.join_after_secs) {
+      return;
+    }
+    self.arrivals.remove(driver_uuid);
+    let queue = self.queues.entry(rank.code.clone()).or_default();
+    let position =
+      queue.partition_point(|queued| queued.joined_at <= joined_at);
+    queue.insert(
+      position,
+      QueuedDriver {
+        driver_uuid: driver_uuid.clone(),
+        joined_at,
+      },
+    );
+  }
+
+  pub fn leave(&mut self, driver_uuid: &str) {
+    self.arrivals.remove(driver_uuid);
+    for queue in self.queues.values_mut() {
+      queue.retain(|queued| queued.driver_uuid != driver_uuid);
+    }
+  }
+
+  pub fn queue(&self, rank_code: &str) -> Vec<QueuedDriver> {
+    self.queues.get(rank_code).cloned().unwrap_or_default()
+  }
+
+  // Drivers queued at the ranks serving the pickup, closest rank first
+  pub fn queued_for(
+    &self,
+    rules: &RankRules,
+    pickup: &GeoPoint,
+  ) -> Vec<String> {
+    let mut ranks: Vec<&TaxiRank> = rules
+      .taxi_ranks
+      .iter()
+      .filter(|rank| rank.serves(pickup))
+      .collect();
+    ranks.sort_by(|a, b| a.distance_m(pickup).total_cmp(&b.distance_m(pickup)));
+    ranks
+      .into_iter()
+      .flat_map(|rank| self.queue(&rank.code))
+      .map(|queued| queued.driver_uuid)
+      .collect()
+  }
+
+  fn rank_of(&self, driver_uuid: &str) -> Option<String> {
+    self
+      .queues
+      .iter()
+      .find(|(_, queue)| {
+        queue.iter().any(|queued| queued.driver_uuid == driver_uuid)
+      })
+      .map(|(rank_code, _)| rank_code.clone())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  // Heuston Station
+  const RANK: GeoPoint = GeoPoint {
+    lat: 53.3464,
+    lng: -6.2927,
+  };
+
+  fn rules() -> RankRules {
+    RankRules {
+      join_after_secs: 60,
+      leave_margin_m: 50.0,
+      taxi_ranks: vec![TaxiRank {
+        code: String::from("heuston"),
+        name: String::from("Heuston Station"),
+        center: RANK,
+        radius_m: 100.0,
+        pickup_radius_m: 300.0,
+      }],
+    }
+  }
+
+  // One degree of latitude is about 111 km
+  fn fix(driver_uuid: &str, m_north: f64, secs: i64) -> LocationFix {
+    LocationFix {
+      driver_uuid: String::from(driver_uuid),
+      coords: GeoPoint {
+        lat: RANK.lat + m_north / 111_000.0,
+        lng: RANK.lng,
+      },
+      accuracy_m: 5.0,
+      heading_deg: None,
+      speed_mps: None,
+      recorded_at: Utc.with_ymd_and_hms(2025, 11, 5, 12, 0, 0).unwrap()
+        + Duration::seconds(secs),
+    }
+  }
+
+  fn queued(queues: &RankQueues) -> Vec<String> {
+    queues
+      .queue("heuston")
+      .into_iter()
+      .map(|queued| queued.driver_uuid)
+      .collect()
+  }
+
+  #[test]
+  fn test_drivers_join_after_staying_and_leave_when_moving_away() {
+    let rules = rules();
+    let mut queues = RankQueues::default();
+    queues.observe(&rules, &fix("driver", 50.0, 0), true);
+    queues.observe(&rules, &fix("driver", 20.0, 30), true);
+    assert!(queued(&queues).is_empty());
+    queues.observe(&rules, &fix("driver", 20.0, 60), true);
+    assert_eq!(queued(&queues), vec!["driver"]);
+
+    // Within the margin the driver keeps their place
+    queues.observe(&rules, &fix("driver", 140.0, 90), true);
+    assert_eq!(queued(&queues), vec!["driver"]);
+    queues.observe(&rules, &fix("driver", 200.0, 120), true);
+    assert!(queued(&queues).is_empty());
+
+    // Driving through does not count
+    queues.observe(&rules, &fix("driver", 0.0, 150), true);
+    queues.observe(&rules, &fix("driver", 500.0, 180), true);
+    queues.observe(&rules, &fix("driver", 0.0, 200), true);
+    assert!(queued(&queues).is_empty());
+  }
+
+  #[test]
+  fn test_queue_is_first_in_first_out() {
+    let rules = rules();
+    let mut queues = RankQueues::default();
+    queues.observe(&rules, &fix("first", 0.0, 0), true);
+    queues.observe(&rules, &fix("second", 0.0, 10), true);
+    queues.observe(&rules, &fix("third", 0.0, 20), true);
+    // The second driver happens to report first once settled
+    queues.observe(&rules, &fix("second", 0.0, 80), true);
+    queues.observe(&rules, &fix("first", 0.0, 90), true);
+    queues.observe(&rules, &fix("third", 0.0, 90), true);
+    assert_eq!(queued(&queues), vec!["first", "second", "third"]);
+
+    // Taking a trip gives up the place
+    queues.observe(&rules, &fix("first", 0.0, 100), false);
+    assert_eq!(queued(&queues), vec!["second", "third"]);
+    let nearby = GeoPoint {
+      lat: RANK.lat + 0.002,
+      lng: RANK.lng,
+    };
+    assert_eq!(queues.queued_for(&rules, &nearby), vec!["second", "third"]);
+    let elsewhere = GeoPoint {
+      lat: RANK.lat + 0.01,
+      lng: RANK.lng,
+    };
+    assert!(queues.queued_for(&rules, &elsewhere).is_empty());
+  }
+}
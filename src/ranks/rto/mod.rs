@@ -0,0 +1 @@
+pub mod rank_queue_rto;
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueuedDriverRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  // Starts at 1 for the driver at the head of the queue
+  pub position: usize,
+  #[serde(rename = "joinedAt")]
+  pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankQueueRto {
+  pub code: String,
+  pub name: String,
+  pub center: GeoPoint,
+  #[serde(rename = "radiusM")]
+  pub radius_m: f64,
+  pub length: usize,
+  // Place of the driver asking, when they are in the queue
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub position: Option<usize>,
+  // Only shown to managers
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub drivers: Vec<QueuedDriverRto>,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EmailReceiptDto {
+  #[validate(email)]
+  pub email: String,
+}
@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub enum ReceiptFormat {
+  #[default]
+  #[serde(rename = "json")]
+  Json,
+  #[serde(rename = "html")]
+  Html,
+  #[serde(rename = "pdf")]
+  Pdf,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetReceiptDto {
+  #[serde(default)]
+  pub format: ReceiptFormat,
+}
@@ -0,0 +1,2 @@
+pub mod email_receipt_dto;
+pub mod get_receipt_dto;
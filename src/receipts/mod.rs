@@ -0,0 +1,437 @@
+pub mod dto;
+pub mod model;
+pub mod receipt_html;
+pub mod receipt_pdf;
+pub mod repository;
+pub mod rto;
+
+use actix_web::http::header::{ContentDisposition, DispositionParam};
+use actix_web::http::header::{DispositionType, CONTENT_DISPOSITION};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use dto::email_receipt_dto::EmailReceiptDto;
+use dto::get_receipt_dto::{GetReceiptDto, ReceiptFormat};
+use model::receipt_document::ReceiptDocument;
+use receipt_html::render_html;
+use receipt_pdf::render_pdf;
+use repository::receipt_repository::ReceiptRepository;
+use rto::receipt_rto::{OperatorRto, ReceiptRto, RouteSummaryRto};
+use validator::Validate;
+
+use crate::drivers::repository::driver_repository::DriverRepository;
+use crate::fares::rto::fare_breakdown_rto::FareBreakdownRto;
+use crate::shared::config::Config;
+use crate::shared::http_error::HttpError;
+use crate::shared::mailer::{Attachment, Mail, Mailer};
+use crate::trips::can_view_trip;
+use crate::trips::dto::get_trip_dto::GetTripDto;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::repository::trip_repository::TripRepository;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+enum ReceiptError {
+  TripNotFound,
+  TripNotCompleted,
+  IssueFailed,
+}
+
+// Itemised receipt of a completed trip, as JSON, an HTML page or a PDF
+pub async fn get_receipt<
+  TR: TripRepository,
+  DR: DriverRepository,
+  RR: ReceiptRepository,
+>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  receipt_repository: web::Data<RR>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  query: web::Query<GetReceiptDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let document = match receipt_document(
+    &**trip_repository,
+    &**driver_repository,
+    &**receipt_repository,
+    &config,
+    &path.uuid,
+    &auth,
+  )
+  .await
+  {
+    Ok(document) => document,
+    Err(error) => return failed_receipt(error),
+  };
+  match query.format {
+    ReceiptFormat::Json => HttpResponse::Ok()
+      .content_type("application/json")
+      .json(ReceiptRto::from(document)),
+    ReceiptFormat::Html => HttpResponse::Ok()
+      .content_type("text/html; charset=utf-8")
+      .body(render_html(&document)),
+    ReceiptFormat::Pdf => HttpResponse::Ok()
+      .content_type("application/pdf")
+      .insert_header((
+        CONTENT_DISPOSITION,
+        ContentDisposition {
+          disposition: DispositionType::Inline,
+          parameters: vec![DispositionParam::Filename(pdf_file_name(
+            &document,
+          ))],
+        },
+      ))
+      .body(render_pdf(&document)),
+  }
+}
+
+// Sends the receipt as an HTML email with the PDF attached
+#[allow(clippy::too_many_arguments)]
+pub async fn email_receipt<
+  TR: TripRepository,
+  DR: DriverRepository,
+  RR: ReceiptRepository,
+  M: Mailer,
+>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  receipt_repository: web::Data<RR>,
+  mailer: web::Data<M>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  dto: web::Json<EmailReceiptDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  let validations = [path.validate(), dto.validate()];
+  if let Some(Err(validation_errors)) =
+    validations.into_iter().find(Result::is_err)
+  {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let document = match receipt_document(
+    &**trip_repository,
+    &**driver_repository,
+    &**receipt_repository,
+    &config,
+    &path.uuid,
+    &auth,
+  )
+  .await
+  {
+    Ok(document) => document,
+    Err(error) => return failed_receipt(error),
+  };
+  let mail = Mail {
+    to: dto.into_inner().email,
+    subject: format!(
+      "Your {} receipt {}",
+      document.operator.name, document.reference
+    ),
+    html_body: render_html(&document),
+    attachments: vec![Attachment {
+      file_name: pdf_file_name(&document),
+      content_type: String::from("application/pdf"),
+      content: render_pdf(&document),
+    }],
+  };
+  match mailer.send(mail).await {
+    Ok(()) => HttpResponse::Accepted().finish(),
+    Err(_) => HttpResponse::InternalServerError().finish(),
+  }
+}
+
+// Issues the receipt number on first use, only to whoever can see the trip
+async fn receipt_document<
+  TR: TripRepository,
+  DR: DriverRepository,
+  RR: ReceiptRepository,
+>(
+  trip_repository: &TR,
+  driver_repository: &DR,
+  receipt_repository: &RR,
+  config: &Config,
+  trip_uuid: &str,
+  auth: &AccessTokenClaims,
+) -> Result<ReceiptDocument, ReceiptError> {
+  let trip = trip_repository
+    .find_one(trip_uuid)
+    .await
+    .filter(|trip| can_view_trip(trip, auth))
+    .ok_or(ReceiptError::TripNotFound)?;
+  if trip.status != TripStatus::Completed || trip.fare.is_none() {
+    return Err(ReceiptError::TripNotCompleted);
+  }
+  let receipt = receipt_repository
+    .issue(&trip.uuid, &config.organisation.code, Utc::now())
+    .await
+    .map_err(|_| ReceiptError::IssueFailed)?;
+  let spsv_licence = match &trip.driver_uuid {
+    Some(driver_uuid) => driver_repository
+      .find_vehicle(driver_uuid)
+      .await
+      .and_then(|vehicle| vehicle.spsv_licence),
+    None => None,
+  };
+  ReceiptDocument::new(&receipt, &config.organisation, trip, spsv_licence)
+    .ok_or(ReceiptError::TripNotCompleted)
+}
+
+fn pdf_file_name(document: &ReceiptDocument) -> String {
+  format!("receipt-{}.pdf", document.reference)
+}
+
+fn failed_receipt(error: ReceiptError) -> HttpResponse {
+  match error {
+    ReceiptError::TripNotFound => HttpResponse::NotFound()
+      .content_type("application/json")
+      .json(HttpError::from("Trip not found")),
+    ReceiptError::TripNotCompleted => HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "trip_not_completed",
+        "Receipts are only issued for completed trips",
+      )),
+    ReceiptError::IssueFailed => HttpResponse::InternalServerError().finish(),
+  }
+}
+
+// Transform ReceiptDocument domain to RTO
+impl From<ReceiptDocument> for ReceiptRto {
+  fn from(document: ReceiptDocument) -> Self {
+    Self {
+      receipt_number: document.reference,
+      issued_at: document.issued_at,
+      operator: OperatorRto {
+        name: document.operator.name,
+        address: document.operator.address,
+        vat_number: document.operator.vat_number,
+      },
+      trip_uuid: document.trip_uuid,
+      driver_spsv_licence: document.driver_spsv_licence,
+      vehicle_plate: document.vehicle_plate,
+      route: RouteSummaryRto {
+        start_coords: document.route.start_coords,
+        stops: document.route.stops,
+        end_coords: document.route.end_coords,
+        started_at: document.route.started_at,
+        ended_at: document.route.ended_at,
+        distance_m: document.route.distance_m,
+      },
+      fare: FareBreakdownRto::from(document.fare),
+      payment_method: document.payment_method,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::body::to_bytes;
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::Duration;
+
+  use super::*;
+  use crate::custom_nanoid;
+  use crate::drivers::model::vehicle::Vehicle;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+  use crate::fares::tariff_table::TariffTable;
+  use crate::helpers::tests::{
+    create_fake_access_token_claims, http_request, parse_http_response,
+  };
+  use crate::receipts::repository::receipt_repository::tests::InMemoryReceiptRepository;
+  use crate::routing::haversine_router::HaversineRouter;
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::mailer::tests::RecordingMailer;
+  use crate::shared::ride_requirements::RideRequirements;
+  use crate::shared::role::Role;
+  use crate::trips::model::payment_method::PaymentMethod;
+  use crate::trips::model::Trip;
+  use crate::trips::repository::trip_repository::tests::InMemoryTripRepository;
+  use crate::trips::repository::trip_repository::CreateTrip;
+
+  async fn create_completed_trip(
+    trip_repository: &InMemoryTripRepository,
+    consumer_uuid: &str,
+    driver_uuid: &str,
+  ) -> Trip {
+    let trip = trip_repository
+      .create(CreateTrip {
+        uuid: custom_nanoid(),
+        start_coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+        end_coords: GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        },
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: String::from(consumer_uuid),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+        booking: None,
+      })
+      .await
+      .unwrap();
+    let now = Utc::now();
+    let started_at = now - Duration::minutes(25);
+    let completed = trip
+      .offer(driver_uuid, None, started_at, now)
+      .unwrap()
+      .accept(Some(String::from("241-D-12345")), None, started_at)
+      .unwrap()
+      .transition(TripStatus::DriverArrived, None, started_at)
+      .unwrap()
+      .transition(TripStatus::InProgress, None, started_at)
+      .unwrap()
+      .complete(
+        &TariffTable::nta(),
+        &HaversineRouter,
+        &[],
+        Some(PaymentMethod::Card),
+        None,
+        now,
+      )
+      .unwrap();
+    let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+    let index = trips
+      .iter()
+      .position(|trip| trip.uuid == completed.uuid)
+      .unwrap();
+    trips[index] = completed.clone();
+    completed
+  }
+
+  #[actix_web::test]
+  async fn test_trip_receipts() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let driver_uuid = custom_nanoid();
+    let trip_repository = InMemoryTripRepository::new();
+    let first =
+      create_completed_trip(&trip_repository, &customer.uuid, &driver_uuid)
+        .await;
+    let second =
+      create_completed_trip(&trip_repository, &customer.uuid, &driver_uuid)
+        .await;
+    let driver_repository = InMemoryDriverRepository::new();
+    driver_repository
+      .save_vehicle(Vehicle {
+        driver_uuid: driver_uuid.clone(),
+        plate: String::from("241-D-12345"),
+        wheelchair_accessible: false,
+        assistance_dog: false,
+        child_seat: false,
+        spsv_licence: Some(String::from("SPSV12345")),
+      })
+      .await
+      .unwrap();
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+    let driver_repository = web::Data::from(Arc::new(driver_repository));
+    let receipt_repository =
+      web::Data::from(Arc::new(InMemoryReceiptRepository::new()));
+    let config = web::Data::new(Config::default());
+    let get = |uuid: &str, format: &str, auth: AccessTokenClaims| {
+      get_receipt(
+        trip_repository.clone(),
+        driver_repository.clone(),
+        receipt_repository.clone(),
+        config.clone(),
+        web::Path::from(GetTripDto {
+          uuid: String::from(uuid),
+        }),
+        web::Query::from_query(&format!("format={format}")).unwrap(),
+        auth,
+      )
+    };
+
+    // Numbers follow the order receipts are first asked for
+    let code = &config.organisation.code;
+    for (trip, number) in [(&second, 1), (&first, 2), (&second, 1)] {
+      let responder = get(&trip.uuid, "json", customer.clone()).await;
+      let rto: ReceiptRto =
+        parse_http_response(responder, &request, StatusCode::OK).await;
+      assert_eq!(rto.receipt_number, format!("{code}-{number:06}"));
+      assert_eq!(rto.trip_uuid, trip.uuid);
+      assert_eq!(rto.driver_spsv_licence.as_deref(), Some("SPSV12345"));
+      assert_eq!(rto.vehicle_plate.as_deref(), Some("241-D-12345"));
+      assert_eq!(rto.payment_method, Some(PaymentMethod::Card));
+      assert_eq!(
+        rto.fare.total_cents,
+        trip.fare.as_ref().unwrap().total_cents
+      );
+      // Printed as charged, not routed again
+      assert_eq!(rto.route.distance_m, trip.fare.as_ref().unwrap().distance_m);
+    }
+
+    let response = get(&first.uuid, "html", customer.clone())
+      .await
+      .respond_to(&request)
+      .map_into_boxed_body();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = to_bytes(response.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+    assert!(html.contains(&format!("{code}-000002")));
+    assert!(html.contains("SPSV12345"));
+
+    let response = get(&first.uuid, "pdf", create_fake_access_token_claims())
+      .await
+      .respond_to(&request)
+      .map_into_boxed_body();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get("content-type").unwrap(),
+      "application/pdf"
+    );
+    let pdf = to_bytes(response.into_body()).await.unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+
+    // Other customers cannot see the trip
+    let stranger = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let response = get(&first.uuid, "json", stranger)
+      .await
+      .respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let mailer = web::Data::from(Arc::new(RecordingMailer::new()));
+    let responder = email_receipt(
+      trip_repository.clone(),
+      driver_repository.clone(),
+      receipt_repository.clone(),
+      mailer.clone(),
+      config.clone(),
+      web::Path::from(GetTripDto {
+        uuid: first.uuid.clone(),
+      }),
+      web::Json(EmailReceiptDto {
+        email: String::from("customer@example.com"),
+      }),
+      customer.clone(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let sent = mailer.sent.read().unwrap(); // Acquire read lock
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "customer@example.com");
+    assert!(sent[0].subject.contains(&format!("{code}-000002")));
+    assert_eq!(sent[0].attachments.len(), 1);
+    assert!(sent[0].attachments[0].content.starts_with(b"%PDF-"));
+  }
+}
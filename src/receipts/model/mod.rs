@@ -0,0 +1,20 @@
+pub mod receipt_document;
+
+use chrono::{DateTime, Utc};
+
+// Receipt numbers run without gaps per organisation, as required for
+// tax records. A trip keeps the number it was first issued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+  pub trip_uuid: String,
+  pub organisation_code: String,
+  pub number: i64,
+  pub issued_at: DateTime<Utc>,
+}
+
+impl Receipt {
+  // e.g. "TAILLE-000042"
+  pub fn reference(&self) -> String {
+    format!("{}-{:06}", self.organisation_code, self.number)
+  }
+}
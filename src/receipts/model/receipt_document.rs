@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Europe::Dublin;
+
+use super::Receipt;
+use crate::fares::model::fare_breakdown::FareBreakdown;
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::organisation::Organisation;
+use crate::trips::model::payment_method::PaymentMethod;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::model::Trip;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteSummary {
+  pub start_coords: GeoPoint,
+  // Stops the driver reached on the way
+  pub stops: Vec<GeoPoint>,
+  pub end_coords: GeoPoint,
+  pub started_at: Option<DateTime<Utc>>,
+  pub ended_at: DateTime<Utc>,
+  // Charged distance, detours are not included
+  pub distance_m: Option<i64>,
+}
+
+// Everything printed on a receipt, whatever the format
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptDocument {
+  pub reference: String,
+  pub issued_at: DateTime<Utc>,
+  pub operator: Organisation,
+  pub trip_uuid: String,
+  pub driver_spsv_licence: Option<String>,
+  pub vehicle_plate: Option<String>,
+  pub route: RouteSummary,
+  pub fare: FareBreakdown,
+  pub payment_method: Option<PaymentMethod>,
+}
+
+impl ReceiptDocument {
+  // Only completed trips have a fare to put on a receipt
+  pub fn new(
+    receipt: &Receipt,
+    operator: &Organisation,
+    trip: Trip,
+    driver_spsv_licence: Option<String>,
+  ) -> Option<Self> {
+    let ended_at = trip
+      .transitions
+      .iter()
+      .find(|transition| transition.to == TripStatus::Completed)?
+      .at;
+    Some(Self {
+      reference: receipt.reference(),
+      issued_at: receipt.issued_at,
+      operator: operator.clone(),
+      driver_spsv_licence,
+      vehicle_plate: trip.vehicle_plate.clone(),
+      route: RouteSummary {
+        start_coords: trip.start_coords,
+        stops: trip.reached_stops().map(|stop| stop.coords).collect(),
+        end_coords: trip.end_coords,
+        started_at: trip.started_at(),
+        ended_at,
+        // What the fare was charged on, whatever the router says now
+        distance_m: trip.fare.as_ref()?.distance_m,
+      },
+      fare: trip.fare?,
+      payment_method: trip.payment_method,
+      trip_uuid: trip.uuid,
+    })
+  }
+
+  // Label and value pairs shown above the fare components
+  pub fn details(&self) -> Vec<(&'static str, String)> {
+    let mut details = vec![
+      ("Receipt number", self.reference.clone()),
+      ("Issued", format_local_time(self.issued_at)),
+      ("Trip", self.trip_uuid.clone()),
+    ];
+    if let Some(vat_number) = &self.operator.vat_number {
+      details.push(("VAT number", vat_number.clone()));
+    }
+    details.extend([
+      (
+        "Driver SPSV licence",
+        self
+          .driver_spsv_licence
+          .clone()
+          .unwrap_or_else(not_recorded),
+      ),
+      (
+        "Vehicle",
+        self.vehicle_plate.clone().unwrap_or_else(not_recorded),
+      ),
+      ("From", format_coords(&self.route.start_coords)),
+    ]);
+    details.extend(
+      self
+        .route
+        .stops
+        .iter()
+        .map(|stop| ("Via", format_coords(stop))),
+    );
+    details.extend([
+      ("To", format_coords(&self.route.end_coords)),
+      (
+        "Picked up",
+        self
+          .route
+          .started_at
+          .map(format_local_time)
+          .unwrap_or_else(not_recorded),
+      ),
+      ("Dropped off", format_local_time(self.route.ended_at)),
+      (
+        "Distance",
+        self
+          .route
+          .distance_m
+          .map(|distance_m| format!("{:.1} km", distance_m as f64 / 1000.0))
+          .unwrap_or_else(not_recorded),
+      ),
+      (
+        "Paid by",
+        self
+          .payment_method
+          .map(|method| String::from(payment_method_label(method)))
+          .unwrap_or_else(not_recorded),
+      ),
+    ]);
+    details
+  }
+}
+
+fn not_recorded() -> String {
+  String::from("Not recorded")
+}
+
+fn format_local_time(at: DateTime<Utc>) -> String {
+  at.with_timezone(&Dublin)
+    .format("%d/%m/%Y %H:%M")
+    .to_string()
+}
+
+fn format_coords(coords: &GeoPoint) -> String {
+  format!("{:.5}, {:.5}", coords.lat, coords.lng)
+}
+
+fn payment_method_label(method: PaymentMethod) -> &'static str {
+  match method {
+    PaymentMethod::Cash => "Cash",
+    PaymentMethod::Card => "Card",
+    PaymentMethod::Account => "Account",
+  }
+}
+
+// e.g. "initial_charge" reads "Initial charge"
+pub fn fare_item_label(code: &str) -> String {
+  let label = code.replace('_', " ");
+  let mut chars = label.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => label,
+  }
+}
+
+// e.g. "€12.40"
+pub fn format_euros(cents: i64) -> String {
+  let sign = if cents < 0 { "-" } else { "" };
+  let cents = cents.abs();
+  format!("{sign}€{}.{:02}", cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_labels_and_amounts() {
+    assert_eq!(fare_item_label("initial_charge"), "Initial charge");
+    assert_eq!(format_euros(1240), "€12.40");
+    assert_eq!(format_euros(5), "€0.05");
+  }
+}
@@ -0,0 +1,51 @@
+use std::fmt::Write;
+
+use crate::receipts::model::receipt_document::{
+  fare_item_label, format_euros, ReceiptDocument,
+};
+
+// Standalone page, also used as the body of receipt emails
+pub fn render_html(document: &ReceiptDocument) -> String {
+  let mut html = String::new();
+  let _ = write!(
+    html,
+    "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n\
+     <meta charset=\"utf-8\">\n<title>Receipt {}</title>\n</head>\n<body>\n\
+     <h1>{}</h1>\n<p>{}</p>\n<table>\n",
+    escape(&document.reference),
+    escape(&document.operator.name),
+    escape(&document.operator.address),
+  );
+  for (label, value) in document.details() {
+    let _ = writeln!(
+      html,
+      "<tr><th>{}</th><td>{}</td></tr>",
+      escape(label),
+      escape(&value)
+    );
+  }
+  html.push_str("</table>\n<table>\n");
+  for item in &document.fare.items {
+    let _ = writeln!(
+      html,
+      "<tr><td>{}</td><td>{}</td></tr>",
+      escape(&fare_item_label(&item.code)),
+      format_euros(item.amount_cents)
+    );
+  }
+  let _ = write!(
+    html,
+    "<tr><th>Total</th><th>{}</th></tr>\n</table>\n</body>\n</html>\n",
+    format_euros(document.fare.total_cents)
+  );
+  html
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
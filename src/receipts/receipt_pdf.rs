@@ -0,0 +1,144 @@
+use crate::receipts::model::receipt_document::{
+  fare_item_label, format_euros, ReceiptDocument,
+};
+
+// A4 in points
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 56;
+const FONT_SIZE: u32 = 11;
+const LINE_HEIGHT: u32 = 16;
+
+// Single page PDF with the built-in Helvetica font, receipts are short
+// enough to never need a second page
+pub fn render_pdf(document: &ReceiptDocument) -> Vec<u8> {
+  let mut lines = vec![
+    document.operator.name.clone(),
+    document.operator.address.clone(),
+    String::new(),
+  ];
+  lines.extend(
+    document
+      .details()
+      .into_iter()
+      .map(|(label, value)| format!("{label}: {value}")),
+  );
+  lines.push(String::new());
+  lines.extend(document.fare.items.iter().map(|item| {
+    format!(
+      "{}: {}",
+      fare_item_label(&item.code),
+      format_euros(item.amount_cents)
+    )
+  }));
+  lines.push(format!(
+    "Total: {}",
+    format_euros(document.fare.total_cents)
+  ));
+  write_pdf(&lines)
+}
+
+fn write_pdf(lines: &[String]) -> Vec<u8> {
+  let mut content = format!(
+    "BT\n/F1 {FONT_SIZE} Tf\n{LINE_HEIGHT} TL\n{MARGIN} {} Td\n",
+    PAGE_HEIGHT - MARGIN
+  )
+  .into_bytes();
+  for line in lines {
+    content.push(b'(');
+    content.extend(encode_text(line));
+    content.extend(b") '\n");
+  }
+  content.extend(b"ET\n");
+
+  let mut objects: Vec<Vec<u8>> = vec![
+    b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+    b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+    format!(
+      "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+       /Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R >>"
+    )
+    .into_bytes(),
+  ];
+  let mut stream =
+    format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+  stream.extend(content);
+  stream.extend(b"\nendstream");
+  objects.push(stream);
+  objects.push(
+    b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica \
+      /Encoding /WinAnsiEncoding >>"
+      .to_vec(),
+  );
+
+  let mut pdf = b"%PDF-1.4\n".to_vec();
+  let mut offsets = Vec::with_capacity(objects.len());
+  for (index, object) in objects.iter().enumerate() {
+    offsets.push(pdf.len());
+    pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+    pdf.extend(object);
+    pdf.extend(b"\nendobj\n");
+  }
+  let xref_offset = pdf.len();
+  pdf.extend(format!("xref\n0 {}\n", objects.len() + 1).into_bytes());
+  pdf.extend(b"0000000000 65535 f \n");
+  for offset in offsets {
+    pdf.extend(format!("{offset:010} 00000 n \n").into_bytes());
+  }
+  pdf.extend(
+    format!(
+      "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+      objects.len() + 1
+    )
+    .into_bytes(),
+  );
+  pdf
+}
+
+// Literal string in WinAnsiEncoding, characters it lacks become "?"
+fn encode_text(text: &str) -> Vec<u8> {
+  let mut encoded = Vec::with_capacity(text.len());
+  for char in text.chars() {
+    match char {
+      '(' | ')' | '\\' => {
+        encoded.push(b'\\');
+        encoded.push(char as u8);
+      }
+      '€' => encoded.extend(b"\\200"),
+      ' '..='~' => encoded.push(char as u8),
+      '\u{a0}'..='\u{ff}' => {
+        encoded.extend(format!("\\{:03o}", char as u32).into_bytes())
+      }
+      _ => encoded.push(b'?'),
+    }
+  }
+  encoded
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pdf_structure() {
+    let pdf = write_pdf(&[String::from("Total: €12.40 (card)")]);
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-1.4\n"));
+    assert!(text.ends_with("%%EOF\n"));
+    assert!(text.contains("(Total: \\20012.40 \\(card\\)) '"));
+    // The cross-reference table points at every object
+    let xref = text.find("xref\n").unwrap();
+    let startxref: usize = text
+      .rsplit("startxref\n")
+      .next()
+      .and_then(|tail| tail.lines().next())
+      .and_then(|offset| offset.parse().ok())
+      .unwrap();
+    assert_eq!(startxref, xref);
+    for object in 1..=5 {
+      let marker = format!("{object} 0 obj\n");
+      let offset = text.find(&marker).unwrap();
+      assert!(text.contains(&format!("{offset:010} 00000 n \n")));
+    }
+  }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use sqlx::{postgres::PgRow, Pool, Postgres};
+use thiserror::Error;
+
+use crate::receipts::model::Receipt;
+use crate::shared::database::Database;
+
+#[derive(Debug, Error)]
+pub enum ReceiptRepositoryError {
+  #[error("Database error: {0}")]
+  DatabaseError(#[from] sqlx::Error),
+}
+
+pub trait ReceiptRepository {
+  // Takes the next number of the organisation on the first call for a
+  // trip, later calls return the same receipt
+  async fn issue(
+    &self,
+    trip_uuid: &str,
+    organisation_code: &str,
+    at: DateTime<Utc>,
+  ) -> Result<Receipt, ReceiptRepositoryError>;
+}
+
+pub struct ReceiptRepositoryImpl {
+  pool: Arc<Pool<Postgres>>,
+}
+
+impl ReceiptRepositoryImpl {
+  pub fn new(database: Arc<Database>) -> Self {
+    Self {
+      pool: database.pool.clone(),
+    }
+  }
+}
+
+impl ReceiptRepository for ReceiptRepositoryImpl {
+  async fn issue(
+    &self,
+    trip_uuid: &str,
+    organisation_code: &str,
+    at: DateTime<Utc>,
+  ) -> Result<Receipt, ReceiptRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
+    let existing = sqlx::query("SELECT * FROM receipts WHERE trip_uuid = $1")
+      .bind(trip_uuid)
+      .fetch_optional(&mut *transaction)
+      .await?;
+    if let Some(row) = existing {
+      return Ok(Receipt::from(row));
+    }
+    // The row lock on the counter keeps numbers gapless under concurrency
+    let query = r#"
+      INSERT INTO receipt_sequences (organisation_code, last_number)
+      VALUES ($1, 1)
+      ON CONFLICT (organisation_code) DO UPDATE SET
+        last_number = receipt_sequences.last_number + 1
+      RETURNING last_number
+    "#;
+    let number: i64 = sqlx::query(query)
+      .bind(organisation_code)
+      .fetch_one(&mut *transaction)
+      .await?
+      .get("last_number");
+    let query = r#"
+      INSERT INTO receipts (trip_uuid, organisation_code, number, issued_at)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (trip_uuid) DO NOTHING
+      RETURNING *
+    "#;
+    let inserted = sqlx::query(query)
+      .bind(trip_uuid)
+      .bind(organisation_code)
+      .bind(number)
+      .bind(at)
+      .fetch_optional(&mut *transaction)
+      .await?;
+    let Some(row) = inserted else {
+      // Issued concurrently, rolling back returns the number
+      transaction.rollback().await?;
+      let row = sqlx::query("SELECT * FROM receipts WHERE trip_uuid = $1")
+        .bind(trip_uuid)
+        .fetch_one(&*self.pool)
+        .await?;
+      return Ok(Receipt::from(row));
+    };
+    transaction.commit().await?;
+    Ok(Receipt::from(row))
+  }
+}
+
+impl From<PgRow> for Receipt {
+  fn from(row: PgRow) -> Self {
+    Self {
+      trip_uuid: row.get("trip_uuid"),
+      organisation_code: row.get("organisation_code"),
+      number: row.get("number"),
+      issued_at: row.get::<DateTime<Utc>, _>("issued_at"),
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use chrono::{DateTime, Utc};
+  use std::sync::RwLock;
+
+  use super::{ReceiptRepository, ReceiptRepositoryError};
+  use crate::receipts::model::Receipt;
+
+  pub struct InMemoryReceiptRepository {
+    pub receipts: RwLock<Vec<Receipt>>,
+  }
+
+  impl InMemoryReceiptRepository {
+    pub fn new() -> Self {
+      Self {
+        receipts: RwLock::new(Vec::new()),
+      }
+    }
+  }
+
+  impl ReceiptRepository for InMemoryReceiptRepository {
+    async fn issue(
+      &self,
+      trip_uuid: &str,
+      organisation_code: &str,
+      at: DateTime<Utc>,
+    ) -> Result<Receipt, ReceiptRepositoryError> {
+      let mut receipts = self.receipts.write().unwrap(); // Acquire write lock
+      if let Some(receipt) = receipts
+        .iter()
+        .find(|receipt| receipt.trip_uuid == trip_uuid)
+      {
+        return Ok(receipt.clone());
+      }
+      let number = receipts
+        .iter()
+        .filter(|receipt| receipt.organisation_code == organisation_code)
+        .count() as i64
+        + 1;
+      let receipt = Receipt {
+        trip_uuid: String::from(trip_uuid),
+        organisation_code: String::from(organisation_code),
+        number,
+        issued_at: at,
+      };
+      receipts.push(receipt.clone());
+      Ok(receipt)
+    }
+  }
+}
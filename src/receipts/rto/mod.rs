@@ -0,0 +1 @@
+pub mod receipt_rto;
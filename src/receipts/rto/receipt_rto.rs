@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::fares::rto::fare_breakdown_rto::FareBreakdownRto;
+use crate::shared::geo_point::GeoPoint;
+use crate::trips::model::payment_method::PaymentMethod;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct OperatorRto {
+  pub name: String,
+  pub address: String,
+  #[serde(rename = "vatNumber")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub vat_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RouteSummaryRto {
+  #[serde(rename = "startCoords")]
+  pub start_coords: GeoPoint,
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub stops: Vec<GeoPoint>,
+  #[serde(rename = "endCoords")]
+  pub end_coords: GeoPoint,
+  #[serde(rename = "startedAt")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub started_at: Option<DateTime<Utc>>,
+  #[serde(rename = "endedAt")]
+  pub ended_at: DateTime<Utc>,
+  #[serde(rename = "distanceM")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub distance_m: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReceiptRto {
+  #[serde(rename = "receiptNumber")]
+  pub receipt_number: String,
+  #[serde(rename = "issuedAt")]
+  pub issued_at: DateTime<Utc>,
+  pub operator: OperatorRto,
+  #[serde(rename = "tripUuid")]
+  pub trip_uuid: String,
+  #[serde(rename = "driverSpsvLicence")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub driver_spsv_licence: Option<String>,
+  #[serde(rename = "vehiclePlate")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub vehicle_plate: Option<String>,
+  pub route: RouteSummaryRto,
+  pub fare: FareBreakdownRto,
+  #[serde(rename = "paymentMethod")]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub payment_method: Option<PaymentMethod>,
+}
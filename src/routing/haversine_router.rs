@@ -0,0 +1,52 @@
+use super::router::{Route, RouteMetric, Router};
+use crate::shared::geo_point::GeoPoint;
+
+// Average door to door speed in town. Roads are longer than the straight
+// line, a low speed keeps the estimate on the pessimistic side.
+const AVERAGE_SPEED_KMH: f64 = 20.0;
+
+// Straight line between the points, for tests and when no road graph is
+// loaded
+#[derive(Debug, Default)]
+pub struct HaversineRouter;
+
+impl HaversineRouter {
+  pub fn duration_secs(distance_km: f64) -> i64 {
+    (distance_km / AVERAGE_SPEED_KMH * 3600.0).round() as i64
+  }
+}
+
+impl Router for HaversineRouter {
+  fn route(&self, from: &GeoPoint, to: &GeoPoint, _: RouteMetric) -> Route {
+    let distance_km = from.distance_km(to);
+    Route {
+      distance_m: (distance_km * 1000.0).round() as i64,
+      duration_secs: Self::duration_secs(distance_km),
+      polyline: vec![*from, *to],
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_straight_line_route() {
+    // O'Connell Bridge to Heuston Station, about 2.5 km
+    let route = HaversineRouter.route(
+      &GeoPoint {
+        lat: 53.3472,
+        lng: -6.2592,
+      },
+      &GeoPoint {
+        lat: 53.3464,
+        lng: -6.2946,
+      },
+      RouteMetric::Fastest,
+    );
+    assert!(route.distance_m > 2000 && route.distance_m < 2600);
+    assert!(route.duration_secs > 6 * 60 && route.duration_secs < 9 * 60);
+    assert_eq!(route.polyline.len(), 2);
+  }
+}
@@ -0,0 +1,28 @@
+pub mod haversine_router;
+pub mod road_graph;
+pub mod router;
+
+use std::sync::Arc;
+
+use haversine_router::HaversineRouter;
+use road_graph::RoadGraph;
+use router::Router;
+
+// Loaded once at startup and shared by the workers. A missing or broken
+// graph is reported and trips are routed in straight lines.
+pub fn load_router(road_graph_path: Option<&str>) -> Arc<dyn Router> {
+  let Some(path) = road_graph_path else {
+    println!("No road graph configured, routing in straight lines");
+    return Arc::new(HaversineRouter);
+  };
+  match RoadGraph::load(path) {
+    Ok(graph) => {
+      println!("Loaded road graph with {} nodes", graph.node_count());
+      Arc::new(graph)
+    }
+    Err(error) => {
+      println!("{}, routing in straight lines", error);
+      Arc::new(HaversineRouter)
+    }
+  }
+}
@@ -0,0 +1,325 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::Path;
+use std::str::{FromStr, SplitWhitespace};
+
+use thiserror::Error;
+
+use super::haversine_router::HaversineRouter;
+use super::router::{Route, RouteMetric, Router};
+use crate::shared::geo_point::GeoPoint;
+
+// Cells are at least 0.6 km wide in Ireland, so the cells around a point
+// cover the snapping distance
+const CELL_DEG: f64 = 0.01;
+// Points further away from any road node are not routed on the graph
+const MAX_SNAP_KM: f64 = 0.5;
+
+type Cell = (i32, i32);
+
+#[derive(Debug, Error)]
+pub enum RoadGraphError {
+  #[error("Cannot read road graph: {0}")]
+  Io(#[from] std::io::Error),
+
+  #[error("Invalid road graph at line {line}: {reason}")]
+  Parse { line: usize, reason: String },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+  to: u32,
+  distance_cm: u32,
+  duration_ms: u32,
+}
+
+impl Edge {
+  fn cost(&self, metric: RouteMetric) -> u64 {
+    match metric {
+      RouteMetric::Shortest => self.distance_cm as u64,
+      RouteMetric::Fastest => self.duration_ms as u64,
+    }
+  }
+}
+
+// Drivable roads preprocessed from an OpenStreetMap extract. The file is
+// plain text, one record per line:
+//
+//   # comment
+//   N <osm node id> <lat> <lng>
+//   W <speed km/h> <oneway 0|1> <osm node id> <osm node id> ...
+//
+// Nodes come before the ways using them. Speeds are resolved from the
+// maxspeed and highway tags when the extract is preprocessed.
+pub struct RoadGraph {
+  nodes: Vec<GeoPoint>,
+  // Edges leaving node `i` are `edges[first_edge[i]..first_edge[i + 1]]`
+  first_edge: Vec<u32>,
+  edges: Vec<Edge>,
+  cells: HashMap<Cell, Vec<u32>>,
+}
+
+fn cell_of(point: &GeoPoint) -> Cell {
+  (
+    (point.lat / CELL_DEG).floor() as i32,
+    (point.lng / CELL_DEG).floor() as i32,
+  )
+}
+
+fn next_field<T: FromStr>(
+  fields: &mut SplitWhitespace,
+  name: &str,
+) -> Result<T, String> {
+  fields
+    .next()
+    .ok_or_else(|| format!("missing {}", name))?
+    .parse()
+    .map_err(|_| format!("invalid {}", name))
+}
+
+impl RoadGraph {
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, RoadGraphError> {
+    Self::parse(&std::fs::read_to_string(path)?)
+  }
+
+  pub fn parse(text: &str) -> Result<Self, RoadGraphError> {
+    let mut nodes = Vec::new();
+    let mut node_indexes: HashMap<i64, u32> = HashMap::new();
+    let mut edges: Vec<(u32, Edge)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+      let invalid = |reason: String| RoadGraphError::Parse {
+        line: index + 1,
+        reason,
+      };
+      let mut fields = line.split_whitespace();
+      match fields.next() {
+        Some("N") => {
+          let id: i64 = next_field(&mut fields, "node id").map_err(invalid)?;
+          let lat = next_field(&mut fields, "lat").map_err(invalid)?;
+          let lng = next_field(&mut fields, "lng").map_err(invalid)?;
+          node_indexes.insert(id, nodes.len() as u32);
+          nodes.push(GeoPoint { lat, lng });
+        }
+        Some("W") => {
+          let speed_kmh: f64 =
+            next_field(&mut fields, "speed").map_err(invalid)?;
+          let oneway: u8 =
+            next_field(&mut fields, "oneway").map_err(invalid)?;
+          if speed_kmh <= 0.0 {
+            return Err(invalid(String::from("speed must be positive")));
+          }
+          let way = fields
+            .map(|field| {
+              let id = field.parse::<i64>().ok()?;
+              node_indexes.get(&id).copied()
+            })
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| invalid(String::from("unknown node")))?;
+          for pair in way.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let distance_m =
+              nodes[from as usize].distance_km(&nodes[to as usize]) * 1000.0;
+            let edge = |to: u32| Edge {
+              to,
+              distance_cm: (distance_m * 100.0).round() as u32,
+              duration_ms: (distance_m / speed_kmh * 3600.0).round() as u32,
+            };
+            edges.push((from, edge(to)));
+            if oneway == 0 {
+              edges.push((to, edge(from)));
+            }
+          }
+        }
+        Some(field) if field.starts_with('#') => {}
+        None => {}
+        Some(_) => return Err(invalid(String::from("unknown record"))),
+      }
+    }
+    Ok(Self::build(nodes, edges))
+  }
+
+  fn build(nodes: Vec<GeoPoint>, mut edges: Vec<(u32, Edge)>) -> Self {
+    edges.sort_by_key(|(from, _)| *from);
+    let mut first_edge = vec![0; nodes.len() + 1];
+    for (from, _) in &edges {
+      first_edge[*from as usize + 1] += 1;
+    }
+    for index in 0..nodes.len() {
+      first_edge[index + 1] += first_edge[index];
+    }
+    let mut cells: HashMap<Cell, Vec<u32>> = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+      cells.entry(cell_of(node)).or_default().push(index as u32);
+    }
+    Self {
+      nodes,
+      first_edge,
+      edges: edges.into_iter().map(|(_, edge)| edge).collect(),
+      cells,
+    }
+  }
+
+  pub fn node_count(&self) -> usize {
+    self.nodes.len()
+  }
+
+  // Closest node within the snapping distance
+  fn snap(&self, point: &GeoPoint) -> Option<(u32, f64)> {
+    let (lat, lng) = cell_of(point);
+    (lat - 1..=lat + 1)
+      .flat_map(|lat| (lng - 1..=lng + 1).map(move |lng| (lat, lng)))
+      .filter_map(|cell| self.cells.get(&cell))
+      .flatten()
+      .map(|&node| (node, self.nodes[node as usize].distance_km(point)))
+      .filter(|(_, distance_km)| *distance_km <= MAX_SNAP_KM)
+      .min_by(|a, b| a.1.total_cmp(&b.1))
+  }
+
+  // Dijkstra, returns the edges to follow from `from` to `to`
+  fn shortest_path(
+    &self,
+    from: u32,
+    to: u32,
+    metric: RouteMetric,
+  ) -> Option<Vec<u32>> {
+    let mut costs = vec![u64::MAX; self.nodes.len()];
+    // Node and edge each node was reached through
+    let mut previous = vec![(u32::MAX, u32::MAX); self.nodes.len()];
+    let mut queue = BinaryHeap::new();
+    costs[from as usize] = 0;
+    queue.push(Reverse((0, from)));
+    while let Some(Reverse((cost, node))) = queue.pop() {
+      if node == to {
+        break;
+      }
+      if cost > costs[node as usize] {
+        continue;
+      }
+      let node_edges =
+        self.first_edge[node as usize]..self.first_edge[node as usize + 1];
+      for edge_index in node_edges {
+        let edge = &self.edges[edge_index as usize];
+        let next_cost = cost + edge.cost(metric);
+        if next_cost < costs[edge.to as usize] {
+          costs[edge.to as usize] = next_cost;
+          previous[edge.to as usize] = (node, edge_index);
+          queue.push(Reverse((next_cost, edge.to)));
+        }
+      }
+    }
+    if costs[to as usize] == u64::MAX {
+      return None;
+    }
+    let mut path = Vec::new();
+    let mut node = to;
+    while node != from {
+      let (previous_node, edge_index) = previous[node as usize];
+      path.push(edge_index);
+      node = previous_node;
+    }
+    path.reverse();
+    Some(path)
+  }
+
+  fn road_route(
+    &self,
+    from: &GeoPoint,
+    to: &GeoPoint,
+    metric: RouteMetric,
+  ) -> Option<Route> {
+    let (start, start_km) = self.snap(from)?;
+    let (end, end_km) = self.snap(to)?;
+    let path = self.shortest_path(start, end, metric)?;
+    let mut polyline = vec![*from, self.nodes[start as usize]];
+    let (mut distance_cm, mut duration_ms) = (0u64, 0u64);
+    for edge_index in path {
+      let edge = &self.edges[edge_index as usize];
+      distance_cm += edge.distance_cm as u64;
+      duration_ms += edge.duration_ms as u64;
+      polyline.push(self.nodes[edge.to as usize]);
+    }
+    polyline.push(*to);
+    // To and from the road in a straight line
+    let access_km = start_km + end_km;
+    Some(Route {
+      distance_m: (distance_cm as f64 / 100.0 + access_km * 1000.0).round()
+        as i64,
+      duration_secs: (duration_ms as f64 / 1000.0).round() as i64
+        + HaversineRouter::duration_secs(access_km),
+      polyline,
+    })
+  }
+}
+
+impl Router for RoadGraph {
+  fn route(
+    &self,
+    from: &GeoPoint,
+    to: &GeoPoint,
+    metric: RouteMetric,
+  ) -> Route {
+    self
+      .road_route(from, to, metric)
+      .unwrap_or_else(|| HaversineRouter.route(from, to, metric))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // A residential street from A to B, and a faster but longer road
+  // through C. D hangs off B on a one way street.
+  const GRAPH: &str = "
+    # Test extract
+    N 1 53.3400 -6.2600
+    N 2 53.3400 -6.2500
+    N 3 53.3450 -6.2550
+    N 4 53.3380 -6.2450
+    W 30 0 1 2
+    W 100 0 1 3 2
+    W 50 1 2 4
+  ";
+
+  fn point(lat: f64, lng: f64) -> GeoPoint {
+    GeoPoint { lat, lng }
+  }
+
+  #[test]
+  fn test_shortest_and_fastest_routes_differ() {
+    let graph = RoadGraph::parse(GRAPH).unwrap();
+    assert_eq!(graph.node_count(), 4);
+    let (from, to) = (point(53.3400, -6.2600), point(53.3400, -6.2500));
+
+    let shortest = graph.route(&from, &to, RouteMetric::Shortest);
+    assert_eq!(shortest.polyline.len(), 4);
+    assert!(shortest.distance_m > 650 && shortest.distance_m < 680);
+
+    let fastest = graph.route(&from, &to, RouteMetric::Fastest);
+    assert!(fastest.polyline.contains(&point(53.3450, -6.2550)));
+    assert!(fastest.distance_m > shortest.distance_m);
+    assert!(fastest.duration_secs < shortest.duration_secs);
+  }
+
+  #[test]
+  fn test_unroutable_points_fall_back_to_straight_line() {
+    let graph = RoadGraph::parse(GRAPH).unwrap();
+    let (b, d) = (point(53.3400, -6.2500), point(53.3380, -6.2450));
+    assert_eq!(graph.route(&b, &d, RouteMetric::Fastest).polyline.len(), 4);
+    // Against the one way street
+    assert_eq!(graph.route(&d, &b, RouteMetric::Fastest).polyline.len(), 2);
+    // Far away from any road
+    let galway = point(53.2707, -9.0568);
+    let route = graph.route(&b, &galway, RouteMetric::Fastest);
+    assert_eq!(
+      route,
+      HaversineRouter.route(&b, &galway, RouteMetric::Fastest)
+    );
+  }
+
+  #[test]
+  fn test_parse_rejects_unknown_nodes() {
+    let error = RoadGraph::parse("N 1 53.34 -6.26\nW 30 0 1 2").err();
+    assert!(matches!(error, Some(RoadGraphError::Parse { line: 2, .. })));
+  }
+}
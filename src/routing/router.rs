@@ -0,0 +1,22 @@
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteMetric {
+  Shortest,
+  Fastest,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+  pub distance_m: i64,
+  pub duration_secs: i64,
+  // From the origin to the destination, both included
+  pub polyline: Vec<GeoPoint>,
+}
+
+// Answers every query, implementations fall back to an estimate when the
+// points cannot be routed
+pub trait Router: Send + Sync {
+  fn route(&self, from: &GeoPoint, to: &GeoPoint, metric: RouteMetric)
+    -> Route;
+}
@@ -2,10 +2,51 @@ use std::env;
 
 use serde::{Deserialize, Serialize};
 
+use crate::dispatch::dispatcher::DispatchRules;
+use crate::dispatch::scheduler::SchedulingRules;
+use crate::drivers::compliance::ComplianceRules;
+use crate::ranks::rank_queues::RankRules;
+use crate::shared::geo_point::ServiceArea;
+use crate::shared::organisation::Organisation;
+use crate::trips::cancellation_policy::CancellationPolicy;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
   pub master_key: String,
   pub jwt_secret: String,
+  // Drivers that miss heartbeats for this long are put offline
+  #[serde(default = "default_driver_heartbeat_timeout_secs")]
+  pub driver_heartbeat_timeout_secs: u64,
+  #[serde(default)]
+  pub compliance: ComplianceRules,
+  #[serde(default)]
+  pub dispatch: DispatchRules,
+  #[serde(default)]
+  pub scheduling: SchedulingRules,
+  #[serde(default)]
+  pub cancellation: CancellationPolicy,
+  #[serde(default)]
+  pub ranks: RankRules,
+  #[serde(default)]
+  pub organisation: Organisation,
+  // Trips outside the area are refused, no check when unset
+  #[serde(default)]
+  pub service_area: Option<ServiceArea>,
+  // Preprocessed road graph, routes are straight lines when unset
+  #[serde(default)]
+  pub road_graph_path: Option<String>,
+}
+
+fn default_driver_heartbeat_timeout_secs() -> u64 {
+  90
+}
+
+// Setting from the environment, the default when unset or invalid
+pub(crate) fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+  env::var(key)
+    .ok()
+    .and_then(|value| value.parse().ok())
+    .unwrap_or(default)
 }
 
 impl Default for Config {
@@ -17,6 +58,20 @@ impl Default for Config {
     Self {
       master_key,
       jwt_secret,
+      driver_heartbeat_timeout_secs: env_or(
+        "DRIVER_HEARTBEAT_TIMEOUT_SECS",
+        default_driver_heartbeat_timeout_secs(),
+      ),
+      compliance: ComplianceRules::default(),
+      dispatch: DispatchRules::default(),
+      scheduling: SchedulingRules::default(),
+      cancellation: CancellationPolicy::default(),
+      ranks: RankRules::default(),
+      organisation: Organisation::default(),
+      service_area: env::var("SERVICE_AREA")
+        .ok()
+        .and_then(|value| value.parse().ok()),
+      road_graph_path: env::var("ROAD_GRAPH_PATH").ok(),
     }
   }
 }
@@ -53,6 +108,8 @@ mod tests {
     let config = Config::default();
     assert_eq!(config.master_key, "DEV_MASTER_KEY");
     assert_eq!(config.jwt_secret, "DEV_JWT_SECRET");
+    assert_eq!(config.driver_heartbeat_timeout_secs, 90);
+    assert_eq!(config.service_area, None);
   }
 
   #[test]
@@ -60,6 +117,15 @@ mod tests {
     let config = Config {
       master_key: "key123".to_string(),
       jwt_secret: "secret123".to_string(),
+      driver_heartbeat_timeout_secs: 90,
+      compliance: ComplianceRules::default(),
+      dispatch: DispatchRules::default(),
+      scheduling: SchedulingRules::default(),
+      cancellation: CancellationPolicy::default(),
+      ranks: RankRules::default(),
+      organisation: Organisation::default(),
+      service_area: Some(ServiceArea::IRELAND),
+      road_graph_path: None,
     };
 
     let serialized =
@@ -79,5 +145,14 @@ mod tests {
       serde_json::from_str(json).expect("Failed to deserialize");
     assert_eq!(config.master_key, "key123");
     assert_eq!(config.jwt_secret, "secret123");
+    assert_eq!(config.driver_heartbeat_timeout_secs, 90);
+    assert_eq!(config.compliance, ComplianceRules::default());
+    assert_eq!(config.dispatch, DispatchRules::default());
+    assert_eq!(config.scheduling, SchedulingRules::default());
+    assert_eq!(config.cancellation, CancellationPolicy::default());
+    assert_eq!(config.ranks, RankRules::default());
+    assert_eq!(config.organisation, Organisation::default());
+    assert_eq!(config.service_area, None);
+    assert_eq!(config.road_graph_path, None);
   }
 }
@@ -0,0 +1,174 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use validator_derive::Validate;
+
+// WGS 84 coordinates in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, Validate)]
+pub struct GeoPoint {
+  #[validate(range(min = -90.0, max = 90.0))]
+  pub lat: f64,
+  #[validate(range(min = -180.0, max = 180.0))]
+  pub lng: f64,
+}
+
+impl GeoPoint {
+  const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+  // Nullable lat/lng column pairs are either both set or both null
+  pub fn from_columns(lat: Option<f64>, lng: Option<f64>) -> Option<Self> {
+    Some(Self {
+      lat: lat?,
+      lng: lng?,
+    })
+  }
+
+  // Great-circle distance using the haversine formula
+  pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+    let d_lat = (other.lat - self.lat).to_radians();
+    let d_lng = (other.lng - self.lng).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+      + self.lat.to_radians().cos()
+        * other.lat.to_radians().cos()
+        * (d_lng / 2.0).sin().powi(2);
+    2.0 * Self::EARTH_RADIUS_KM * a.sqrt().asin()
+  }
+}
+
+// Bounding box trips must start and end in
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct ServiceArea {
+  pub min_lat: f64,
+  pub min_lng: f64,
+  pub max_lat: f64,
+  pub max_lng: f64,
+}
+
+impl ServiceArea {
+  // The island of Ireland, including the offshore islands
+  pub const IRELAND: ServiceArea = ServiceArea {
+    min_lat: 51.35,
+    min_lng: -10.7,
+    max_lat: 55.45,
+    max_lng: -5.35,
+  };
+
+  pub fn contains(&self, point: &GeoPoint) -> bool {
+    (self.min_lat..=self.max_lat).contains(&point.lat)
+      && (self.min_lng..=self.max_lng).contains(&point.lng)
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseServiceAreaError;
+
+// Either "ireland" or "min_lat,min_lng,max_lat,max_lng"
+impl FromStr for ServiceArea {
+  type Err = ParseServiceAreaError;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    if value.eq_ignore_ascii_case("ireland") {
+      return Ok(ServiceArea::IRELAND);
+    }
+    let bounds = value
+      .split(',')
+      .map(|bound| bound.trim().parse::<f64>())
+      .collect::<Result<Vec<f64>, _>>()
+      .map_err(|_| ParseServiceAreaError)?;
+    match bounds[..] {
+      [min_lat, min_lng, max_lat, max_lng]
+        if min_lat <= max_lat && min_lng <= max_lng =>
+      {
+        Ok(ServiceArea {
+          min_lat,
+          min_lng,
+          max_lat,
+          max_lng,
+        })
+      }
+      _ => Err(ParseServiceAreaError),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use validator::Validate;
+
+  use super::*;
+
+  #[test]
+  fn test_validation() {
+    let dublin = GeoPoint {
+      lat: 53.3498,
+      lng: -6.2603,
+    };
+    assert!(dublin.validate().is_ok());
+    let invalid = GeoPoint {
+      lat: 91.0,
+      lng: -6.2603,
+    };
+    assert!(invalid.validate().is_err());
+    let invalid = GeoPoint {
+      lat: 53.3498,
+      lng: -180.5,
+    };
+    assert!(invalid.validate().is_err());
+  }
+
+  #[test]
+  fn test_distance_km() {
+    let dublin = GeoPoint {
+      lat: 53.3498,
+      lng: -6.2603,
+    };
+    let cork = GeoPoint {
+      lat: 51.8985,
+      lng: -8.4756,
+    };
+    let distance = dublin.distance_km(&cork);
+    assert!((distance - 219.4).abs() < 1.0, "{}", distance);
+    assert_eq!(dublin.distance_km(&dublin), 0.0);
+  }
+
+  #[test]
+  fn test_service_area() {
+    let belfast = GeoPoint {
+      lat: 54.5973,
+      lng: -5.9301,
+    };
+    let london = GeoPoint {
+      lat: 51.5072,
+      lng: -0.1276,
+    };
+    assert!(ServiceArea::IRELAND.contains(&belfast));
+    assert!(!ServiceArea::IRELAND.contains(&london));
+  }
+
+  #[test]
+  fn test_parse_service_area() {
+    assert_eq!("Ireland".parse(), Ok(ServiceArea::IRELAND));
+    assert_eq!(
+      "53.2,-6.5,53.5,-6.0".parse(),
+      Ok(ServiceArea {
+        min_lat: 53.2,
+        min_lng: -6.5,
+        max_lat: 53.5,
+        max_lng: -6.0,
+      })
+    );
+    assert!("53.5,-6.0,53.2,-6.5".parse::<ServiceArea>().is_err());
+    assert!("dublin".parse::<ServiceArea>().is_err());
+  }
+
+  #[test]
+  fn test_serialization() {
+    let point = GeoPoint {
+      lat: 53.3498,
+      lng: -6.2603,
+    };
+    let serialized =
+      serde_json::to_string(&point).expect("Failed to serialize");
+    assert_eq!(serialized, r#"{"lat":53.3498,"lng":-6.2603}"#);
+  }
+}
@@ -3,12 +3,26 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpError {
   pub message: String,
+  // Machine-readable error code, e.g. "user_name_taken"
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub code: Option<String>,
+}
+
+impl HttpError {
+  pub fn with_code(code: &str, message: &str) -> Self {
+    Self {
+      message: String::from(message),
+      code: Some(String::from(code)),
+    }
+  }
 }
 
 impl From<&str> for HttpError {
   fn from(message: &str) -> Self {
     Self {
       message: String::from(message),
+      code: None,
     }
   }
 }
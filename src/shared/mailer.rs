@@ -0,0 +1,72 @@
+use std::io::{self, Write};
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+  pub file_name: String,
+  pub content_type: String,
+  pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mail {
+  pub to: String,
+  pub subject: String,
+  pub html_body: String,
+  pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Error)]
+pub enum MailerError {
+  #[error("IO error: {0}")]
+  IoError(#[from] io::Error),
+}
+
+// Outgoing email, an SMTP or provider API client plugs in here
+pub trait Mailer {
+  async fn send(&self, mail: Mail) -> Result<(), MailerError>;
+}
+
+// Logs mails until a delivery provider is set up
+pub struct ConsoleMailer;
+
+impl Mailer for ConsoleMailer {
+  async fn send(&self, mail: Mail) -> Result<(), MailerError> {
+    writeln!(
+      io::stdout(),
+      "Mail to {}: {} ({} attachments)",
+      mail.to,
+      mail.subject,
+      mail.attachments.len()
+    )?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use std::sync::RwLock;
+
+  use super::{Mail, Mailer, MailerError};
+
+  pub struct RecordingMailer {
+    pub sent: RwLock<Vec<Mail>>,
+  }
+
+  impl RecordingMailer {
+    pub fn new() -> Self {
+      Self {
+        sent: RwLock::new(Vec::new()),
+      }
+    }
+  }
+
+  impl Mailer for RecordingMailer {
+    async fn send(&self, mail: Mail) -> Result<(), MailerError> {
+      let mut sent = self.sent.write().unwrap(); // Acquire write lock
+      sent.push(mail);
+      Ok(())
+    }
+  }
+}
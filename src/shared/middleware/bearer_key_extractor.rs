@@ -0,0 +1,33 @@
+use actix_governor::{
+  KeyExtractor, PeerIpKeyExtractor, SimpleKeyExtractionError,
+};
+use actix_web::dev::ServiceRequest;
+
+// Rate limits per access token instead of per IP address. Drivers on the
+// same mobile network often share a carrier NAT address, so limiting by
+// IP would throttle all of them together. Requests without a token fall
+// back to the peer IP.
+#[derive(Clone)]
+pub struct BearerKeyExtractor;
+
+impl KeyExtractor for BearerKeyExtractor {
+  type Key = String;
+  type KeyExtractionError = SimpleKeyExtractionError<&'static str>;
+
+  fn extract(
+    &self,
+    req: &ServiceRequest,
+  ) -> Result<Self::Key, Self::KeyExtractionError> {
+    let token = req
+      .headers()
+      .get("Authorization")
+      .and_then(|header| header.to_str().ok())
+      .and_then(|auth_str| auth_str.strip_prefix("Bearer "));
+    match token {
+      Some(token) => Ok(format!("token:{}", token)),
+      None => PeerIpKeyExtractor
+        .extract(req)
+        .map(|ip| format!("ip:{}", ip)),
+    }
+  }
+}
@@ -1 +1,2 @@
+pub mod bearer_key_extractor;
 pub mod bearer_middleware;
@@ -1,7 +1,13 @@
 pub mod config;
 pub mod database;
+pub mod geo_point;
 pub mod http_error;
 pub mod middleware;
+pub mod mailer;
+pub mod notifier;
+pub mod organisation;
 pub mod repository;
+pub mod ride_requirements;
 pub mod role;
 pub mod rto;
+pub mod spatial_index;
@@ -0,0 +1,48 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+  pub user_uuid: String,
+  pub message: String,
+}
+
+// Messages to customers and drivers, push or SMS delivery plugs in here
+pub trait Notifier {
+  async fn notify(&self, notification: Notification);
+}
+
+// Logs notifications until a delivery provider is set up
+pub struct ConsoleNotifier;
+
+impl Notifier for ConsoleNotifier {
+  async fn notify(&self, notification: Notification) {
+    println!(
+      "Notification to {}: {}",
+      notification.user_uuid, notification.message
+    );
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use std::sync::RwLock;
+
+  use super::{Notification, Notifier};
+
+  pub struct RecordingNotifier {
+    pub sent: RwLock<Vec<Notification>>,
+  }
+
+  impl RecordingNotifier {
+    pub fn new() -> Self {
+      Self {
+        sent: RwLock::new(Vec::new()),
+      }
+    }
+  }
+
+  impl Notifier for RecordingNotifier {
+    async fn notify(&self, notification: Notification) {
+      let mut sent = self.sent.write().unwrap(); // Acquire write lock
+      sent.push(notification);
+    }
+  }
+}
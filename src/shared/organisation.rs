@@ -0,0 +1,34 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+use crate::zones::load_zones;
+use crate::zones::model::zone::Zone;
+
+// Taxi operator running the service, printed on receipts
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Organisation {
+  // Short code receipt numbers are sequenced and prefixed by
+  pub code: String,
+  pub name: String,
+  pub address: String,
+  pub vat_number: Option<String>,
+  // Areas with their own pickup rules or charges
+  #[serde(default)]
+  pub zones: Vec<Zone>,
+}
+
+impl Default for Organisation {
+  fn default() -> Self {
+    let var_or = |key: &str, default: &str| {
+      env::var(key).unwrap_or_else(|_| String::from(default))
+    };
+    Self {
+      code: var_or("ORGANISATION_CODE", "TAILLE"),
+      name: var_or("ORGANISATION_NAME", "Taille Taxis"),
+      address: var_or("ORGANISATION_ADDRESS", "Dublin, Ireland"),
+      vat_number: env::var("ORGANISATION_VAT_NUMBER").ok(),
+      zones: load_zones(),
+    }
+  }
+}
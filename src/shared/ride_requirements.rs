@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+// Needs a customer has for a ride. The accessibility flags are hard
+// requirements on the vehicle, the language is only a preference.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RideRequirements {
+  #[serde(rename = "wheelchairAccessible")]
+  pub wheelchair_accessible: bool,
+  #[serde(rename = "assistanceDog")]
+  pub assistance_dog: bool,
+  #[serde(rename = "childSeat")]
+  pub child_seat: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "preferredLanguage")]
+  pub preferred_language: Option<String>,
+}
+
+// Per-trip changes on top of the stored preferences, unset fields
+// keep the stored value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RideRequirementsOverride {
+  pub wheelchair_accessible: Option<bool>,
+  pub assistance_dog: Option<bool>,
+  pub child_seat: Option<bool>,
+  pub preferred_language: Option<String>,
+}
+
+impl RideRequirements {
+  pub fn with_override(self, changes: RideRequirementsOverride) -> Self {
+    Self {
+      wheelchair_accessible: changes
+        .wheelchair_accessible
+        .unwrap_or(self.wheelchair_accessible),
+      assistance_dog: changes.assistance_dog.unwrap_or(self.assistance_dog),
+      child_seat: changes.child_seat.unwrap_or(self.child_seat),
+      preferred_language: changes
+        .preferred_language
+        .or(self.preferred_language),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_override_keeps_unset_fields() {
+    let stored = RideRequirements {
+      wheelchair_accessible: true,
+      assistance_dog: false,
+      child_seat: false,
+      preferred_language: Some(String::from("ga")),
+    };
+
+    let requirements = stored.with_override(RideRequirementsOverride {
+      child_seat: Some(true),
+      ..RideRequirementsOverride::default()
+    });
+
+    assert!(requirements.wheelchair_accessible);
+    assert!(requirements.child_seat);
+    assert!(!requirements.assistance_dog);
+    assert_eq!(requirements.preferred_language.as_deref(), Some("ga"));
+  }
+
+  #[test]
+  fn test_serialization() {
+    let serialized = serde_json::to_value(RideRequirements {
+      wheelchair_accessible: true,
+      ..RideRequirements::default()
+    })
+    .expect("Failed to serialize");
+    assert_eq!(
+      serialized,
+      serde_json::json!({
+        "wheelchairAccessible": true,
+        "assistanceDog": false,
+        "childSeat": false
+      })
+    );
+  }
+}
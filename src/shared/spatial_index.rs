@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use super::geo_point::GeoPoint;
+
+// Cells are about 1.1 km north-south and 0.7 km east-west in Ireland, so
+// a busy city centre cell holds a handful of entries at most
+const CELL_DEG: f64 = 0.01;
+// Slightly under the real value so the ring distance bound stays safe
+const KM_PER_DEG: f64 = 111.0;
+
+type Cell = (i32, i32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nearby {
+  pub id: String,
+  pub distance_km: f64,
+}
+
+// Grid of fixed size lat/lng cells. Cells do not wrap around the
+// antimeridian, which is fine for the areas we serve.
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+  cells: HashMap<Cell, Vec<(String, GeoPoint)>>,
+  positions: HashMap<String, GeoPoint>,
+}
+
+fn cell_of(point: &GeoPoint) -> Cell {
+  (
+    (point.lat / CELL_DEG).floor() as i32,
+    (point.lng / CELL_DEG).floor() as i32,
+  )
+}
+
+// Cells at exactly `ring` steps from the center, ring 0 being the center
+fn ring_cells((lat, lng): Cell, ring: i32) -> Vec<Cell> {
+  if ring == 0 {
+    return vec![(lat, lng)];
+  }
+  let mut cells = Vec::with_capacity(8 * ring as usize);
+  for step in -ring..=ring {
+    cells.push((lat - ring, lng + step));
+    cells.push((lat + ring, lng + step));
+  }
+  for step in (1 - ring)..ring {
+    cells.push((lat + step, lng - ring));
+    cells.push((lat + step, lng + ring));
+  }
+  cells
+}
+
+impl SpatialIndex {
+  // Moves the entry if it is already indexed
+  pub fn insert(&mut self, id: &str, point: GeoPoint) {
+    self.remove(id);
+    self
+      .cells
+      .entry(cell_of(&point))
+      .or_default()
+      .push((String::from(id), point));
+    self.positions.insert(String::from(id), point);
+  }
+
+  pub fn remove(&mut self, id: &str) -> bool {
+    let Some(point) = self.positions.remove(id) else {
+      return false;
+    };
+    let cell = cell_of(&point);
+    if let Some(entries) = self.cells.get_mut(&cell) {
+      entries.retain(|(entry_id, _)| entry_id != id);
+      if entries.is_empty() {
+        self.cells.remove(&cell);
+      }
+    }
+    true
+  }
+
+  // The `k` closest entries within `radius_km`, closest first. Rings of
+  // cells are searched outwards until nothing in the next ring can be
+  // closer than the k-th entry found so far.
+  pub fn nearest(
+    &self,
+    point: &GeoPoint,
+    k: usize,
+    radius_km: f64,
+  ) -> Vec<Nearby> {
+    let mut found: Vec<Nearby> = Vec::new();
+    if k == 0 {
+      return found;
+    }
+    // East-west cells are narrowest on the side closest to the pole
+    let max_lat = (point.lat.abs() + radius_km / KM_PER_DEG).min(89.0);
+    let cell_km = CELL_DEG * KM_PER_DEG * max_lat.to_radians().cos();
+    let max_ring = (radius_km / cell_km).ceil() as i32 + 1;
+    let center = cell_of(point);
+    for ring in 0..=max_ring {
+      for cell in ring_cells(center, ring) {
+        let Some(entries) = self.cells.get(&cell) else {
+          continue;
+        };
+        for (id, position) in entries {
+          let distance_km = position.distance_km(point);
+          if distance_km <= radius_km {
+            found.push(Nearby {
+              id: id.clone(),
+              distance_km,
+            });
+          }
+        }
+      }
+      // Entries outside the searched rings are at least this far away
+      let searched_km = ring as f64 * cell_km;
+      if found.len() >= k {
+        found.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+        found.truncate(k);
+        if found[k - 1].distance_km <= searched_km {
+          break;
+        }
+      }
+    }
+    found.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+    found
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, Instant};
+
+  use super::*;
+
+  // O'Connell Bridge, Dublin
+  const CENTER: GeoPoint = GeoPoint {
+    lat: 53.3472,
+    lng: -6.2592,
+  };
+
+  // Deterministic points spread over greater Dublin
+  fn dublin_points(count: usize) -> Vec<GeoPoint> {
+    let mut seed: u64 = 42;
+    let mut next = move || {
+      seed = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+      (seed >> 11) as f64 / (1u64 << 53) as f64
+    };
+    (0..count)
+      .map(|_| GeoPoint {
+        lat: 53.20 + next() * 0.35,
+        lng: -6.50 + next() * 0.50,
+      })
+      .collect()
+  }
+
+  fn index(points: &[GeoPoint]) -> SpatialIndex {
+    let mut index = SpatialIndex::default();
+    for (i, point) in points.iter().enumerate() {
+      index.insert(&i.to_string(), *point);
+    }
+    index
+  }
+
+  #[test]
+  fn test_nearest_matches_full_scan() {
+    let points = dublin_points(2000);
+    let index = index(&points);
+
+    for query in &dublin_points(2050).split_off(2000) {
+      let mut expected: Vec<(String, f64)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| (i.to_string(), point.distance_km(query)))
+        .filter(|(_, distance_km)| *distance_km <= 3.0)
+        .collect();
+      expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+      expected.truncate(5);
+
+      let found: Vec<(String, f64)> = index
+        .nearest(query, 5, 3.0)
+        .into_iter()
+        .map(|nearby| (nearby.id, nearby.distance_km))
+        .collect();
+      assert_eq!(found, expected);
+    }
+  }
+
+  #[test]
+  fn test_moved_and_removed_entries() {
+    let mut index = SpatialIndex::default();
+    index.insert("driver", CENTER);
+    index.insert(
+      "driver",
+      GeoPoint {
+        lat: CENTER.lat + 0.5,
+        lng: CENTER.lng,
+      },
+    );
+    assert!(index.nearest(&CENTER, 1, 10.0).is_empty());
+    assert_eq!(index.nearest(&CENTER, 1, 100.0).len(), 1);
+
+    assert!(index.remove("driver"));
+    assert!(!index.remove("driver"));
+    assert!(index.nearest(&CENTER, 1, 100.0).is_empty());
+  }
+
+  // Run with `cargo test --release -- --ignored bench_`
+  #[test]
+  #[ignore]
+  fn bench_nearest_with_fifty_thousand_drivers() {
+    let index = index(&dublin_points(50_000));
+    let queries = dublin_points(51_000).split_off(50_000);
+
+    let started_at = Instant::now();
+    for query in &queries {
+      assert_eq!(index.nearest(query, 10, 10.0).len(), 10);
+    }
+    let per_lookup = started_at.elapsed() / queries.len() as u32;
+    println!("nearest with 50000 drivers: {:?} per lookup", per_lookup);
+    assert!(per_lookup < Duration::from_millis(1));
+  }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::model::trip_cancellation::CancelledBy;
+use super::model::trip_status::TripStatus;
+use super::model::Trip;
+use crate::shared::config::env_or;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CancellationPolicy {
+  // Customers cancel for free until the driver has been on the way for
+  // this long
+  pub free_period_secs: i64,
+  pub fee_cents: i64,
+}
+
+impl Default for CancellationPolicy {
+  fn default() -> Self {
+    Self {
+      free_period_secs: env_or("CANCELLATION_FREE_PERIOD_SECS", 300),
+      fee_cents: env_or("CANCELLATION_FEE_CENTS", 1000),
+    }
+  }
+}
+
+impl CancellationPolicy {
+  // Only customers are charged, once the driver has arrived or has been
+  // assigned for longer than the free period
+  pub fn fee_cents(
+    &self,
+    trip: &Trip,
+    cancelled_by: CancelledBy,
+    at: DateTime<Utc>,
+  ) -> i64 {
+    if cancelled_by != CancelledBy::Customer {
+      return 0;
+    }
+    let chargeable = match trip.status {
+      TripStatus::DriverArrived => true,
+      TripStatus::Accepted => trip.assigned_at().is_some_and(|assigned_at| {
+        at - assigned_at > Duration::seconds(self.free_period_secs)
+      }),
+      _ => false,
+    };
+    if chargeable {
+      self.fee_cents
+    } else {
+      0
+    }
+  }
+}
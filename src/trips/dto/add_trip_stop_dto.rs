@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use validator::Validate;
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AddTripStopDto {
+  #[validate(nested)]
+  pub coords: GeoPoint,
+  // Index among the stops, added after the last one when missing
+  pub position: Option<usize>,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct AssignTripDto {
+  #[validate(length(min = 1))]
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+use crate::trips::model::trip_cancellation::CancellationReason;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CancelTripDto {
+  pub reason: CancellationReason,
+  #[validate(length(max = 500))]
+  pub note: Option<String>,
+}
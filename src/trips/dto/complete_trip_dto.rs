@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+use crate::trips::model::payment_method::PaymentMethod;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CompleteTripDto {
+  #[serde(rename = "paymentMethod")]
+  pub payment_method: Option<PaymentMethod>,
+  // Passengers actually carried, when different from the booking
+  #[validate(range(min = 1, max = 8))]
+  pub passengers: Option<i32>,
+}
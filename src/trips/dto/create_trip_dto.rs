@@ -1,8 +1,65 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use validator::{Validate, ValidationError};
 use validator_derive::Validate;
 
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+pub struct RideRequirementsDto {
+  #[serde(rename = "wheelchairAccessible")]
+  pub wheelchair_accessible: Option<bool>,
+  #[serde(rename = "assistanceDog")]
+  pub assistance_dog: Option<bool>,
+  #[serde(rename = "childSeat")]
+  pub child_seat: Option<bool>,
+  // ISO 639 language code, e.g. "en" or "ga"
+  #[validate(length(min = 2, max = 3))]
+  #[serde(rename = "preferredLanguage")]
+  pub preferred_language: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_trip_locations"))]
 pub struct CreateTripDto {
-  pub start_coords: String,
-  pub end_coords: String,
+  // Each end of the trip is either raw coordinates or a saved place
+  #[validate(nested)]
+  #[serde(rename = "startCoords")]
+  pub start_coords: Option<GeoPoint>,
+  #[serde(rename = "startPlaceUuid")]
+  pub start_place_uuid: Option<String>,
+  #[validate(nested)]
+  #[serde(rename = "endCoords")]
+  pub end_coords: Option<GeoPoint>,
+  #[serde(rename = "endPlaceUuid")]
+  pub end_place_uuid: Option<String>,
+  // Visited in order on the way to the end
+  #[validate(length(max = 5), nested)]
+  #[serde(default)]
+  pub stops: Vec<GeoPoint>,
+  // Overrides the customer's stored ride preferences for this trip
+  #[validate(nested)]
+  #[serde(default)]
+  pub requirements: Option<RideRequirementsDto>,
+  // Everyone travelling, the customer included, one when unset
+  #[validate(range(min = 1, max = 8))]
+  pub passengers: Option<i32>,
+  // Quote from the estimate endpoint, kept with the trip
+  #[serde(rename = "fareEstimateUuid")]
+  pub fare_estimate_uuid: Option<String>,
+  // Pre-books the trip, dispatched right away when unset
+  #[serde(rename = "pickupAt")]
+  pub pickup_at: Option<DateTime<Utc>>,
+}
+
+fn validate_trip_locations(dto: &CreateTripDto) -> Result<(), ValidationError> {
+  let exactly_one = |coords: &Option<GeoPoint>, place: &Option<String>| {
+    coords.is_some() != place.is_some()
+  };
+  if !exactly_one(&dto.start_coords, &dto.start_place_uuid)
+    || !exactly_one(&dto.end_coords, &dto.end_place_uuid)
+  {
+    return Err(ValidationError::new("coords_or_saved_place"));
+  }
+  Ok(())
 }
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use validator::Validate;
+use validator_derive::Validate;
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EstimateTripDto {
+  #[validate(nested)]
+  #[serde(rename = "startCoords")]
+  pub start_coords: GeoPoint,
+  #[validate(nested)]
+  #[serde(rename = "endCoords")]
+  pub end_coords: GeoPoint,
+  #[validate(length(max = 5), nested)]
+  #[serde(default)]
+  pub stops: Vec<GeoPoint>,
+  // Everyone travelling, the customer included, one when unset
+  #[validate(range(min = 1, max = 8))]
+  pub passengers: Option<i32>,
+  // Now when missing, the time decides the rate
+  #[serde(rename = "pickupAt")]
+  pub pickup_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use validator::ValidationError;
+use validator_derive::Validate;
+
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::repository::trip_repository::SortOrder;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_range"))]
+pub struct ListTripsDto {
+  // Comma separated, e.g. "completed,cancelled"
+  #[serde(default)]
+  #[serde(deserialize_with = "deserialize_statuses")]
+  pub status: Vec<TripStatus>,
+  // Pickup time range, the end is exclusive
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+  // Trips are sorted by pickup time, latest first by default
+  #[serde(default)]
+  pub order: SortOrder,
+  // The nextCursor of the previous page
+  #[validate(length(min = 1, max = 64))]
+  pub cursor: Option<String>,
+  #[validate(range(min = 1, max = 100))]
+  pub limit: Option<i64>,
+}
+
+pub fn deserialize_statuses<'de, D>(
+  deserializer: D,
+) -> Result<Vec<TripStatus>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  String::deserialize(deserializer)?
+    .split(',')
+    .map(str::trim)
+    .filter(|status| !status.is_empty())
+    .map(|status| {
+      serde_json::from_value(serde_json::Value::from(status))
+        .map_err(serde::de::Error::custom)
+    })
+    .collect()
+}
+
+fn validate_range(dto: &ListTripsDto) -> Result<(), ValidationError> {
+  if let (Some(from), Some(to)) = (dto.from, dto.to) {
+    if to <= from {
+      return Err(ValidationError::new("to_before_from"));
+    }
+  }
+  Ok(())
+}
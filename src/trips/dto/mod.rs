@@ -1,2 +1,15 @@
+pub mod add_trip_stop_dto;
+pub mod assign_trip_dto;
+pub mod cancel_trip_dto;
+pub mod complete_trip_dto;
 pub mod create_trip_dto;
+pub mod estimate_trip_dto;
 pub mod get_trip_dto;
+pub mod list_trips_dto;
+pub mod offer_trip_dto;
+pub mod share_trip_dto;
+pub mod shared_trip_dto;
+pub mod subscribe_trip_dto;
+pub mod trip_share_dto;
+pub mod trip_stop_dto;
+pub mod trip_summary_dto;
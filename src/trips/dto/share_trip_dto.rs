@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ShareTripDto {
+  // How long the link works for, four hours when missing
+  #[validate(range(min = 5, max = 1440))]
+  #[serde(rename = "expiresInMins")]
+  pub expires_in_mins: Option<i64>,
+}
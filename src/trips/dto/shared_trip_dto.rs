@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SharedTripDto {
+  #[validate(length(min = 1, max = 64))]
+  pub token: String,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SubscribeTripDto {
+  // Id of the last status event the client received before reconnecting
+  #[serde(rename = "lastEventId")]
+  pub last_event_id: Option<u64>,
+}
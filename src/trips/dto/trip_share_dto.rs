@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TripShareDto {
+  pub uuid: String,
+  pub token: String,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use validator_derive::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TripStopDto {
+  pub uuid: String,
+  pub stop_uuid: String,
+}
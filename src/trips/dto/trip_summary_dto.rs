@@ -0,0 +1,25 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use validator::ValidationError;
+use validator_derive::Validate;
+
+// At most a quarter is summarised per request
+const MAX_SUMMARY_DAYS: i64 = 92;
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_range"))]
+pub struct TripSummaryDto {
+  // Irish calendar days, both inclusive
+  pub from: NaiveDate,
+  pub to: NaiveDate,
+}
+
+fn validate_range(dto: &TripSummaryDto) -> Result<(), ValidationError> {
+  if dto.to < dto.from {
+    return Err(ValidationError::new("to_before_from"));
+  }
+  if (dto.to - dto.from).num_days() >= MAX_SUMMARY_DAYS {
+    return Err(ValidationError::new("range_too_long"));
+  }
+  Ok(())
+}
@@ -1,19 +1,92 @@
+pub mod cancellation_policy;
 pub mod dto;
 pub mod model;
 pub mod repository;
 pub mod rto;
+pub mod trip_event_hub;
+pub mod trip_socket;
+pub mod trip_sse;
 
-use actix_web::{http::header, web, HttpResponse, Responder};
-use dto::create_trip_dto::CreateTripDto;
+use std::sync::Arc;
+
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use actix_web_lab::sse::Sse;
+use cancellation_policy::CancellationPolicy;
+use chrono::{DateTime, Duration, Utc};
+use dto::add_trip_stop_dto::AddTripStopDto;
+use dto::assign_trip_dto::AssignTripDto;
+use dto::cancel_trip_dto::CancelTripDto;
+use dto::complete_trip_dto::CompleteTripDto;
+use dto::create_trip_dto::{CreateTripDto, RideRequirementsDto};
+use dto::estimate_trip_dto::EstimateTripDto;
 use dto::get_trip_dto::GetTripDto;
+use dto::list_trips_dto::ListTripsDto;
+use dto::offer_trip_dto::OfferTripDto;
+use dto::share_trip_dto::ShareTripDto;
+use dto::shared_trip_dto::SharedTripDto;
+use dto::subscribe_trip_dto::SubscribeTripDto;
+use dto::trip_share_dto::TripShareDto;
+use dto::trip_stop_dto::TripStopDto;
+use dto::trip_summary_dto::TripSummaryDto;
+use model::daily_trip_summary::DailyTripSummary;
+use model::fare_estimate::FareEstimate;
+use model::payment_method::PaymentMethod;
+use model::trip_cancellation::{
+  CancellationReason, CancellationRequest, CancelledBy, TripCancellation,
+};
+use model::trip_cursor::TripCursor;
+use model::trip_event::TripEvent;
+use model::trip_share::TripShare;
+use model::trip_status::TripStatus;
+use model::trip_stop::TripStop;
+use model::trip_transition::{TripTransition, TripTransitionError};
 use model::Trip;
-use nanoid::nanoid;
-use repository::trip_repository::{CreateTrip, TripRepository, TripRepositoryError};
-use rto::get_trip_rto::GetTripRto;
+use repository::trip_repository::{
+  CreateTrip, TripQuery, TripRepository, TripRepositoryError,
+};
+use rto::eligible_driver_rto::EligibleDriverRto;
+use rto::fare_estimate_rto::FareEstimateRto;
+use rto::get_trip_rto::{GetTripRto, TripCancellationRto, TripTransitionRto};
+use rto::shared_trip_rto::{SharedDriverPositionRto, SharedTripRto};
+use rto::trip_event_rto::TripEventRto;
+use rto::trip_page_rto::TripPageRto;
+use rto::trip_share_rto::TripShareRto;
+use rto::trip_stop_rto::TripStopRto;
+use rto::trip_summary_rto::{DailyTripSummaryRto, TripSummaryRto};
+use trip_event_hub::TripEventHub;
+use trip_socket::run_trip_socket;
+use trip_sse::{driver_eta, trip_event_stream, SSE_KEEP_ALIVE};
 use validator::Validate;
 
+use crate::customers::find_own_place;
+use crate::customers::repository::customer_repository::CustomerRepository;
+use crate::dispatch::dispatcher::{is_busy, offer_next};
+use crate::drivers::compliance::compliance_report;
+use crate::drivers::matching::{eligible_drivers, has_suitable_vehicle};
+use crate::drivers::location_store::DriverLocationStore;
+use crate::drivers::model::driver_status::DriverStatus;
+use crate::drivers::repository::driver_repository::DriverRepository;
+use crate::fares::fare_engine::{estimate_fare_range, FareInput};
+use crate::fares::rto::fare_breakdown_rto::FareBreakdownRto;
+use crate::fares::tariff_table::TariffTable;
+use crate::routing::router::{RouteMetric, Router};
+use crate::shared::config::Config;
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::role::Role;
+use crate::shared::ride_requirements::{
+  RideRequirements, RideRequirementsOverride,
+};
+use crate::users::repository::user_repository::UserRepository;
+use crate::zones::model::zone::Zone;
+use crate::zones::{pickup_point, pickup_refused, zone_surcharges};
 use crate::{custom_nanoid, shared::{http_error::HttpError, rto::created_rto::CreatedRto}, users::model::access_token_claims::AccessTokenClaims};
 
+// Trips per page when the client does not ask for a size
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+// Lifetime of a shared trip link when the customer does not pick one
+const DEFAULT_SHARE_MINS: i64 = 240;
+
 pub async fn get_trip<TR: TripRepository>(
   trip_repository: web::Data<TR>,
   path: web::Path<GetTripDto>,
@@ -33,6 +106,928 @@ pub async fn get_trip<TR: TripRepository>(
     .unwrap_or_else(|err| err)
 }
 
+// Trips the caller booked or drove, one page at a time
+pub async fn list_trips<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  query: web::Query<ListTripsDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = query.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let query = query.into_inner();
+  let trip_query = TripQuery {
+    participant_uuid: Some(auth.uuid),
+    statuses: query.status,
+    from: query.from,
+    to: query.to,
+    order: query.order,
+    ..TripQuery::default()
+  };
+  trip_page(&**trip_repository, trip_query, query.cursor, query.limit).await
+}
+
+// Fills in the cursor and page size of the query
+pub async fn trip_page<TR: TripRepository>(
+  trip_repository: &TR,
+  query: TripQuery,
+  cursor: Option<String>,
+  limit: Option<i64>,
+) -> HttpResponse {
+  let after = match cursor.as_deref().map(str::parse::<TripCursor>) {
+    None => None,
+    Some(Ok(cursor)) => Some(cursor),
+    Some(Err(())) => {
+      return HttpResponse::BadRequest()
+        .content_type("application/json")
+        .json(HttpError::with_code(
+          "invalid_cursor",
+          "Cursor is not valid",
+        ))
+    }
+  };
+  let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+  // One more than asked for tells whether another page follows
+  let mut trips = trip_repository
+    .find_page(&TripQuery {
+      after,
+      limit: limit + 1,
+      ..query
+    })
+    .await;
+  let next_cursor = (trips.len() as i64 > limit).then(|| {
+    trips.truncate(limit as usize);
+    trips.last().map(|trip| TripCursor::from(trip).to_string())
+  });
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(TripPageRto {
+      trips: trips.into_iter().map(GetTripRto::from).collect(),
+      next_cursor: next_cursor.flatten(),
+    })
+}
+
+// Daily totals of the calling driver's trips
+pub async fn get_trip_summary<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  query: web::Query<TripSummaryDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  if auth.role != Role::Driver {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  // Perform validation
+  if let Err(validation_errors) = query.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let days = trip_repository
+    .summarise_for_driver(&auth.uuid, query.from, query.to)
+    .await;
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(TripSummaryRto {
+      from: query.from,
+      to: query.to,
+      currency: String::from("EUR"),
+      completed_trips: days.iter().map(|day| day.completed_trips).sum(),
+      fare_total_cents: days.iter().map(|day| day.fare_total_cents).sum(),
+      days: days.into_iter().map(DailyTripSummaryRto::from).collect(),
+    })
+}
+
+// WebSocket pushing status changes, and the driver position once they
+// accepted, to anyone who can view the trip. Clients reconnecting pass
+// the id of the last status event they got to receive what they missed.
+#[allow(clippy::too_many_arguments)]
+pub async fn subscribe_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  trip_events: web::Data<TripEventHub>,
+  location_store: web::Data<DriverLocationStore>,
+  path: web::Path<GetTripDto>,
+  query: web::Query<SubscribeTripDto>,
+  request: HttpRequest,
+  body: web::Payload,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  // Subscribe first so no transition is missed while loading the trip
+  let events = trip_events.subscribe(&path.uuid);
+  let Some(trip) = trip_repository
+    .find_one(&path.uuid)
+    .await
+    .filter(|trip| can_view_trip(trip, &auth))
+  else {
+    return trip_not_found();
+  };
+  trip_events.track(&trip);
+  let (response, session, messages) = match actix_ws::handle(&request, body) {
+    Ok(upgrade) => upgrade,
+    Err(error) => return error.error_response(),
+  };
+  let replay = initial_events(&trip, &location_store, query.last_event_id);
+  actix_web::rt::spawn(run_trip_socket(
+    session,
+    messages,
+    replay,
+    events,
+    query.last_event_id,
+  ));
+  response
+}
+
+// Server-sent events alternative to the WebSocket for clients behind
+// proxies that block it. Browsers reconnect by themselves and send the
+// Last-Event-ID header.
+pub async fn get_trip_events<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  trip_events: web::Data<TripEventHub>,
+  location_store: web::Data<DriverLocationStore>,
+  router: web::Data<dyn Router>,
+  path: web::Path<GetTripDto>,
+  request: HttpRequest,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  // Subscribe first so no transition is missed while loading the trip
+  let events = trip_events.subscribe(&path.uuid);
+  let Some(trip) = trip_repository
+    .find_one(&path.uuid)
+    .await
+    .filter(|trip| can_view_trip(trip, &auth))
+  else {
+    return trip_not_found();
+  };
+  trip_events.track(&trip);
+  let last_event_id = request
+    .headers()
+    .get("Last-Event-ID")
+    .and_then(|header| header.to_str().ok())
+    .and_then(|value| value.trim().parse::<u64>().ok());
+  let replay = initial_events(&trip, &location_store, last_event_id);
+  Sse::from_infallible_stream(trip_event_stream(
+    &trip,
+    replay,
+    events,
+    last_event_id,
+    router.into_inner(),
+  ))
+  .with_keep_alive(SSE_KEEP_ALIVE)
+  .respond_to(&request)
+}
+
+// What the client missed, or the current status on a first connection,
+// and where the driver is right now
+fn initial_events(
+  trip: &Trip,
+  location_store: &DriverLocationStore,
+  last_event_id: Option<u64>,
+) -> Vec<TripEvent> {
+  let mut events = match last_event_id {
+    Some(last_event_id) => TripEvent::missed_statuses(trip, last_event_id),
+    None => vec![TripEvent::current_status(trip)],
+  };
+  // Later positions are pushed as the driver sends them
+  let position = trip
+    .driver_uuid
+    .as_deref()
+    .filter(|_| trip.status.shares_driver_position())
+    .and_then(|driver_uuid| location_store.latest(driver_uuid));
+  events.extend(position.map(TripEvent::DriverPosition));
+  events
+}
+
+// Dispatcher view of the online drivers whose vehicle suits the trip
+pub async fn get_eligible_drivers<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if auth.role != Role::Admin && auth.role != Role::Manager {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let Some(trip) = trip_repository.find_one(&path.uuid).await else {
+    return trip_not_found();
+  };
+  let drivers: Vec<EligibleDriverRto> =
+    eligible_drivers(&**driver_repository, &trip.requirements)
+      .await
+      .into_iter()
+      .map(|(availability, vehicle)| EligibleDriverRto {
+        driver_uuid: availability.driver_uuid,
+        plate: vehicle.plate,
+      })
+      .collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(drivers)
+}
+
+// Staff offer a trip by hand to a driver who is online, the driver
+// passes the same checks as for an automatic offer
+pub async fn offer_trip<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  dto: web::Json<OfferTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if !is_staff(&auth) {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let driver_online = driver_repository
+    .find_one(&dto.driver_uuid)
+    .await
+    .is_some_and(|availability| availability.status == DriverStatus::Online);
+  if !driver_online {
+    return HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "driver_unavailable",
+        "Driver is not online",
+      ));
+  }
+  if let Some(trip) = trip_repository.find_one(&path.uuid).await {
+    let suitable = has_suitable_vehicle(
+      &**driver_repository,
+      &dto.driver_uuid,
+      &trip.requirements,
+    )
+    .await;
+    if !suitable {
+      return driver_ineligible();
+    }
+  }
+  if is_busy(&**trip_repository, &dto.driver_uuid).await {
+    return HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "driver_unavailable",
+        "Driver is busy with another trip",
+      ));
+  }
+  let can_take_trips = compliance_report(
+    &**driver_repository,
+    &config.compliance,
+    &dto.driver_uuid,
+    Utc::now(),
+  )
+  .await
+  .can_take_trips();
+  if !can_take_trips {
+    return driver_over_limit();
+  }
+  let action = TripAction::Offer {
+    driver_uuid: dto.into_inner().driver_uuid,
+    expires_at: Utc::now()
+      + Duration::seconds(config.dispatch.offer_timeout_secs),
+  };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+// Staff give a pre-booked trip to a driver ahead of time. The driver
+// does not need to be online yet, only to have a suitable vehicle and
+// to be within their driving time limits if the pickup is soon.
+pub async fn assign_trip<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  dto: web::Json<AssignTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if !is_staff(&auth) {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  if let Some(trip) = trip_repository.find_one(&path.uuid).await {
+    let suitable = has_suitable_vehicle(
+      &**driver_repository,
+      &dto.driver_uuid,
+      &trip.requirements,
+    )
+    .await;
+    if !suitable {
+      return driver_ineligible();
+    }
+    // Limits still exceeded at a pickup within the daily window, later
+    // ones are checked again when the driver accepts
+    let now = Utc::now();
+    let pickup_soon = trip
+      .pickup_at
+      .is_none_or(|pickup_at| pickup_at - now < Duration::hours(24));
+    if pickup_soon
+      && !compliance_report(
+        &**driver_repository,
+        &config.compliance,
+        &dto.driver_uuid,
+        now,
+      )
+      .await
+      .can_take_trips()
+    {
+      return driver_over_limit();
+    }
+  }
+  let action = TripAction::Assign {
+    driver_uuid: dto.into_inner().driver_uuid,
+  };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+// Drivers over their driving time limits cannot take the trip on, this
+// also covers trips pre-assigned to them before they went over
+pub async fn accept_trip<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let can_take_trips = compliance_report(
+    &**driver_repository,
+    &config.compliance,
+    &auth.uuid,
+    Utc::now(),
+  )
+  .await
+  .can_take_trips();
+  if !can_take_trips {
+    return driver_over_limit();
+  }
+  // Kept on the trip as drivers may change vehicles later
+  let vehicle_plate = driver_repository
+    .find_vehicle(&auth.uuid)
+    .await
+    .map(|vehicle| vehicle.plate);
+  let action = TripAction::Accept { vehicle_plate };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+pub async fn decline_trip<TR: TripRepository, DR: DriverRepository>(
+  trip_repository: web::Data<TR>,
+  driver_repository: web::Data<DR>,
+  location_store: web::Data<DriverLocationStore>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let action = TripAction::Decline;
+  let trip =
+    match apply_trip_action(&**trip_repository, path, auth, action).await {
+      Ok(trip) => trip,
+      Err(response) => return response,
+    };
+  // Move on to the next closest driver straight away, the dispatch
+  // monitor retries if this fails
+  let _ = offer_next(
+    &**trip_repository,
+    &**driver_repository,
+    &location_store,
+    &config,
+    trip.clone(),
+    Utc::now(),
+  )
+  .await;
+  trip_found(trip)
+}
+
+pub async fn arrive_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  transition_trip(&**trip_repository, path, auth, TripAction::Arrive).await
+}
+
+pub async fn start_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  transition_trip(&**trip_repository, path, auth, TripAction::Start).await
+}
+
+pub async fn complete_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  tariffs: web::Data<TariffTable>,
+  router: web::Data<dyn Router>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  dto: Option<web::Json<CompleteTripDto>>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Some(Err(validation_errors)) = dto.as_deref().map(Validate::validate) {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  // Older driver apps complete trips without a body
+  let dto = dto.map(web::Json::into_inner);
+  let action = TripAction::Complete {
+    tariffs: tariffs.into_inner(),
+    router: router.into_inner(),
+    zones: config.organisation.zones.clone(),
+    payment_method: dto.as_ref().and_then(|dto| dto.payment_method),
+    passengers: dto.and_then(|dto| dto.passengers),
+  };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+pub async fn no_show_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  transition_trip(&**trip_repository, path, auth, TripAction::NoShow).await
+}
+
+// Customers, the driver and dispatchers can cancel, customers may owe a
+// fee depending on how far along the trip is
+pub async fn cancel_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  dto: web::Json<CancelTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let dto = dto.into_inner();
+  let action = TripAction::Cancel {
+    reason: dto.reason,
+    note: dto.note,
+    policy: config.cancellation.clone(),
+  };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+// Customers can ask for another stop on the way, or drop one they no
+// longer need, until the trip ends
+pub async fn add_trip_stop<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  config: web::Data<Config>,
+  path: web::Path<GetTripDto>,
+  dto: web::Json<AddTripStopDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if let Some(response) =
+    reject_stops_outside_service_area(&config, &[dto.coords])
+  {
+    return response;
+  }
+  let action = TripAction::AddStop {
+    coords: dto.coords,
+    position: dto.position,
+  };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+pub async fn remove_trip_stop<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<TripStopDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let TripStopDto { uuid, stop_uuid } = path.into_inner();
+  let path = web::Path::from(GetTripDto { uuid });
+  let action = TripAction::RemoveStop { stop_uuid };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+pub async fn arrive_at_stop<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<TripStopDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let TripStopDto { uuid, stop_uuid } = path.into_inner();
+  let path = web::Path::from(GetTripDto { uuid });
+  let action = TripAction::ArriveAtStop { stop_uuid };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+pub async fn depart_from_stop<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<TripStopDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let TripStopDto { uuid, stop_uuid } = path.into_inner();
+  let path = web::Path::from(GetTripDto { uuid });
+  let action = TripAction::DepartFromStop { stop_uuid };
+  transition_trip(&**trip_repository, path, auth, action).await
+}
+
+// Customers share a read-only link so friends and family can follow
+// the trip without an account
+pub async fn share_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<GetTripDto>,
+  dto: Option<web::Json<ShareTripDto>>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  let dto = dto.map(web::Json::into_inner);
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if let Some(Err(validation_errors)) = dto.as_ref().map(Validate::validate) {
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let Some(trip) = trip_repository
+    .find_one(&path.uuid)
+    .await
+    .filter(|trip| can_view_trip(trip, &auth))
+  else {
+    return trip_not_found();
+  };
+  if trip.consumer_uuid != auth.uuid {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  if trip.status.is_final() {
+    return trip_ended();
+  }
+  let expires_in_mins = dto
+    .and_then(|dto| dto.expires_in_mins)
+    .unwrap_or(DEFAULT_SHARE_MINS);
+  let now = Utc::now();
+  let share = TripShare {
+    token: custom_nanoid(),
+    trip_uuid: trip.uuid,
+    created_at: now,
+    expires_at: now + Duration::minutes(expires_in_mins),
+    revoked_at: None,
+  };
+  trip_repository
+    .create_share(share)
+    .await
+    .map(trip_shared)
+    .unwrap_or_else(failed_create_trip)
+}
+
+pub async fn revoke_trip_share<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  path: web::Path<TripShareDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  let Some(trip) = trip_repository
+    .find_one(&path.uuid)
+    .await
+    .filter(|trip| can_view_trip(trip, &auth))
+  else {
+    return trip_not_found();
+  };
+  if trip.consumer_uuid != auth.uuid {
+    return HttpResponse::Forbidden().body("Forbidden");
+  }
+  let Some(share) = trip_repository
+    .find_share(&path.token)
+    .await
+    .filter(|share| share.trip_uuid == trip.uuid)
+  else {
+    return share_not_found();
+  };
+  trip_repository
+    .revoke_share(&share.token, Utc::now())
+    .await
+    .map(|_| HttpResponse::NoContent().finish())
+    .unwrap_or_else(failed_create_trip)
+}
+
+// Public view behind a shared link. Revoked, expired and ended shares
+// all look unknown, so a token reveals nothing once it stops working.
+pub async fn get_shared_trip<TR: TripRepository, UR: UserRepository>(
+  trip_repository: web::Data<TR>,
+  user_repository: web::Data<UR>,
+  location_store: web::Data<DriverLocationStore>,
+  router: web::Data<dyn Router>,
+  path: web::Path<SharedTripDto>,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  let now = Utc::now();
+  let Some(share) = trip_repository
+    .find_share(&path.token)
+    .await
+    .filter(|share| share.is_active(now))
+  else {
+    return share_not_found();
+  };
+  let Some(trip) = trip_repository
+    .find_one(&share.trip_uuid)
+    .await
+    .filter(|trip| !trip.status.is_final())
+  else {
+    return share_not_found();
+  };
+  // Followers only learn who is coming once the driver accepted
+  let driver_uuid = trip
+    .driver_uuid
+    .as_deref()
+    .filter(|_| trip.status.shares_driver_position());
+  let driver = match driver_uuid {
+    Some(driver_uuid) => user_repository.find_one(driver_uuid).await,
+    None => None,
+  };
+  let fix =
+    driver_uuid.and_then(|driver_uuid| location_store.latest(driver_uuid));
+  let eta = fix.as_ref().and_then(|fix| {
+    driver_eta(
+      &**router,
+      trip.status,
+      &trip.start_coords,
+      &trip.end_coords,
+      fix,
+    )
+  });
+  let vehicle_plate = driver_uuid.and(trip.vehicle_plate.clone());
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .append_header((header::CACHE_CONTROL, "no-store"))
+    .json(SharedTripRto {
+      status: trip.status,
+      driver_first_name: driver.and_then(|driver| driver.first_name),
+      vehicle_plate,
+      driver_position: fix.map(|fix| SharedDriverPositionRto {
+        coords: fix.coords,
+        recorded_at: fix.recorded_at,
+      }),
+      eta,
+      expires_at: share.expires_at,
+    })
+}
+
+enum TripAction {
+  Offer {
+    driver_uuid: String,
+    expires_at: DateTime<Utc>,
+  },
+  Assign {
+    driver_uuid: String,
+  },
+  Accept {
+    vehicle_plate: Option<String>,
+  },
+  Decline,
+  Arrive,
+  Start,
+  Complete {
+    tariffs: Arc<TariffTable>,
+    router: Arc<dyn Router>,
+    zones: Vec<Zone>,
+    payment_method: Option<PaymentMethod>,
+    passengers: Option<i32>,
+  },
+  NoShow,
+  Cancel {
+    reason: CancellationReason,
+    note: Option<String>,
+    policy: CancellationPolicy,
+  },
+  AddStop {
+    coords: GeoPoint,
+    position: Option<usize>,
+  },
+  RemoveStop {
+    stop_uuid: String,
+  },
+  ArriveAtStop {
+    stop_uuid: String,
+  },
+  DepartFromStop {
+    stop_uuid: String,
+  },
+}
+
+impl TripAction {
+  // Only the assigned driver moves the trip along, anyone involved can
+  // cancel it or change its stops
+  fn is_allowed(&self, trip: &Trip, auth: &AccessTokenClaims) -> bool {
+    match self {
+      TripAction::Offer { .. } | TripAction::Assign { .. } => is_staff(auth),
+      TripAction::Cancel { .. } => can_cancel_trip(trip, auth),
+      TripAction::AddStop { .. } | TripAction::RemoveStop { .. } => {
+        can_view_trip(trip, auth)
+      }
+      _ => auth.role == Role::Driver && trip.is_assigned_to(&auth.uuid),
+    }
+  }
+
+  fn apply(
+    self,
+    trip: Trip,
+    auth: &AccessTokenClaims,
+    at: DateTime<Utc>,
+  ) -> Result<Trip, TripTransitionError> {
+    let actor_uuid = Some(auth.uuid.clone());
+    let to = match self {
+      TripAction::Offer {
+        driver_uuid,
+        expires_at,
+      } => return trip.offer(&driver_uuid, actor_uuid, at, expires_at),
+      TripAction::Assign { driver_uuid } => {
+        return trip.pre_assign(&driver_uuid, at)
+      }
+      TripAction::Decline => return trip.decline(actor_uuid, at),
+      TripAction::Accept { vehicle_plate } => {
+        return trip.accept(vehicle_plate, actor_uuid, at)
+      }
+      TripAction::Arrive => TripStatus::DriverArrived,
+      TripAction::Start => TripStatus::InProgress,
+      TripAction::Complete {
+        tariffs,
+        router,
+        zones,
+        payment_method,
+        passengers,
+      } => {
+        let trip = Trip {
+          passengers: passengers.unwrap_or(trip.passengers),
+          ..trip
+        };
+        return trip.complete(
+          &tariffs,
+          &*router,
+          &zones,
+          payment_method,
+          actor_uuid,
+          at,
+        );
+      }
+      TripAction::NoShow => TripStatus::NoShow,
+      TripAction::Cancel {
+        reason,
+        note,
+        policy,
+      } => {
+        let request = CancellationRequest {
+          cancelled_by: cancelled_by(&trip, auth),
+          actor_uuid,
+          reason,
+          note,
+        };
+        return trip.cancel(request, &policy, at);
+      }
+      TripAction::AddStop { coords, position } => {
+        return trip.add_stop(coords, position, at)
+      }
+      TripAction::RemoveStop { stop_uuid } => {
+        return trip.remove_stop(&stop_uuid, at)
+      }
+      TripAction::ArriveAtStop { stop_uuid } => {
+        return trip.arrive_at_stop(&stop_uuid, at)
+      }
+      TripAction::DepartFromStop { stop_uuid } => {
+        return trip.depart_from_stop(&stop_uuid, at)
+      }
+    };
+    trip.transition(to, actor_uuid, at)
+  }
+}
+
+async fn transition_trip<TR: TripRepository>(
+  trip_repository: &TR,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+  action: TripAction,
+) -> HttpResponse {
+  apply_trip_action(trip_repository, path, auth, action)
+    .await
+    .map(trip_found)
+    .unwrap_or_else(|err| err)
+}
+
+async fn apply_trip_action<TR: TripRepository>(
+  trip_repository: &TR,
+  path: web::Path<GetTripDto>,
+  auth: AccessTokenClaims,
+  action: TripAction,
+) -> Result<Trip, HttpResponse> {
+  // Perform validation
+  if let Err(validation_errors) = path.validate() {
+    // If validation fails, return a 400 error with details
+    return Err(HttpResponse::BadRequest().json(validation_errors));
+  }
+  let Some(trip) = trip_repository
+    .find_one(&path.uuid)
+    .await
+    .filter(|trip| can_view_trip(trip, &auth))
+  else {
+    return Err(trip_not_found());
+  };
+  if !action.is_allowed(&trip, &auth) {
+    return Err(HttpResponse::Forbidden().body("Forbidden"));
+  }
+  let expected_status = trip.status;
+  let trip = action
+    .apply(trip, &auth, Utc::now())
+    .map_err(failed_trip_transition)?;
+  trip_repository
+    .update(trip, expected_status)
+    .await
+    .map_err(failed_update_trip)
+}
+
+fn is_staff(auth: &AccessTokenClaims) -> bool {
+  auth.role == Role::Admin || auth.role == Role::Manager
+}
+
+// Staff acting on their own trip count as the customer
+pub fn cancelled_by(trip: &Trip, auth: &AccessTokenClaims) -> CancelledBy {
+  if trip.consumer_uuid == auth.uuid {
+    CancelledBy::Customer
+  } else if trip.is_assigned_to(&auth.uuid) {
+    CancelledBy::Driver
+  } else {
+    CancelledBy::Dispatcher
+  }
+}
+
+// Drivers only offered or pre-assigned the trip decline it instead
+fn can_cancel_trip(trip: &Trip, auth: &AccessTokenClaims) -> bool {
+  if trip.is_assigned_to(&auth.uuid) && trip.consumer_uuid != auth.uuid {
+    return trip.status.is_accepted();
+  }
+  can_view_trip(trip, auth)
+}
+
+pub fn can_view_trip(trip: &Trip, auth: &AccessTokenClaims) -> bool {
+  trip.consumer_uuid == auth.uuid
+    || trip.is_assigned_to(&auth.uuid)
+    || is_staff(auth)
+}
+
+fn failed_trip_transition(error: TripTransitionError) -> HttpResponse {
+  let code = match error {
+    TripTransitionError::InvalidTransition { .. } => "invalid_trip_transition",
+    TripTransitionError::OfferExpired(_) => "offer_expired",
+    TripTransitionError::NotScheduled(_) => "trip_not_scheduled",
+    TripTransitionError::StopsClosed(_) => "trip_stops_closed",
+    TripTransitionError::NotInProgress(_) => "trip_not_in_progress",
+    TripTransitionError::StopNotFound(_) => "stop_not_found",
+    TripTransitionError::StopOutOfOrder(_) => "stop_out_of_order",
+    TripTransitionError::StopReached(_) => "stop_reached",
+    TripTransitionError::TooManyStops(_) => "too_many_stops",
+  };
+  HttpResponse::Conflict()
+    .content_type("application/json")
+    .json(HttpError::with_code(code, &error.to_string()))
+}
+
+fn failed_update_trip(error: TripRepositoryError) -> HttpResponse {
+  match error {
+    TripRepositoryError::StatusChanged(_) => HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "trip_status_changed",
+        "Trip was updated concurrently",
+      )),
+    TripRepositoryError::VersionChanged(_) => HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "trip_changed",
+        "Trip was updated concurrently",
+      )),
+    _ => HttpResponse::InternalServerError().finish(),
+  }
+}
+
 fn trip_found(trip: Trip) -> HttpResponse {
   HttpResponse::Ok()
     .content_type("application/json")
@@ -40,25 +1035,176 @@ fn trip_found(trip: Trip) -> HttpResponse {
     .json(GetTripRto::from(trip))
 }
 
+fn driver_ineligible() -> HttpResponse {
+  HttpResponse::Conflict()
+    .content_type("application/json")
+    .json(HttpError::with_code(
+      "driver_ineligible",
+      "Driver has no vehicle suitable for the trip",
+    ))
+}
+
+fn driver_over_limit() -> HttpResponse {
+  HttpResponse::Conflict()
+    .content_type("application/json")
+    .json(HttpError::with_code(
+      "driver_over_limit",
+      "Driver is over their driving time limits",
+    ))
+}
+
 fn trip_not_found() -> HttpResponse {
   HttpResponse::NotFound()
     .content_type("application/json")
     .json(HttpError::from("Trip not found"))
 }
 
+fn trip_shared(share: TripShare) -> HttpResponse {
+  let rto = TripShareRto::from(share);
+  HttpResponse::Created()
+    .content_type("application/json")
+    .append_header((header::LOCATION, rto.url.clone()))
+    .json(rto)
+}
+
+fn trip_ended() -> HttpResponse {
+  HttpResponse::Conflict()
+    .content_type("application/json")
+    .json(HttpError::with_code("trip_ended", "Trip has already ended"))
+}
+
+fn share_not_found() -> HttpResponse {
+  HttpResponse::NotFound()
+    .content_type("application/json")
+    .json(HttpError::from("Shared trip not found"))
+}
+
 // Transform User domain to RTO
 impl From<Trip> for GetTripRto {
   fn from(trip: Trip) -> Self {
     Self {
       uuid: trip.uuid,
       driver_uuid: trip.driver_uuid,
-      consumer_uuid: trip.consumer_uuid
+      consumer_uuid: trip.consumer_uuid,
+      start_coords: trip.start_coords,
+      end_coords: trip.end_coords,
+      stops: trip.stops.into_iter().map(TripStopRto::from).collect(),
+      requirements: trip.requirements,
+      passengers: trip.passengers,
+      status: trip.status,
+      requested_at: trip.created_at,
+      pickup_at: trip.pickup_at,
+      fare: trip.fare.map(FareBreakdownRto::from),
+      cancellation: trip.cancellation.map(TripCancellationRto::from),
+      vehicle_plate: trip.vehicle_plate,
+      payment_method: trip.payment_method,
+      fare_estimate_uuid: trip.fare_estimate_uuid,
+      transitions: trip
+        .transitions
+        .into_iter()
+        .map(TripTransitionRto::from)
+        .collect(),
+    }
+  }
+}
+
+// Transform TripShare domain to RTO
+impl From<TripShare> for TripShareRto {
+  fn from(share: TripShare) -> Self {
+    Self {
+      url: format!("/v1/shared-trips/{}", share.token),
+      token: share.token,
+      expires_at: share.expires_at,
     }
   }
 }
 
-pub async fn create_trip<TR: TripRepository>(
+// Transform TripStop domain to RTO
+impl From<TripStop> for TripStopRto {
+  fn from(stop: TripStop) -> Self {
+    Self {
+      uuid: stop.uuid,
+      coords: stop.coords,
+      arrived_at: stop.arrived_at,
+      departed_at: stop.departed_at,
+    }
+  }
+}
+
+// Transform TripEvent domain to RTO
+impl From<TripEvent> for TripEventRto {
+  fn from(event: TripEvent) -> Self {
+    match event {
+      TripEvent::Status {
+        id,
+        status,
+        at,
+        driver_uuid,
+      } => Self::Status {
+        id,
+        status,
+        at,
+        driver_uuid,
+      },
+      TripEvent::DriverPosition(fix) => Self::DriverPosition {
+        coords: fix.coords,
+        heading: fix.heading_deg,
+        speed: fix.speed_mps,
+        recorded_at: fix.recorded_at,
+      },
+    }
+  }
+}
+
+// Transform DailyTripSummary domain to RTO
+impl From<DailyTripSummary> for DailyTripSummaryRto {
+  fn from(day: DailyTripSummary) -> Self {
+    Self {
+      date: day.date,
+      completed_trips: day.completed_trips,
+      cancelled_trips: day.cancelled_trips,
+      fare_total_cents: day.fare_total_cents,
+    }
+  }
+}
+
+// Transform TripCancellation domain to RTO
+impl From<TripCancellation> for TripCancellationRto {
+  fn from(cancellation: TripCancellation) -> Self {
+    Self {
+      cancelled_by: cancellation.cancelled_by,
+      actor_uuid: cancellation.actor_uuid,
+      reason: cancellation.reason,
+      note: cancellation.note,
+      at: cancellation.at,
+      currency: String::from("EUR"),
+      fee_cents: cancellation.fee_cents,
+    }
+  }
+}
+
+// Transform TripTransition domain to RTO
+impl From<TripTransition> for TripTransitionRto {
+  fn from(transition: TripTransition) -> Self {
+    Self {
+      from: transition.from,
+      to: transition.to,
+      at: transition.at,
+      actor_uuid: transition.actor_uuid,
+    }
+  }
+}
+
+pub async fn create_trip<
+  TR: TripRepository,
+  CR: CustomerRepository,
+  DR: DriverRepository,
+>(
   trip_repository: web::Data<TR>,
+  customer_repository: web::Data<CR>,
+  driver_repository: web::Data<DR>,
+  location_store: web::Data<DriverLocationStore>,
+  config: web::Data<Config>,
   dto: web::Json<CreateTripDto>,
   auth: AccessTokenClaims,
 ) -> impl Responder {
@@ -67,11 +1213,253 @@ pub async fn create_trip<TR: TripRepository>(
     // If validation fails, return a 400 error with details
     return HttpResponse::BadRequest().json(validation_errors);
   }
-  trip_repository
-    .create(CreateTrip::from(auth, dto.into_inner()))
+  let dto = dto.into_inner();
+  let start_coords = resolve_location(
+    &**customer_repository,
+    &auth.uuid,
+    dto.start_coords,
+    dto.start_place_uuid.as_deref(),
+  )
+  .await;
+  let end_coords = resolve_location(
+    &**customer_repository,
+    &auth.uuid,
+    dto.end_coords,
+    dto.end_place_uuid.as_deref(),
+  )
+  .await;
+  let (Some(start_coords), Some(end_coords)) = (start_coords, end_coords)
+  else {
+    return HttpResponse::BadRequest()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "invalid_saved_place",
+        "Saved place not found",
+      ));
+  };
+  if let Some(response) =
+    reject_outside_service_area(&config, &start_coords, &end_coords)
+      .or_else(|| reject_stops_outside_service_area(&config, &dto.stops))
+  {
+    return response;
+  }
+  let start_coords =
+    match pickup_point(&config.organisation.zones, &start_coords) {
+      Ok(start_coords) => start_coords,
+      Err(error) => return pickup_refused(error),
+    };
+  let within_booking_window = dto.pickup_at.is_none_or(|pickup_at| {
+    config
+      .scheduling
+      .is_within_booking_window(pickup_at, Utc::now())
+  });
+  if !within_booking_window {
+    return HttpResponse::BadRequest()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "outside_booking_window",
+        "Pickup time is too soon or too far ahead",
+      ));
+  }
+  if let Some(estimate_uuid) = &dto.fare_estimate_uuid {
+    let estimate = trip_repository
+      .find_estimate(estimate_uuid)
+      .await
+      .filter(|estimate| estimate.consumer_uuid == auth.uuid);
+    let Some(estimate) = estimate else {
+      return HttpResponse::BadRequest()
+        .content_type("application/json")
+        .json(HttpError::with_code(
+          "invalid_fare_estimate",
+          "Fare estimate not found",
+        ));
+    };
+    let now = Utc::now();
+    if estimate.is_expired(now) {
+      return HttpResponse::BadRequest()
+        .content_type("application/json")
+        .json(HttpError::with_code(
+          "fare_estimate_expired",
+          "Fare estimate has expired",
+        ));
+    }
+    // Quoted the same way as the estimate, a past pickup time means now
+    let pickup_at = dto.pickup_at.filter(|at| *at > now).unwrap_or(now);
+    if !estimate.quotes(
+      (&start_coords, &end_coords),
+      pickup_at,
+      dto.passengers.unwrap_or(1),
+    ) {
+      return HttpResponse::BadRequest()
+        .content_type("application/json")
+        .json(HttpError::with_code(
+          "fare_estimate_mismatch",
+          "Fare estimate was given for a different trip",
+        ));
+    }
+  }
+  let preferences = customer_repository
+    .find_preferences(&auth.uuid)
     .await
-    .map(trip_created)
-    .unwrap_or_else(failed_create_trip)
+    .unwrap_or_default();
+  let trip = match trip_repository
+    .create(CreateTrip::from(
+      auth,
+      (start_coords, end_coords),
+      preferences,
+      dto,
+    ))
+    .await
+  {
+    Ok(trip) => trip,
+    Err(error) => return failed_create_trip(error),
+  };
+  // Offer the trip right away instead of waiting for the dispatch monitor,
+  // scheduled trips are left to the scheduler
+  let _ = offer_next(
+    &**trip_repository,
+    &**driver_repository,
+    &location_store,
+    &config,
+    trip.clone(),
+    Utc::now(),
+  )
+  .await;
+  trip_created(trip)
+}
+
+// Quote for a trip, kept so it can be attached to the booking
+pub async fn estimate_trip<TR: TripRepository>(
+  trip_repository: web::Data<TR>,
+  tariffs: web::Data<TariffTable>,
+  router: web::Data<dyn Router>,
+  config: web::Data<Config>,
+  dto: web::Json<EstimateTripDto>,
+  auth: AccessTokenClaims,
+) -> impl Responder {
+  // Perform validation
+  if let Err(validation_errors) = dto.validate() {
+    // If validation fails, return a 400 error with details
+    return HttpResponse::BadRequest().json(validation_errors);
+  }
+  if let Some(response) =
+    reject_outside_service_area(&config, &dto.start_coords, &dto.end_coords)
+      .or_else(|| reject_stops_outside_service_area(&config, &dto.stops))
+  {
+    return response;
+  }
+  let zones = &config.organisation.zones;
+  let start_coords = match pickup_point(zones, &dto.start_coords) {
+    Ok(start_coords) => start_coords,
+    Err(error) => return pickup_refused(error),
+  };
+  let now = Utc::now();
+  // A pickup time in the past means now
+  let pickup_at = dto.pickup_at.filter(|at| *at > now).unwrap_or(now);
+  let passengers = dto.passengers.unwrap_or(1);
+  // Fares are metered on the shortest route through every stop, time
+  // spent waiting at them is not known yet
+  let points: Vec<&GeoPoint> = std::iter::once(&start_coords)
+    .chain(&dto.stops)
+    .chain(std::iter::once(&dto.end_coords))
+    .collect();
+  let (distance_m, duration_secs) = points
+    .windows(2)
+    .map(|leg| router.route(leg[0], leg[1], RouteMetric::Shortest))
+    .fold((0, 0), |(distance_m, duration_secs), route| {
+      (
+        distance_m + route.distance_m,
+        duration_secs + route.duration_secs,
+      )
+    });
+  let input = FareInput {
+    started_at: pickup_at,
+    distance_m,
+    duration_secs,
+    additional_passengers: i64::from(passengers - 1),
+    stop_waits: Vec::new(),
+    surcharges: zone_surcharges(zones, &start_coords, &dto.end_coords),
+  };
+  let Some(fare) = estimate_fare_range(&tariffs, &input) else {
+    return HttpResponse::BadRequest()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "no_tariff",
+        "No tariff in force at the pickup time",
+      ));
+  };
+  let estimate = FareEstimate {
+    uuid: custom_nanoid(),
+    consumer_uuid: auth.uuid,
+    created_at: now,
+    start_coords,
+    end_coords: dto.end_coords,
+    pickup_at,
+    distance_m: input.distance_m,
+    duration_secs: input.duration_secs,
+    passengers,
+    fare,
+  };
+  match trip_repository.create_estimate(estimate).await {
+    Ok(estimate) => HttpResponse::Created()
+      .content_type("application/json")
+      .json(FareEstimateRto::from(estimate)),
+    Err(error) => failed_create_trip(error),
+  }
+}
+
+// Error response when either end is outside the service area
+pub fn reject_outside_service_area(
+  config: &Config,
+  start_coords: &GeoPoint,
+  end_coords: &GeoPoint,
+) -> Option<HttpResponse> {
+  let outside_service_area = config.service_area.is_some_and(|area| {
+    !area.contains(start_coords) || !area.contains(end_coords)
+  });
+  outside_service_area.then(|| {
+    HttpResponse::BadRequest()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "outside_service_area",
+        "Trip must start and end inside the service area",
+      ))
+  })
+}
+
+pub fn reject_stops_outside_service_area(
+  config: &Config,
+  stops: &[GeoPoint],
+) -> Option<HttpResponse> {
+  let outside_service_area = config
+    .service_area
+    .is_some_and(|area| stops.iter().any(|stop| !area.contains(stop)));
+  outside_service_area.then(|| {
+    HttpResponse::BadRequest()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "outside_service_area",
+        "Stops must be inside the service area",
+      ))
+  })
+}
+
+// Raw coordinates win, otherwise the saved place must belong to the caller
+async fn resolve_location<CR: CustomerRepository>(
+  customer_repository: &CR,
+  customer_uuid: &str,
+  coords: Option<GeoPoint>,
+  place_uuid: Option<&str>,
+) -> Option<GeoPoint> {
+  match (coords, place_uuid) {
+    (Some(coords), _) => Some(coords),
+    (None, Some(place_uuid)) => {
+      find_own_place(customer_repository, customer_uuid, place_uuid)
+        .await
+        .map(|place| place.coords)
+    }
+    (None, None) => None,
+  }
 }
 
 fn trip_created(trip: Trip) -> HttpResponse {
@@ -81,18 +1469,58 @@ fn trip_created(trip: Trip) -> HttpResponse {
     .json(CreatedRto::from(trip))
 }
 
-fn failed_create_trip(error: TripRepositoryError) -> HttpResponse {
+fn failed_create_trip(_error: TripRepositoryError) -> HttpResponse {
   HttpResponse::InternalServerError().finish()
 }
 
 impl CreateTrip {
-  fn from(auth: AccessTokenClaims, dto: CreateTripDto) -> Self {
+  fn from(
+    auth: AccessTokenClaims,
+    (start_coords, end_coords): (GeoPoint, GeoPoint),
+    preferences: RideRequirements,
+    dto: CreateTripDto,
+  ) -> Self {
+    let changes = dto.requirements.map(RideRequirementsOverride::from);
     Self {
       uuid: custom_nanoid(),
-      start_coords: dto.start_coords,
-      end_coords: dto.end_coords,
+      start_coords,
+      end_coords,
+      stops: dto.stops.into_iter().map(TripStop::new).collect(),
       driver_uuid: None,
-      consumer_uuid: auth.uuid
+      consumer_uuid: auth.uuid,
+      requirements: preferences.with_override(changes.unwrap_or_default()),
+      passengers: dto.passengers.unwrap_or(1),
+      fare_estimate_uuid: dto.fare_estimate_uuid,
+      pickup_at: dto.pickup_at,
+      booking: None,
+    }
+  }
+}
+
+impl From<RideRequirementsDto> for RideRequirementsOverride {
+  fn from(dto: RideRequirementsDto) -> Self {
+    Self {
+      wheelchair_accessible: dto.wheelchair_accessible,
+      assistance_dog: dto.assistance_dog,
+      child_seat: dto.child_seat,
+      preferred_language: dto.preferred_language,
+    }
+  }
+}
+
+// Transform FareEstimate domain to RTO
+impl From<FareEstimate> for FareEstimateRto {
+  fn from(estimate: FareEstimate) -> Self {
+    Self {
+      uuid: estimate.uuid,
+      pickup_at: estimate.pickup_at,
+      distance_m: estimate.distance_m,
+      duration_secs: estimate.duration_secs,
+      tariff_version: estimate.fare.tariff_version,
+      rate: estimate.fare.rate,
+      currency: String::from("EUR"),
+      min_cents: estimate.fare.min_cents,
+      max_cents: estimate.fare.max_cents,
     }
   }
 }
@@ -103,3 +1531,1453 @@ impl From<Trip> for CreatedRto {
     Self { uuid: trip.uuid }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{http::StatusCode, HttpRequest};
+  use chrono::Utc;
+  use chrono_tz::Europe::Dublin;
+  use repository::trip_repository::tests::InMemoryTripRepository;
+  use rto::driver_eta_rto::EtaTarget;
+
+  use crate::customers::repository::customer_repository::tests::InMemoryCustomerRepository;
+  use crate::customers::repository::customer_repository::CreateSavedPlace;
+  use crate::drivers::model::availability::DriverAvailability;
+  use crate::drivers::model::driver_status::DriverStatus;
+  use crate::drivers::model::location_fix::LocationFix;
+  use crate::drivers::model::vehicle::Vehicle;
+  use crate::drivers::repository::driver_repository::tests::InMemoryDriverRepository;
+  use crate::fares::model::fare_breakdown::FareBreakdown;
+  use crate::fares::model::rate_period::RatePeriod;
+  use crate::helpers::tests::{
+    create_access_token, create_fake_access_token_claims, http_request,
+    parse_http_response,
+  };
+  use crate::routing::haversine_router::HaversineRouter;
+  use crate::shared::geo_point::ServiceArea;
+  use crate::shared::role::Role;
+  use crate::users::repository::user_repository::tests::InMemoryUserRepository;
+  use crate::users::repository::user_repository::CreateUser;
+
+  use super::*;
+
+  #[actix_web::test]
+  async fn test_create_trip_applies_preferences_and_overrides() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = create_fake_access_token_claims();
+    let trip_repository = Arc::new(InMemoryTripRepository::new());
+    let customer_repository = InMemoryCustomerRepository::new();
+    customer_repository
+      .save_preferences(
+        &auth.uuid,
+        RideRequirements {
+          wheelchair_accessible: true,
+          preferred_language: Some(String::from("ga")),
+          ..RideRequirements::default()
+        },
+      )
+      .await
+      .unwrap();
+
+    let responder = create_trip(
+      web::Data::from(trip_repository.clone()),
+      web::Data::from(Arc::new(customer_repository)),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(
+        serde_json::from_value(serde_json::json!({
+          "startCoords": { "lat": 53.3498, "lng": -6.2603 },
+          "endCoords": { "lat": 53.4264, "lng": -6.2499 },
+          "requirements": { "childSeat": true },
+        }))
+        .unwrap(),
+      ),
+      auth.clone(),
+    )
+    .await;
+    let rto: CreatedRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+
+    let trip = trip_repository.find_one(&rto.uuid).await.unwrap();
+    assert_eq!(trip.consumer_uuid, auth.uuid);
+    assert!(trip.requirements.wheelchair_accessible);
+    assert!(trip.requirements.child_seat);
+    assert_eq!(trip.requirements.preferred_language.as_deref(), Some("ga"));
+  }
+
+  #[actix_web::test]
+  async fn test_create_trip_from_saved_places() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = create_fake_access_token_claims();
+    let trip_repository = Arc::new(InMemoryTripRepository::new());
+    let customer_repository = InMemoryCustomerRepository::new();
+    let home = customer_repository
+      .create_place(CreateSavedPlace {
+        uuid: custom_nanoid(),
+        customer_uuid: auth.uuid.clone(),
+        name: String::from("Home"),
+        coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+      })
+      .await
+      .unwrap();
+    let customer_repository = web::Data::from(Arc::new(customer_repository));
+
+    let responder = create_trip(
+      web::Data::from(trip_repository.clone()),
+      customer_repository.clone(),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(CreateTripDto {
+        start_coords: None,
+        start_place_uuid: Some(home.uuid.clone()),
+        end_coords: Some(GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        }),
+        end_place_uuid: None,
+        stops: Vec::new(),
+        requirements: None,
+        passengers: None,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: CreatedRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    let trip = trip_repository.find_one(&rto.uuid).await.unwrap();
+    assert_eq!(trip.start_coords, home.coords);
+
+    // Another customer cannot use the saved place
+    let responder = create_trip(
+      web::Data::from(trip_repository),
+      customer_repository,
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(CreateTripDto {
+        start_coords: None,
+        start_place_uuid: Some(home.uuid),
+        end_coords: Some(GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        }),
+        end_place_uuid: None,
+        stops: Vec::new(),
+        requirements: None,
+        passengers: None,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("invalid_saved_place"));
+  }
+
+  #[actix_web::test]
+  async fn test_estimate_is_attached_to_trip() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let auth = create_fake_access_token_claims();
+    let trip_repository = web::Data::new(InMemoryTripRepository::new());
+    let (start_coords, end_coords) = (
+      GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      },
+    );
+
+    let responder = estimate_trip(
+      trip_repository.clone(),
+      web::Data::new(TariffTable::nta()),
+      web::Data::from(Arc::new(HaversineRouter) as Arc<dyn Router>),
+      web::Data::new(Config::default()),
+      web::Json(
+        serde_json::from_value(serde_json::json!({
+          "startCoords": start_coords,
+          "endCoords": end_coords,
+        }))
+        .unwrap(),
+      ),
+      auth.clone(),
+    )
+    .await;
+    let estimate: FareEstimateRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    assert!(estimate.distance_m > 8000);
+    assert!(estimate.min_cents < estimate.max_cents);
+
+    let create_trip_dto = || CreateTripDto {
+      start_coords: Some(start_coords),
+      start_place_uuid: None,
+      end_coords: Some(end_coords),
+      end_place_uuid: None,
+      stops: Vec::new(),
+      requirements: None,
+      passengers: None,
+      fare_estimate_uuid: Some(estimate.uuid.clone()),
+      pickup_at: None,
+    };
+    let responder = create_trip(
+      trip_repository.clone(),
+      web::Data::new(InMemoryCustomerRepository::new()),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(create_trip_dto()),
+      auth.clone(),
+    )
+    .await;
+    let rto: CreatedRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    let trip = trip_repository.find_one(&rto.uuid).await.unwrap();
+    assert_eq!(trip.fare_estimate_uuid, Some(estimate.uuid.clone()));
+
+    // The quote belongs to the customer who asked for it
+    let responder = create_trip(
+      trip_repository.clone(),
+      web::Data::new(InMemoryCustomerRepository::new()),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(create_trip_dto()),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("invalid_fare_estimate"));
+
+    // Nor can it be used for another trip
+    let responder = create_trip(
+      trip_repository.clone(),
+      web::Data::new(InMemoryCustomerRepository::new()),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(CreateTripDto {
+        end_coords: Some(GeoPoint {
+          lat: 53.2707,
+          lng: -6.1991,
+        }),
+        passengers: Some(1),
+        ..create_trip_dto()
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("fare_estimate_mismatch"));
+    let responder = create_trip(
+      trip_repository.clone(),
+      web::Data::new(InMemoryCustomerRepository::new()),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(CreateTripDto {
+        passengers: Some(4),
+        ..create_trip_dto()
+      }),
+      auth.clone(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("fare_estimate_mismatch"));
+
+    // Or once it has expired
+    let expired = trip_repository
+      .create_estimate(FareEstimate {
+        uuid: custom_nanoid(),
+        created_at: Utc::now() - Duration::hours(1),
+        ..trip_repository.find_estimate(&estimate.uuid).await.unwrap()
+      })
+      .await
+      .unwrap();
+    let responder = create_trip(
+      trip_repository,
+      web::Data::new(InMemoryCustomerRepository::new()),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(Config::default()),
+      web::Json(CreateTripDto {
+        fare_estimate_uuid: Some(expired.uuid),
+        ..create_trip_dto()
+      }),
+      auth,
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("fare_estimate_expired"));
+  }
+
+  #[actix_web::test]
+  async fn test_create_trip_outside_service_area() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let config = Config {
+      service_area: Some(ServiceArea::IRELAND),
+      ..Config::default()
+    };
+
+    let responder = create_trip(
+      web::Data::from(Arc::new(InMemoryTripRepository::new())),
+      web::Data::from(Arc::new(InMemoryCustomerRepository::new())),
+      web::Data::new(InMemoryDriverRepository::new()),
+      web::Data::new(DriverLocationStore::new()),
+      web::Data::new(config),
+      web::Json(CreateTripDto {
+        start_coords: Some(GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        }),
+        start_place_uuid: None,
+        // London
+        end_coords: Some(GeoPoint {
+          lat: 51.5072,
+          lng: -0.1276,
+        }),
+        end_place_uuid: None,
+        stops: Vec::new(),
+        requirements: None,
+        passengers: None,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("outside_service_area"));
+  }
+
+  #[actix_web::test]
+  async fn test_scheduled_trip_is_pre_assigned() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let trip_repository = web::Data::new(InMemoryTripRepository::new());
+    // Driving without a break for longer than the continuous limit
+    let tired_since = Utc::now() - Duration::hours(5);
+    let driver_repository =
+      web::Data::new(online_driver("tired", tired_since).await);
+    driver_repository
+      .save_vehicle(Vehicle {
+        driver_uuid: String::from("driver"),
+        plate: String::from("241-D-12345"),
+        wheelchair_accessible: false,
+        assistance_dog: true,
+        child_seat: false,
+        spsv_licence: None,
+      })
+      .await
+      .unwrap();
+    let create_trip_dto = |pickup_at| CreateTripDto {
+      start_coords: Some(GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      }),
+      start_place_uuid: None,
+      end_coords: Some(GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      }),
+      end_place_uuid: None,
+      stops: Vec::new(),
+      requirements: None,
+      passengers: None,
+      fare_estimate_uuid: None,
+      pickup_at: Some(pickup_at),
+    };
+    let book = |pickup_at| {
+      create_trip(
+        trip_repository.clone(),
+        web::Data::new(InMemoryCustomerRepository::new()),
+        driver_repository.clone(),
+        web::Data::new(DriverLocationStore::new()),
+        web::Data::new(Config::default()),
+        web::Json(create_trip_dto(pickup_at)),
+        create_fake_access_token_claims(),
+      )
+    };
+
+    let responder = book(Utc::now() + Duration::minutes(5)).await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("outside_booking_window"));
+
+    let responder = book(Utc::now() + Duration::hours(2)).await;
+    let rto: CreatedRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    let trip = trip_repository.find_one(&rto.uuid).await.unwrap();
+    assert_eq!(trip.status, TripStatus::Scheduled);
+
+    let assign = |driver_uuid: &str| {
+      assign_trip(
+        trip_repository.clone(),
+        driver_repository.clone(),
+        web::Data::new(Config::default()),
+        web::Path::from(GetTripDto {
+          uuid: rto.uuid.clone(),
+        }),
+        web::Json(AssignTripDto {
+          driver_uuid: String::from(driver_uuid),
+        }),
+        create_fake_access_token_claims(),
+      )
+    };
+    let responder = assign("no_vehicle").await;
+    let error: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(error.code.as_deref(), Some("driver_ineligible"));
+
+    // Still over the limit when the trip is due
+    let responder = assign("tired").await;
+    let error: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(error.code.as_deref(), Some("driver_over_limit"));
+
+    let responder = assign("driver").await;
+    let trip: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(trip.status, TripStatus::Scheduled);
+    assert_eq!(trip.driver_uuid.as_deref(), Some("driver"));
+    assert!(trip.pickup_at.is_some());
+  }
+
+  #[test]
+  fn test_create_trip_dto_rejects_invalid_coords() {
+    let dto: CreateTripDto = serde_json::from_value(serde_json::json!({
+      "startCoords": { "lat": 153.3498, "lng": -6.2603 },
+      "endCoords": { "lat": 53.4264, "lng": -6.2499 },
+    }))
+    .unwrap();
+    assert!(dto.validate().is_err());
+  }
+
+  #[test]
+  fn test_create_trip_dto_requires_one_location_per_end() {
+    let dto: CreateTripDto = serde_json::from_value(serde_json::json!({
+      "startCoords": { "lat": 53.3498, "lng": -6.2603 },
+      "startPlaceUuid": custom_nanoid(),
+      "pickupAt": Utc::now() + Duration::hours(2),
+    }))
+    .unwrap();
+    assert!(dto.start_place_uuid.is_some() && dto.pickup_at.is_some());
+    assert!(dto.validate().is_err());
+  }
+
+  #[actix_web::test]
+  async fn test_get_eligible_drivers() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = trip_repository
+      .create(CreateTrip {
+        uuid: custom_nanoid(),
+        start_coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+        end_coords: GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        },
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: custom_nanoid(),
+        requirements: RideRequirements {
+          wheelchair_accessible: true,
+          ..RideRequirements::default()
+        },
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+        booking: None,
+      })
+      .await
+      .unwrap();
+    let now = Utc::now();
+    let driver_repository = InMemoryDriverRepository::from(
+      ["accessible", "saloon"]
+        .map(|driver_uuid| {
+          DriverAvailability::offline(driver_uuid, now)
+            .transition(DriverStatus::Online, now)
+            .unwrap()
+        })
+        .to_vec(),
+    );
+    for (driver_uuid, wheelchair_accessible) in
+      [("accessible", true), ("saloon", false)]
+    {
+      driver_repository
+        .save_vehicle(Vehicle {
+          driver_uuid: String::from(driver_uuid),
+          plate: String::from("241-D-12345"),
+          wheelchair_accessible,
+          assistance_dog: true,
+          child_seat: false,
+          spsv_licence: None,
+        })
+        .await
+        .unwrap();
+    }
+
+    let responder = get_eligible_drivers(
+      web::Data::from(Arc::new(trip_repository)),
+      web::Data::from(Arc::new(driver_repository)),
+      web::Path::from(GetTripDto { uuid: trip.uuid }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+
+    let rtos: Vec<EligibleDriverRto> =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rtos.len(), 1);
+    assert_eq!(rtos[0].driver_uuid, "accessible");
+  }
+
+  async fn create_requested_trip(
+    trip_repository: &InMemoryTripRepository,
+    consumer_uuid: &str,
+  ) -> Trip {
+    trip_repository
+      .create(CreateTrip {
+        uuid: custom_nanoid(),
+        start_coords: GeoPoint {
+          lat: 53.3498,
+          lng: -6.2603,
+        },
+        end_coords: GeoPoint {
+          lat: 53.4264,
+          lng: -6.2499,
+        },
+        stops: Vec::new(),
+        driver_uuid: None,
+        consumer_uuid: String::from(consumer_uuid),
+        requirements: RideRequirements::default(),
+        passengers: 1,
+        fare_estimate_uuid: None,
+        pickup_at: None,
+        booking: None,
+      })
+      .await
+      .unwrap()
+  }
+
+  #[actix_web::test]
+  async fn test_trip_lifecycle() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+    let now = Utc::now();
+    let availability = DriverAvailability::offline(&driver.uuid, now)
+      .transition(DriverStatus::Online, now)
+      .unwrap();
+    let driver_repository = web::Data::from(Arc::new(
+      InMemoryDriverRepository::from(vec![availability]),
+    ));
+    driver_repository
+      .save_vehicle(Vehicle {
+        driver_uuid: driver.uuid.clone(),
+        plate: String::from("241-D-12345"),
+        wheelchair_accessible: false,
+        assistance_dog: false,
+        child_seat: false,
+        spsv_licence: None,
+      })
+      .await
+      .unwrap();
+    let path = || {
+      web::Path::from(GetTripDto {
+        uuid: trip.uuid.clone(),
+      })
+    };
+
+    let responder = offer_trip(
+      trip_repository.clone(),
+      driver_repository.clone(),
+      web::Data::new(Config::default()),
+      path(),
+      web::Json(OfferTripDto {
+        driver_uuid: driver.uuid.clone(),
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.status, TripStatus::Offered);
+    assert_eq!(rto.driver_uuid.as_ref(), Some(&driver.uuid));
+
+    // Only the assigned driver can start the trip
+    let responder =
+      start_trip(trip_repository.clone(), path(), customer.clone()).await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let responder = accept_trip(
+      trip_repository.clone(),
+      driver_repository,
+      web::Data::new(Config::default()),
+      path(),
+      driver.clone(),
+    )
+    .await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.status, TripStatus::Accepted);
+    assert_eq!(rto.vehicle_plate.as_deref(), Some("241-D-12345"));
+
+    // The driver has to arrive before starting
+    let responder =
+      start_trip(trip_repository.clone(), path(), driver.clone()).await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("invalid_trip_transition"));
+
+    let responder =
+      arrive_trip(trip_repository.clone(), path(), driver.clone()).await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::OK);
+    let responder =
+      start_trip(trip_repository.clone(), path(), driver.clone()).await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::OK);
+    let responder = complete_trip(
+      trip_repository.clone(),
+      web::Data::new(TariffTable::nta()),
+      web::Data::from(Arc::new(HaversineRouter) as Arc<dyn Router>),
+      web::Data::new(Config::default()),
+      path(),
+      Some(web::Json(CompleteTripDto {
+        payment_method: Some(PaymentMethod::Card),
+        passengers: Some(3),
+      })),
+      driver.clone(),
+    )
+    .await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.payment_method, Some(PaymentMethod::Card));
+    assert_eq!(rto.passengers, 3);
+    let fare = rto.fare.unwrap();
+    assert_eq!(fare.items[0].code, "initial_charge");
+    let passengers_item = fare
+      .items
+      .iter()
+      .find(|item| item.code == "additional_passengers")
+      .map(|item| item.amount_cents);
+    assert_eq!(passengers_item, Some(200));
+    assert_eq!(
+      fare.total_cents,
+      fare.items.iter().map(|item| item.amount_cents).sum::<i64>()
+    );
+
+    let responder = cancel_trip(
+      trip_repository.clone(),
+      web::Data::new(Config::default()),
+      path(),
+      web::Json(cancel_dto(CancellationReason::ChangedPlans)),
+      customer,
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let trip = trip_repository.find_one(&trip.uuid).await.unwrap();
+    assert_eq!(trip.status, TripStatus::Completed);
+    assert_eq!(
+      trip.fare.map(|fare| fare.total_cents),
+      Some(fare.total_cents)
+    );
+    let statuses: Vec<TripStatus> = trip
+      .transitions
+      .iter()
+      .map(|transition| transition.to)
+      .collect();
+    assert_eq!(
+      statuses,
+      vec![
+        TripStatus::Offered,
+        TripStatus::Accepted,
+        TripStatus::DriverArrived,
+        TripStatus::InProgress,
+        TripStatus::Completed,
+      ]
+    );
+  }
+
+  #[actix_web::test]
+  async fn test_trip_stops() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+    let path = || {
+      web::Path::from(GetTripDto {
+        uuid: trip.uuid.clone(),
+      })
+    };
+    let stop_path = |stop_uuid: &str| {
+      web::Path::from(TripStopDto {
+        uuid: trip.uuid.clone(),
+        stop_uuid: String::from(stop_uuid),
+      })
+    };
+    let add_stop = |lat: f64, auth: AccessTokenClaims| {
+      add_trip_stop(
+        trip_repository.clone(),
+        web::Data::new(Config::default()),
+        path(),
+        web::Json(AddTripStopDto {
+          coords: GeoPoint { lat, lng: -6.2592 },
+          position: None,
+        }),
+        auth,
+      )
+    };
+
+    for lat in [53.3811, 53.3955] {
+      let responder = add_stop(lat, customer.clone()).await;
+      let response = responder.respond_to(&request);
+      assert_eq!(response.status(), StatusCode::OK);
+    }
+    // Other customers cannot change the route
+    let stranger = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let responder = add_stop(53.39, stranger).await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    // Saving the trip as loaded before the stops would drop them
+    let result = trip_repository
+      .update(trip.clone(), TripStatus::Requested)
+      .await;
+    assert!(matches!(
+      result,
+      Err(TripRepositoryError::VersionChanged(0))
+    ));
+
+    {
+      let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+      let now = Utc::now();
+      trips[0] = trips[0]
+        .clone()
+        .offer(&driver.uuid, None, now, now + Duration::seconds(20))
+        .and_then(|trip| trip.accept(None, None, now))
+        .and_then(|trip| trip.transition(TripStatus::DriverArrived, None, now))
+        .and_then(|trip| trip.transition(TripStatus::InProgress, None, now))
+        .unwrap();
+    }
+    let stops = trip_repository.find_one(&trip.uuid).await.unwrap().stops;
+
+    let responder = arrive_at_stop(
+      trip_repository.clone(),
+      stop_path(&stops[1].uuid),
+      driver.clone(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("stop_out_of_order"));
+    // Only the driver reports reaching a stop
+    let responder = arrive_at_stop(
+      trip_repository.clone(),
+      stop_path(&stops[0].uuid),
+      customer.clone(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let responder = arrive_at_stop(
+      trip_repository.clone(),
+      stop_path(&stops[0].uuid),
+      driver.clone(),
+    )
+    .await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert!(rto.stops[0].arrived_at.is_some());
+    let responder = remove_trip_stop(
+      trip_repository.clone(),
+      stop_path(&stops[0].uuid),
+      customer.clone(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("stop_reached"));
+
+    let responder = depart_from_stop(
+      trip_repository.clone(),
+      stop_path(&stops[0].uuid),
+      driver.clone(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::OK);
+    let responder = remove_trip_stop(
+      trip_repository.clone(),
+      stop_path(&stops[1].uuid),
+      customer.clone(),
+    )
+    .await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.stops.len(), 1);
+    assert_eq!(rto.stops[0].uuid, stops[0].uuid);
+    assert!(rto.stops[0].departed_at.is_some());
+
+    // Stop changes add no transitions, so event ids stay the same
+    let trip = trip_repository.find_one(&trip.uuid).await.unwrap();
+    assert_eq!(trip.transitions.len(), 4);
+    assert_eq!(TripEvent::current_status(&trip).id(), Some(4));
+  }
+
+  #[actix_web::test]
+  async fn test_shared_trip_link() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+    let user_repository = InMemoryUserRepository::new();
+    user_repository
+      .create(CreateUser {
+        uuid: driver.uuid.clone(),
+        user_name: custom_nanoid(),
+        first_name: Some(String::from("Aoife")),
+        role: Role::Driver,
+      })
+      .await
+      .unwrap();
+    let user_repository = web::Data::new(user_repository);
+    let location_store = web::Data::new(DriverLocationStore::new());
+    let path = || {
+      web::Path::from(GetTripDto {
+        uuid: trip.uuid.clone(),
+      })
+    };
+    let view = |token: &str| {
+      get_shared_trip(
+        trip_repository.clone(),
+        user_repository.clone(),
+        location_store.clone(),
+        web::Data::from(Arc::new(HaversineRouter) as Arc<dyn Router>),
+        web::Path::from(SharedTripDto {
+          token: String::from(token),
+        }),
+      )
+    };
+
+    // Only the customer shares their trip
+    let responder = share_trip(
+      trip_repository.clone(),
+      path(),
+      None,
+      AccessTokenClaims {
+        role: Role::Admin,
+        ..create_fake_access_token_claims()
+      },
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let responder = share_trip(
+      trip_repository.clone(),
+      path(),
+      Some(web::Json(ShareTripDto {
+        expires_in_mins: Some(60),
+      })),
+      customer.clone(),
+    )
+    .await;
+    let share: TripShareRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    assert_eq!(share.url, format!("/v1/shared-trips/{}", share.token));
+
+    // Nobody is on the way yet
+    let rto: SharedTripRto =
+      parse_http_response(view(&share.token).await, &request, StatusCode::OK)
+        .await;
+    assert_eq!(rto.status, TripStatus::Requested);
+    assert!(rto.driver_first_name.is_none());
+    assert!(rto.eta.is_none());
+
+    {
+      let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+      let now = Utc::now();
+      trips[0] = trips[0]
+        .clone()
+        .offer(&driver.uuid, None, now, now + Duration::seconds(20))
+        .and_then(|trip| {
+          trip.accept(Some(String::from("251-D-1234")), None, now)
+        })
+        .unwrap();
+    }
+    location_store.record(LocationFix {
+      driver_uuid: driver.uuid.clone(),
+      coords: GeoPoint {
+        lat: 53.3438,
+        lng: -6.2546,
+      },
+      accuracy_m: 5.0,
+      heading_deg: None,
+      speed_mps: None,
+      recorded_at: Utc::now(),
+    });
+    let rto: SharedTripRto =
+      parse_http_response(view(&share.token).await, &request, StatusCode::OK)
+        .await;
+    assert_eq!(rto.driver_first_name.as_deref(), Some("Aoife"));
+    assert_eq!(rto.vehicle_plate.as_deref(), Some("251-D-1234"));
+    assert!(rto.driver_position.is_some());
+    assert_eq!(rto.eta.map(|eta| eta.target), Some(EtaTarget::Pickup));
+
+    let responder = revoke_trip_share(
+      trip_repository.clone(),
+      web::Path::from(TripShareDto {
+        uuid: trip.uuid.clone(),
+        token: share.token.clone(),
+      }),
+      customer.clone(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = view(&share.token).await.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // A fresh link stops working once the trip ends
+    let responder =
+      share_trip(trip_repository.clone(), path(), None, customer.clone()).await;
+    let share: TripShareRto =
+      parse_http_response(responder, &request, StatusCode::CREATED).await;
+    {
+      let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+      trips[0].status = TripStatus::Completed;
+    }
+    let response = view(&share.token).await.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let responder =
+      share_trip(trip_repository.clone(), path(), None, customer).await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("trip_ended"));
+  }
+
+  #[actix_web::test]
+  async fn test_offer_trip_requires_online_driver() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &custom_nanoid()).await;
+
+    let responder = offer_trip(
+      web::Data::from(Arc::new(trip_repository)),
+      web::Data::from(Arc::new(InMemoryDriverRepository::new())),
+      web::Data::new(Config::default()),
+      web::Path::from(GetTripDto { uuid: trip.uuid }),
+      web::Json(OfferTripDto {
+        driver_uuid: custom_nanoid(),
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("driver_unavailable"));
+  }
+
+  // Online since `since` with a standard saloon
+  async fn online_driver(
+    driver_uuid: &str,
+    since: DateTime<Utc>,
+  ) -> InMemoryDriverRepository {
+    let availability = DriverAvailability::offline(driver_uuid, since)
+      .transition(DriverStatus::Online, since)
+      .unwrap();
+    let driver_repository = InMemoryDriverRepository::from(vec![availability]);
+    driver_repository
+      .save_vehicle(Vehicle {
+        driver_uuid: String::from(driver_uuid),
+        plate: String::from("241-D-12345"),
+        wheelchair_accessible: false,
+        assistance_dog: false,
+        child_seat: false,
+        spsv_licence: None,
+      })
+      .await
+      .unwrap();
+    driver_repository
+  }
+
+  #[actix_web::test]
+  async fn test_offer_trip_requires_suitable_vehicle() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver_uuid = custom_nanoid();
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &custom_nanoid()).await;
+    let trip = trip_repository
+      .update(
+        Trip {
+          requirements: RideRequirements {
+            wheelchair_accessible: true,
+            ..RideRequirements::default()
+          },
+          ..trip
+        },
+        TripStatus::Requested,
+      )
+      .await
+      .unwrap();
+
+    let responder = offer_trip(
+      web::Data::from(Arc::new(trip_repository)),
+      web::Data::from(Arc::new(online_driver(&driver_uuid, Utc::now()).await)),
+      web::Data::new(Config::default()),
+      web::Path::from(GetTripDto { uuid: trip.uuid }),
+      web::Json(OfferTripDto { driver_uuid }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("driver_ineligible"));
+  }
+
+  #[actix_web::test]
+  async fn test_offer_trip_refuses_driver_over_limit() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver_uuid = custom_nanoid();
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &custom_nanoid()).await;
+    // Driving without a break for longer than the continuous limit
+    let since = Utc::now() - Duration::hours(5);
+
+    let responder = offer_trip(
+      web::Data::from(Arc::new(trip_repository)),
+      web::Data::from(Arc::new(online_driver(&driver_uuid, since).await)),
+      web::Data::new(Config::default()),
+      web::Path::from(GetTripDto { uuid: trip.uuid }),
+      web::Json(OfferTripDto { driver_uuid }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("driver_over_limit"));
+  }
+
+  #[actix_web::test]
+  async fn test_accept_trip_refuses_driver_over_limit() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &custom_nanoid()).await;
+    // Offered before the driver went over the continuous limit
+    let now = Utc::now();
+    let offered = trip
+      .offer(&driver.uuid, None, now, now + Duration::seconds(20))
+      .unwrap();
+    let trip = trip_repository
+      .update(offered, TripStatus::Requested)
+      .await
+      .unwrap();
+    let since = now - Duration::hours(5);
+
+    let responder = accept_trip(
+      web::Data::from(Arc::new(trip_repository)),
+      web::Data::from(Arc::new(online_driver(&driver.uuid, since).await)),
+      web::Data::new(Config::default()),
+      web::Path::from(GetTripDto { uuid: trip.uuid }),
+      driver,
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+    assert_eq!(rto.code.as_deref(), Some("driver_over_limit"));
+  }
+
+  #[actix_web::test]
+  async fn test_list_trips_pages_by_pickup_time() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let now = Utc::now();
+    let mut uuids = Vec::new();
+    for hours in [3, 1, 2] {
+      let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+      uuids.push((hours, trip.uuid));
+    }
+    // Someone else's trip never shows up
+    create_requested_trip(&trip_repository, &custom_nanoid()).await;
+    {
+      let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+      for trip in trips.iter_mut() {
+        if let Some((hours, _)) =
+          uuids.iter().find(|(_, uuid)| *uuid == trip.uuid)
+        {
+          trip.pickup_at = Some(now + Duration::hours(*hours));
+        }
+        if trip.pickup_at == Some(now + Duration::hours(2)) {
+          trip.status = TripStatus::Cancelled;
+        }
+      }
+    }
+    uuids.sort();
+    let expected: Vec<String> =
+      uuids.into_iter().map(|(_, uuid)| uuid).collect();
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+
+    let mut listed = Vec::new();
+    let mut query = String::from("order=asc&limit=2");
+    loop {
+      let responder = list_trips(
+        trip_repository.clone(),
+        web::Query::from_query(&query).unwrap(),
+        customer.clone(),
+      )
+      .await;
+      let page: TripPageRto =
+        parse_http_response(responder, &request, StatusCode::OK).await;
+      assert!(page.trips.len() <= 2);
+      listed.extend(page.trips.into_iter().map(|trip| trip.uuid));
+      let Some(cursor) = page.next_cursor else {
+        break;
+      };
+      query = format!("order=asc&limit=2&cursor={cursor}");
+    }
+    assert_eq!(listed, expected);
+
+    // Latest pickup first by default
+    let responder = list_trips(
+      trip_repository.clone(),
+      web::Query::from_query("status=requested,scheduled").unwrap(),
+      customer.clone(),
+    )
+    .await;
+    let page: TripPageRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    let listed: Vec<&str> =
+      page.trips.iter().map(|trip| trip.uuid.as_str()).collect();
+    assert_eq!(listed, vec![expected[2].as_str(), expected[0].as_str()]);
+    assert!(page.next_cursor.is_none());
+
+    let responder = list_trips(
+      trip_repository,
+      web::Query::from_query("cursor=nonsense").unwrap(),
+      customer,
+    )
+    .await;
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::BAD_REQUEST).await;
+    assert_eq!(rto.code.as_deref(), Some("invalid_cursor"));
+  }
+
+  #[actix_web::test]
+  async fn test_driver_trip_summary() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    for _ in 0..3 {
+      create_requested_trip(&trip_repository, &custom_nanoid()).await;
+    }
+    let today = Utc::now().with_timezone(&Dublin).date_naive();
+    {
+      let mut trips = trip_repository.trips.write().unwrap(); // Acquire write lock
+      for (index, trip) in trips.iter_mut().enumerate() {
+        trip.driver_uuid = Some(driver.uuid.clone());
+        trip.status = match index {
+          0 => TripStatus::Cancelled,
+          _ => TripStatus::Completed,
+        };
+        trip.fare = Some(FareBreakdown {
+          tariff_version: String::from("test"),
+          rate: RatePeriod::Standard,
+          items: Vec::new(),
+          total_cents: 1500,
+          distance_m: Some(9000),
+        });
+      }
+    }
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+
+    let responder = get_trip_summary(
+      trip_repository.clone(),
+      web::Query::from_query(&format!("from={today}&to={today}")).unwrap(),
+      driver,
+    )
+    .await;
+    let rto: TripSummaryRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.days.len(), 1);
+    assert_eq!(rto.days[0].date, today);
+    assert_eq!(rto.days[0].completed_trips, 2);
+    assert_eq!(rto.days[0].cancelled_trips, 1);
+    // Fares of cancelled trips are not earned
+    assert_eq!(rto.fare_total_cents, 3000);
+
+    let responder = get_trip_summary(
+      trip_repository,
+      web::Query::from_query(&format!("from={today}&to={today}")).unwrap(),
+      create_fake_access_token_claims(),
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+  }
+
+  #[actix_web::test]
+  async fn test_customer_cancels_trip() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+
+    // Strangers do not even see the trip
+    let stranger = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let responder = cancel_trip(
+      trip_repository.clone(),
+      web::Data::new(Config::default()),
+      web::Path::from(GetTripDto {
+        uuid: trip.uuid.clone(),
+      }),
+      web::Json(cancel_dto(CancellationReason::ChangedPlans)),
+      stranger,
+    )
+    .await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let responder = cancel_trip(
+      trip_repository,
+      web::Data::new(Config::default()),
+      web::Path::from(GetTripDto { uuid: trip.uuid }),
+      web::Json(cancel_dto(CancellationReason::BookedByMistake)),
+      customer.clone(),
+    )
+    .await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.status, TripStatus::Cancelled);
+    assert_eq!(rto.transitions[0].actor_uuid.as_ref(), Some(&customer.uuid));
+    // No driver was assigned yet so the cancellation is free
+    let cancellation = rto.cancellation.unwrap();
+    assert_eq!(cancellation.cancelled_by, CancelledBy::Customer);
+    assert_eq!(cancellation.reason, CancellationReason::BookedByMistake);
+    assert_eq!(cancellation.fee_cents, 0);
+  }
+
+  #[actix_web::test]
+  async fn test_offered_driver_cannot_cancel_trip() {
+    let request: HttpRequest = http_request(&custom_nanoid());
+    let driver = AccessTokenClaims {
+      role: Role::Driver,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &custom_nanoid()).await;
+    let now = Utc::now();
+    let offered = trip
+      .offer(&driver.uuid, None, now, now + Duration::seconds(20))
+      .unwrap();
+    let trip = trip_repository
+      .update(offered, TripStatus::Requested)
+      .await
+      .unwrap();
+    let trip_repository = web::Data::from(Arc::new(trip_repository));
+    let cancel = |trip_uuid: String| {
+      cancel_trip(
+        trip_repository.clone(),
+        web::Data::new(Config::default()),
+        web::Path::from(GetTripDto { uuid: trip_uuid }),
+        web::Json(cancel_dto(CancellationReason::VehicleProblem)),
+        driver.clone(),
+      )
+    };
+
+    // An offer is declined, not cancelled
+    let responder = cancel(trip.uuid.clone()).await;
+    let response = responder.respond_to(&request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let accepted = trip.clone().accept(None, None, Utc::now()).unwrap();
+    trip_repository
+      .update(accepted, TripStatus::Offered)
+      .await
+      .unwrap();
+    let responder = cancel(trip.uuid.clone()).await;
+    let rto: GetTripRto =
+      parse_http_response(responder, &request, StatusCode::OK).await;
+    assert_eq!(rto.status, TripStatus::Cancelled);
+    assert_eq!(rto.cancellation.unwrap().cancelled_by, CancelledBy::Driver);
+  }
+
+  fn cancel_dto(reason: CancellationReason) -> CancelTripDto {
+    CancelTripDto { reason, note: None }
+  }
+
+  #[actix_web::test]
+  async fn test_participants_open_trip_socket() {
+    let config = Config {
+      jwt_secret: String::from("FAKE_JWT_SECRET"),
+      ..Config::default()
+    };
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let stranger = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+    let app = actix_web::test::init_service(
+      actix_web::App::new()
+        .app_data(web::Data::new(config.clone()))
+        .app_data(web::Data::new(trip_repository))
+        .app_data(web::Data::new(TripEventHub::new()))
+        .app_data(web::Data::new(DriverLocationStore::new()))
+        .route(
+          "/v1/trips/{uuid}/ws",
+          web::get().to(subscribe_trip::<InMemoryTripRepository>),
+        ),
+    )
+    .await;
+    let socket_request = |claims: &AccessTokenClaims| {
+      actix_web::test::TestRequest::get()
+        .uri(&format!("/v1/trips/{}/ws?lastEventId=0", trip.uuid))
+        .insert_header((
+          header::AUTHORIZATION,
+          format!("Bearer {}", create_access_token(claims, &config.jwt_secret)),
+        ))
+        .insert_header((header::UPGRADE, "websocket"))
+        .insert_header((header::CONNECTION, "Upgrade"))
+        .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+        .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+        .to_request()
+    };
+
+    let response =
+      actix_web::test::call_service(&app, socket_request(&stranger)).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response =
+      actix_web::test::call_service(&app, socket_request(&customer)).await;
+    assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+  }
+
+  #[actix_web::test]
+  async fn test_trip_events_resume_from_last_event_id() {
+    let config = Config {
+      jwt_secret: String::from("FAKE_JWT_SECRET"),
+      ..Config::default()
+    };
+    let customer = AccessTokenClaims {
+      role: Role::Customer,
+      ..create_fake_access_token_claims()
+    };
+    let trip_repository = InMemoryTripRepository::new();
+    let trip = create_requested_trip(&trip_repository, &customer.uuid).await;
+    let trip_uuid = trip.uuid.clone();
+    let cancelled = trip
+      .transition(TripStatus::Cancelled, None, Utc::now())
+      .unwrap();
+    trip_repository
+      .update(cancelled, TripStatus::Requested)
+      .await
+      .unwrap();
+    let app = actix_web::test::init_service(
+      actix_web::App::new()
+        .app_data(web::Data::new(config.clone()))
+        .app_data(web::Data::new(trip_repository))
+        .app_data(web::Data::new(TripEventHub::new()))
+        .app_data(web::Data::new(DriverLocationStore::new()))
+        .app_data(web::Data::from(Arc::new(HaversineRouter) as Arc<dyn Router>))
+        .route(
+          "/v1/trips/{uuid}/events",
+          web::get().to(get_trip_events::<InMemoryTripRepository>),
+        ),
+    )
+    .await;
+    let request = actix_web::test::TestRequest::get()
+      .uri(&format!("/v1/trips/{}/events", trip_uuid))
+      .insert_header((
+        header::AUTHORIZATION,
+        format!(
+          "Bearer {}",
+          create_access_token(&customer, &config.jwt_secret)
+        ),
+      ))
+      .insert_header(("Last-Event-ID", "0"))
+      .to_request();
+
+    let response = actix_web::test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    // The trip is over, so the stream ends after the missed cancellation
+    let body = actix_web::body::to_bytes(response.into_body())
+      .await
+      .unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("event: status\n"));
+    assert!(body.contains("id: 1\n"));
+    assert!(body.contains("\"status\":\"cancelled\""));
+  }
+}
@@ -0,0 +1,10 @@
+use chrono::NaiveDate;
+
+// Totals of a driver's trips picked up on one Irish calendar day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyTripSummary {
+  pub date: NaiveDate,
+  pub completed_trips: i64,
+  pub cancelled_trips: i64,
+  pub fare_total_cents: i64,
+}
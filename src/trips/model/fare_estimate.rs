@@ -0,0 +1,44 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::fares::model::fare_range::FareRange;
+use crate::shared::geo_point::GeoPoint;
+
+// Kept after booking, the quote is what disputes are settled against
+#[derive(Debug, Clone, PartialEq)]
+pub struct FareEstimate {
+  pub uuid: String,
+  pub consumer_uuid: String,
+  pub created_at: DateTime<Utc>,
+  pub start_coords: GeoPoint,
+  pub end_coords: GeoPoint,
+  pub pickup_at: DateTime<Utc>,
+  pub distance_m: i64,
+  pub duration_secs: i64,
+  pub passengers: i32,
+  pub fare: FareRange,
+}
+
+impl FareEstimate {
+  // Quotes are honoured for this long after they are given
+  const VALID_FOR_MINS: i64 = 15;
+  // Pickups this close to the quoted time fall in the same tariff period
+  const PICKUP_TOLERANCE_MINS: i64 = 15;
+
+  pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+    now - self.created_at > Duration::minutes(Self::VALID_FOR_MINS)
+  }
+
+  // Whether the quote was given for this trip
+  pub fn quotes(
+    &self,
+    (start_coords, end_coords): (&GeoPoint, &GeoPoint),
+    pickup_at: DateTime<Utc>,
+    passengers: i32,
+  ) -> bool {
+    self.start_coords == *start_coords
+      && self.end_coords == *end_coords
+      && (pickup_at - self.pickup_at).abs()
+        <= Duration::minutes(Self::PICKUP_TOLERANCE_MINS)
+      && self.passengers == passengers
+  }
+}
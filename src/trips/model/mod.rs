@@ -1,12 +1,686 @@
+pub mod daily_trip_summary;
+pub mod fare_estimate;
+pub mod payment_method;
+pub mod trip_booking;
+pub mod trip_cancellation;
+pub mod trip_cursor;
+pub mod trip_event;
+pub mod trip_offer;
+pub mod trip_share;
+pub mod trip_status;
+pub mod trip_stop;
+pub mod trip_transition;
+
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::fares::fare_engine::{calculate_fare, FareInput, StopWait};
+use crate::fares::model::fare_breakdown::FareBreakdown;
+use crate::fares::tariff_table::TariffTable;
+use crate::routing::router::{RouteMetric, Router};
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+use crate::trips::cancellation_policy::CancellationPolicy;
+use crate::zones::model::zone::Zone;
+use crate::zones::zone_surcharges;
+use payment_method::PaymentMethod;
+use trip_booking::TripBooking;
+use trip_cancellation::{CancellationRequest, TripCancellation};
+use trip_offer::{OfferOutcome, TripOffer};
+use trip_status::TripStatus;
+use trip_stop::TripStop;
+use trip_transition::{TripTransition, TripTransitionError};
+
+// Intermediate stops a trip can have
+pub const MAX_STOPS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Trip {
   pub uuid: String,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
-  pub start_coords: String,
-  pub end_coords: String,
+  pub start_coords: GeoPoint,
+  pub end_coords: GeoPoint,
+  // Visited in order between the pickup and the destination
+  pub stops: Vec<TripStop>,
+  // Driver the trip is offered to, then the one driving it
   pub driver_uuid: Option<String>,
-  pub consumer_uuid: String
+  pub consumer_uuid: String,
+  pub requirements: RideRequirements,
+  // Everyone travelling, the customer included
+  pub passengers: i32,
+  pub status: TripStatus,
+  // Oldest first, the trip is requested at creation time
+  pub transitions: Vec<TripTransition>,
+  // Every driver the trip was offered to, oldest first
+  pub offers: Vec<TripOffer>,
+  // Set when the trip is completed
+  pub fare: Option<FareBreakdown>,
+  // Quote the customer booked with
+  pub fare_estimate_uuid: Option<String>,
+  // Set for pre-booked trips, which start out scheduled
+  pub pickup_at: Option<DateTime<Utc>>,
+  // Set for trips of a recurring booking
+  pub booking: Option<TripBooking>,
+  // When the pickup reminder was sent
+  pub reminded_at: Option<DateTime<Utc>>,
+  // Set when the trip is cancelled
+  pub cancellation: Option<TripCancellation>,
+  // Registration of the vehicle the driver accepted the trip with
+  pub vehicle_plate: Option<String>,
+  // Reported by the driver when completing the trip
+  pub payment_method: Option<PaymentMethod>,
+  // Bumped on every save, a save expects the version it loaded
+  pub version: i32,
+}
+
+impl Trip {
+  pub fn transition(
+    mut self,
+    to: TripStatus,
+    actor_uuid: Option<String>,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if !self.status.can_transition_to(to) {
+      return Err(TripTransitionError::InvalidTransition {
+        from: self.status,
+        to,
+      });
+    }
+    // Leaving the offered status settles the pending offer
+    if let Some(offer) = self.offers.iter_mut().find(|offer| offer.is_pending())
+    {
+      if to == TripStatus::Accepted && offer.expires_at < at {
+        return Err(TripTransitionError::OfferExpired(offer.expires_at));
+      }
+      offer.outcome = match to {
+        TripStatus::Accepted => OfferOutcome::Accepted,
+        TripStatus::Requested => OfferOutcome::Declined,
+        _ => OfferOutcome::Withdrawn,
+      };
+      offer.responded_at = Some(at);
+    }
+    self.transitions.push(TripTransition {
+      from: self.status,
+      to,
+      at,
+      actor_uuid,
+    });
+    self.status = to;
+    self.updated_at = at;
+    Ok(self)
+  }
+
+  pub fn offer(
+    self,
+    driver_uuid: &str,
+    actor_uuid: Option<String>,
+    at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    let mut trip = self.transition(TripStatus::Offered, actor_uuid, at)?;
+    trip.offers.push(TripOffer {
+      driver_uuid: String::from(driver_uuid),
+      offered_at: at,
+      expires_at,
+      outcome: OfferOutcome::Pending,
+      responded_at: None,
+    });
+    Ok(Self {
+      driver_uuid: Some(String::from(driver_uuid)),
+      ..trip
+    })
+  }
+
+  // Scheduled trips can be given to a driver ahead of time, it is
+  // offered to them first once dispatch starts
+  pub fn pre_assign(
+    self,
+    driver_uuid: &str,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if self.status != TripStatus::Scheduled {
+      return Err(TripTransitionError::NotScheduled(self.status));
+    }
+    Ok(Self {
+      driver_uuid: Some(String::from(driver_uuid)),
+      updated_at: at,
+      ..self
+    })
+  }
+
+  pub fn decline(
+    self,
+    actor_uuid: Option<String>,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    let trip = self.transition(TripStatus::Requested, actor_uuid, at)?;
+    Ok(Self {
+      driver_uuid: None,
+      ..trip
+    })
+  }
+
+  // The driver did not answer in time, the trip goes back to dispatch
+  pub fn expire_offer(
+    mut self,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if let Some(offer) = self.offers.iter_mut().find(|offer| offer.is_pending())
+    {
+      offer.outcome = OfferOutcome::Expired;
+      offer.responded_at = Some(at);
+    }
+    self.decline(None, at)
+  }
+
+  // Meters the trip from the moment it started, over the routed distance
+  // until the driven one is recorded, with the time spent at each stop
+  pub fn complete(
+    self,
+    tariffs: &TariffTable,
+    router: &dyn Router,
+    zones: &[Zone],
+    payment_method: Option<PaymentMethod>,
+    actor_uuid: Option<String>,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    let started_at = self.started_at().unwrap_or(at);
+    let surcharges =
+      zone_surcharges(zones, &self.start_coords, &self.end_coords);
+    let legs_m = self.charged_legs_m(router);
+    let mut at_m = 0;
+    let stop_waits = self
+      .reached_stops()
+      .zip(&legs_m)
+      .map(|(stop, leg_m)| {
+        at_m += leg_m;
+        StopWait {
+          at_m,
+          secs: stop.waiting_secs(at),
+        }
+      })
+      .collect();
+    let trip = self.transition(TripStatus::Completed, actor_uuid, at)?;
+    let fare = calculate_fare(
+      tariffs,
+      &FareInput {
+        started_at,
+        distance_m: legs_m.iter().sum(),
+        duration_secs: (at - started_at).num_seconds(),
+        additional_passengers: trip.additional_passengers(),
+        stop_waits,
+        surcharges,
+      },
+    );
+    Ok(Self {
+      fare,
+      payment_method,
+      ..trip
+    })
+  }
+
+  // The fare includes the first passenger
+  pub fn additional_passengers(&self) -> i64 {
+    i64::from(self.passengers - 1).max(0)
+  }
+
+  // Records who cancelled and why, with the fee owed under the policy
+  pub fn cancel(
+    self,
+    request: CancellationRequest,
+    policy: &CancellationPolicy,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    let fee_cents = policy.fee_cents(&self, request.cancelled_by, at);
+    let trip =
+      self.transition(TripStatus::Cancelled, request.actor_uuid.clone(), at)?;
+    Ok(Self {
+      cancellation: Some(TripCancellation {
+        cancelled_by: request.cancelled_by,
+        actor_uuid: request.actor_uuid,
+        reason: request.reason,
+        note: request.note,
+        at,
+        fee_cents,
+      }),
+      ..trip
+    })
+  }
+
+  pub fn accept(
+    self,
+    vehicle_plate: Option<String>,
+    actor_uuid: Option<String>,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    let trip = self.transition(TripStatus::Accepted, actor_uuid, at)?;
+    Ok(Self {
+      vehicle_plate,
+      ..trip
+    })
+  }
+
+  // Inserted at the position asked, but never before a stop already
+  // reached. Added to the end by default.
+  pub fn add_stop(
+    mut self,
+    coords: GeoPoint,
+    position: Option<usize>,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if self.status.is_final() {
+      return Err(TripTransitionError::StopsClosed(self.status));
+    }
+    if self.stops.len() >= MAX_STOPS {
+      return Err(TripTransitionError::TooManyStops(MAX_STOPS));
+    }
+    let reached = self.reached_stops().count();
+    let position = position
+      .unwrap_or(self.stops.len())
+      .clamp(reached, self.stops.len());
+    self.stops.insert(position, TripStop::new(coords));
+    self.updated_at = at;
+    Ok(self)
+  }
+
+  pub fn remove_stop(
+    mut self,
+    stop_uuid: &str,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if self.status.is_final() {
+      return Err(TripTransitionError::StopsClosed(self.status));
+    }
+    let index = self.stop_index(stop_uuid)?;
+    if self.stops[index].arrived_at.is_some() {
+      return Err(TripTransitionError::StopReached(String::from(stop_uuid)));
+    }
+    self.stops.remove(index);
+    self.updated_at = at;
+    Ok(self)
+  }
+
+  // Stops are reached in order, after leaving the previous one
+  pub fn arrive_at_stop(
+    mut self,
+    stop_uuid: &str,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if self.status != TripStatus::InProgress {
+      return Err(TripTransitionError::NotInProgress(self.status));
+    }
+    let index = self.stop_index(stop_uuid)?;
+    let is_next = self.stops[index].arrived_at.is_none()
+      && (index == 0 || self.stops[index - 1].departed_at.is_some());
+    if !is_next {
+      return Err(TripTransitionError::StopOutOfOrder(String::from(stop_uuid)));
+    }
+    self.stops[index].arrived_at = Some(at);
+    self.updated_at = at;
+    Ok(self)
+  }
+
+  pub fn depart_from_stop(
+    mut self,
+    stop_uuid: &str,
+    at: DateTime<Utc>,
+  ) -> Result<Self, TripTransitionError> {
+    if self.status != TripStatus::InProgress {
+      return Err(TripTransitionError::NotInProgress(self.status));
+    }
+    let index = self.stop_index(stop_uuid)?;
+    let stop = &mut self.stops[index];
+    if stop.arrived_at.is_none() || stop.departed_at.is_some() {
+      return Err(TripTransitionError::StopOutOfOrder(String::from(stop_uuid)));
+    }
+    stop.departed_at = Some(at);
+    self.updated_at = at;
+    Ok(self)
+  }
+
+  fn stop_index(&self, stop_uuid: &str) -> Result<usize, TripTransitionError> {
+    self
+      .stops
+      .iter()
+      .position(|stop| stop.uuid == stop_uuid)
+      .ok_or_else(|| TripTransitionError::StopNotFound(String::from(stop_uuid)))
+  }
+
+  pub fn reached_stops(&self) -> impl Iterator<Item = &TripStop> {
+    self.stops.iter().filter(|stop| stop.arrived_at.is_some())
+  }
+
+  // Shortest route from the pickup through every stop reached to the
+  // destination, customers are not charged for detours
+  pub fn charged_legs_m(&self, router: &dyn Router) -> Vec<i64> {
+    let points: Vec<&GeoPoint> = std::iter::once(&self.start_coords)
+      .chain(self.reached_stops().map(|stop| &stop.coords))
+      .chain(std::iter::once(&self.end_coords))
+      .collect();
+    points
+      .windows(2)
+      .map(|leg| {
+        router
+          .route(leg[0], leg[1], RouteMetric::Shortest)
+          .distance_m
+      })
+      .collect()
+  }
+
+  // When the current driver accepted the trip
+  pub fn assigned_at(&self) -> Option<DateTime<Utc>> {
+    self
+      .transitions
+      .iter()
+      .rev()
+      .find(|transition| transition.to == TripStatus::Accepted)
+      .map(|transition| transition.at)
+  }
+
+  // From the driver accepting until the trip ended, or until now
+  pub fn driven_period(
+    &self,
+    now: DateTime<Utc>,
+  ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let accepted_at = self
+      .transitions
+      .iter()
+      .find(|transition| transition.to == TripStatus::Accepted)?
+      .at;
+    let ended_at = self
+      .transitions
+      .iter()
+      .find(|transition| transition.to.is_final())
+      .map_or(now, |transition| transition.at);
+    Some((accepted_at, ended_at))
+  }
+
+  // Trips booked for now are picked up as soon as they are requested
+  pub fn pickup_time(&self) -> DateTime<Utc> {
+    self.pickup_at.unwrap_or(self.created_at)
+  }
+
+  pub fn started_at(&self) -> Option<DateTime<Utc>> {
+    self
+      .transitions
+      .iter()
+      .find(|transition| transition.to == TripStatus::InProgress)
+      .map(|transition| transition.at)
+  }
+
+  // Scheduled trips are only dispatched once released
+  pub fn dispatch_started_at(&self) -> DateTime<Utc> {
+    self
+      .transitions
+      .iter()
+      .find(|transition| transition.from == TripStatus::Scheduled)
+      .map_or(self.created_at, |transition| transition.at)
+  }
+
+  pub fn pending_offer(&self) -> Option<&TripOffer> {
+    self.offers.iter().find(|offer| offer.is_pending())
+  }
+
+  pub fn was_offered_to(&self, driver_uuid: &str) -> bool {
+    self
+      .offers
+      .iter()
+      .any(|offer| offer.driver_uuid == driver_uuid)
+  }
+
+  pub fn is_assigned_to(&self, driver_uuid: &str) -> bool {
+    self.driver_uuid.as_deref() == Some(driver_uuid)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{Duration, TimeZone};
+
+  use super::trip_cancellation::{CancellationReason, CancelledBy};
+  use super::*;
+  use crate::routing::haversine_router::HaversineRouter;
+
+  fn requested_trip() -> Trip {
+    Trip {
+      uuid: String::from("trip"),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      start_coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      end_coords: GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      },
+      stops: Vec::new(),
+      driver_uuid: None,
+      consumer_uuid: String::from("customer"),
+      requirements: RideRequirements::default(),
+      passengers: 1,
+      status: TripStatus::Requested,
+      transitions: Vec::new(),
+      offers: Vec::new(),
+      fare: None,
+      fare_estimate_uuid: None,
+      pickup_at: None,
+      booking: None,
+      reminded_at: None,
+      cancellation: None,
+      vehicle_plate: None,
+      payment_method: None,
+      version: 0,
+    }
+  }
+
+  #[test]
+  fn test_transitions_are_recorded() {
+    let now = Utc::now();
+    let trip = requested_trip()
+      .offer("driver", None, now, now + Duration::seconds(20))
+      .unwrap()
+      .transition(TripStatus::Accepted, Some(String::from("driver")), now)
+      .unwrap();
+    assert_eq!(trip.status, TripStatus::Accepted);
+    assert!(trip.is_assigned_to("driver"));
+    assert_eq!(trip.transitions.len(), 2);
+    assert_eq!(trip.transitions[1].from, TripStatus::Offered);
+    assert_eq!(trip.transitions[1].actor_uuid.as_deref(), Some("driver"));
+    assert_eq!(trip.offers[0].outcome, OfferOutcome::Accepted);
+  }
+
+  #[test]
+  fn test_decline_clears_driver() {
+    let now = Utc::now();
+    let trip = requested_trip()
+      .offer("driver", None, now, now + Duration::seconds(20))
+      .unwrap()
+      .decline(Some(String::from("driver")), now)
+      .unwrap();
+    assert_eq!(trip.status, TripStatus::Requested);
+    assert_eq!(trip.driver_uuid, None);
+    assert_eq!(trip.offers[0].outcome, OfferOutcome::Declined);
+    assert!(trip.was_offered_to("driver"));
+  }
+
+  #[test]
+  fn test_expired_offer_cannot_be_accepted() {
+    let now = Utc::now();
+    let expires_at = now + Duration::seconds(20);
+    let trip = requested_trip()
+      .offer("driver", None, now, expires_at)
+      .unwrap();
+    let error = trip
+      .clone()
+      .transition(
+        TripStatus::Accepted,
+        Some(String::from("driver")),
+        expires_at + Duration::seconds(1),
+      )
+      .unwrap_err();
+    assert_eq!(error, TripTransitionError::OfferExpired(expires_at));
+
+    let trip = trip.expire_offer(expires_at).unwrap();
+    assert_eq!(trip.status, TripStatus::Requested);
+    assert_eq!(trip.offers[0].outcome, OfferOutcome::Expired);
+    assert!(trip.pending_offer().is_none());
+  }
+
+  #[test]
+  fn test_only_scheduled_trips_are_pre_assigned() {
+    let now = Utc::now();
+    let error = requested_trip().pre_assign("driver", now).unwrap_err();
+    assert_eq!(
+      error,
+      TripTransitionError::NotScheduled(TripStatus::Requested)
+    );
+
+    let released_at = now + Duration::hours(1);
+    let trip = Trip {
+      status: TripStatus::Scheduled,
+      pickup_at: Some(now + Duration::hours(2)),
+      ..requested_trip()
+    }
+    .pre_assign("driver", now)
+    .unwrap();
+    assert!(trip.is_assigned_to("driver"));
+    assert_eq!(trip.dispatch_started_at(), trip.created_at);
+    let trip = trip
+      .transition(TripStatus::Requested, None, released_at)
+      .unwrap();
+    assert_eq!(trip.dispatch_started_at(), released_at);
+  }
+
+  #[test]
+  fn test_late_customer_cancellation_is_charged() {
+    let policy = CancellationPolicy {
+      free_period_secs: 300,
+      fee_cents: 1000,
+    };
+    let request = |cancelled_by| CancellationRequest {
+      cancelled_by,
+      actor_uuid: None,
+      reason: CancellationReason::ChangedPlans,
+      note: None,
+    };
+    let now = Utc::now();
+    let accepted = requested_trip()
+      .offer("driver", None, now, now + Duration::seconds(20))
+      .unwrap()
+      .transition(TripStatus::Accepted, Some(String::from("driver")), now)
+      .unwrap();
+
+    // Within the free period
+    let trip = accepted
+      .clone()
+      .cancel(request(CancelledBy::Customer), &policy, now)
+      .unwrap();
+    let cancellation = trip.cancellation.unwrap();
+    assert_eq!(trip.status, TripStatus::Cancelled);
+    assert_eq!(cancellation.fee_cents, 0);
+
+    let late = now + Duration::minutes(6);
+    let trip = accepted
+      .clone()
+      .cancel(request(CancelledBy::Customer), &policy, late)
+      .unwrap();
+    assert_eq!(trip.cancellation.unwrap().fee_cents, 1000);
+    let trip = accepted
+      .clone()
+      .cancel(request(CancelledBy::Driver), &policy, late)
+      .unwrap();
+    assert_eq!(trip.cancellation.unwrap().fee_cents, 0);
+
+    // Arrived drivers are charged for straight away
+    let trip = accepted
+      .transition(TripStatus::DriverArrived, None, now)
+      .unwrap()
+      .cancel(request(CancelledBy::Customer), &policy, now)
+      .unwrap();
+    let cancellation = trip.cancellation.unwrap();
+    assert_eq!(cancellation.cancelled_by, CancelledBy::Customer);
+    assert_eq!(cancellation.fee_cents, 1000);
+  }
+
+  #[test]
+  fn test_illegal_transition_is_rejected() {
+    let trip = requested_trip();
+    let error = trip
+      .transition(TripStatus::Completed, None, Utc::now())
+      .unwrap_err();
+    assert_eq!(
+      error,
+      TripTransitionError::InvalidTransition {
+        from: TripStatus::Requested,
+        to: TripStatus::Completed,
+      }
+    );
+  }
+
+  #[test]
+  fn test_stops_are_reached_in_order_and_waiting_is_charged() {
+    // Wednesday noon, on the standard rate
+    let now = Utc.with_ymd_and_hms(2025, 11, 5, 12, 0, 0).unwrap();
+    let stop = GeoPoint {
+      lat: 53.3811,
+      lng: -6.2592,
+    };
+    let trip = requested_trip()
+      .add_stop(stop, None, now)
+      .and_then(|trip| trip.add_stop(stop, Some(0), now))
+      .unwrap();
+    let (first, second) =
+      (trip.stops[0].uuid.clone(), trip.stops[1].uuid.clone());
+    let error = trip.clone().arrive_at_stop(&first, now).unwrap_err();
+    assert_eq!(
+      error,
+      TripTransitionError::NotInProgress(TripStatus::Requested)
+    );
+
+    let started_at = now - Duration::minutes(30);
+    let trip = trip
+      .offer("driver", None, started_at, now)
+      .and_then(|trip| trip.accept(None, None, started_at))
+      .and_then(|trip| {
+        trip.transition(TripStatus::DriverArrived, None, started_at)
+      })
+      .and_then(|trip| {
+        trip.transition(TripStatus::InProgress, None, started_at)
+      })
+      .unwrap();
+    let error = trip.clone().arrive_at_stop(&second, now).unwrap_err();
+    assert_eq!(error, TripTransitionError::StopOutOfOrder(second.clone()));
+
+    let arrived_at = now - Duration::minutes(20);
+    let trip = trip
+      .arrive_at_stop(&first, arrived_at)
+      .and_then(|trip| {
+        trip.depart_from_stop(&first, arrived_at + Duration::minutes(10))
+      })
+      .unwrap();
+    let error = trip.clone().remove_stop(&first, now).unwrap_err();
+    assert_eq!(error, TripTransitionError::StopReached(first.clone()));
+    // New stops go after the ones already reached
+    let trip = trip.add_stop(stop, Some(0), now).unwrap();
+    assert_eq!(trip.stops[0].uuid, first);
+    let trip = trip.remove_stop(&second, now).unwrap();
+    assert_eq!(trip.stops.len(), 2);
+
+    // The stop never reached is not part of the charged route
+    let router = HaversineRouter;
+    let trip = trip
+      .complete(&TariffTable::nta(), &router, &[], None, None, now)
+      .unwrap();
+    let direct = router
+      .route(&trip.start_coords, &trip.end_coords, RouteMetric::Shortest)
+      .distance_m;
+    let legs_m = trip.charged_legs_m(&router);
+    assert_eq!(legs_m.len(), 2);
+    assert!(legs_m.iter().sum::<i64>() >= direct);
+    let fare = trip.fare.unwrap();
+    let waiting = fare.items.iter().find(|item| item.code == "waiting_time");
+    // Ten minutes in the first band
+    assert_eq!(waiting.map(|item| item.amount_cents), Some(460));
+  }
 }
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+// How the customer paid the driver at the end of the trip
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum PaymentMethod {
+  #[serde(rename = "cash")]
+  Cash,
+  #[serde(rename = "card")]
+  Card,
+  // Billed later to a business account
+  #[serde(rename = "account")]
+  Account,
+}
@@ -0,0 +1,9 @@
+use chrono::NaiveDate;
+
+// Date of a recurring booking the trip was created for. A date never has
+// more than one trip that is not cancelled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TripBooking {
+  pub booking_uuid: String,
+  pub occurs_on: NaiveDate,
+}
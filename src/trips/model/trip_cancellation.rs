@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum CancelledBy {
+  #[serde(rename = "customer")]
+  Customer,
+  #[serde(rename = "driver")]
+  Driver,
+  #[serde(rename = "dispatcher")]
+  Dispatcher,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum CancellationReason {
+  // Given by customers
+  #[serde(rename = "changed_plans")]
+  ChangedPlans,
+  #[serde(rename = "booked_by_mistake")]
+  BookedByMistake,
+  #[serde(rename = "driver_late")]
+  DriverLate,
+  #[serde(rename = "found_other_transport")]
+  FoundOtherTransport,
+  // Given by drivers
+  #[serde(rename = "vehicle_problem")]
+  VehicleProblem,
+  #[serde(rename = "unsafe_pickup")]
+  UnsafePickup,
+  #[serde(rename = "customer_unreachable")]
+  CustomerUnreachable,
+  // Given by dispatchers
+  #[serde(rename = "duplicate_booking")]
+  DuplicateBooking,
+  #[serde(rename = "no_drivers_available")]
+  NoDriversAvailable,
+  #[serde(rename = "customer_request")]
+  CustomerRequest,
+  #[serde(rename = "other")]
+  Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancellationRequest {
+  pub cancelled_by: CancelledBy,
+  pub actor_uuid: Option<String>,
+  pub reason: CancellationReason,
+  pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TripCancellation {
+  pub cancelled_by: CancelledBy,
+  pub actor_uuid: Option<String>,
+  pub reason: CancellationReason,
+  pub note: Option<String>,
+  pub at: DateTime<Utc>,
+  // Charged to the customer, zero when the cancellation is free
+  pub fee_cents: i64,
+}
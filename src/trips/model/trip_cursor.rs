@@ -0,0 +1,47 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+
+use super::Trip;
+
+// Position in a trip listing, the pickup time and uuid of the last trip
+// of the previous page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TripCursor {
+  pub pickup_at: DateTime<Utc>,
+  pub uuid: String,
+}
+
+impl From<&Trip> for TripCursor {
+  fn from(trip: &Trip) -> Self {
+    Self {
+      pickup_at: trip.pickup_time(),
+      uuid: trip.uuid.clone(),
+    }
+  }
+}
+
+// Opaque to clients, e.g. "1767225600000000_V1StGXR8Z5jdHi6BmyT"
+impl fmt::Display for TripCursor {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}_{}", self.pickup_at.timestamp_micros(), self.uuid)
+  }
+}
+
+impl FromStr for TripCursor {
+  type Err = ();
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let (micros, uuid) = value.split_once('_').ok_or(())?;
+    let micros = micros.parse().map_err(|_| ())?;
+    let pickup_at = DateTime::from_timestamp_micros(micros).ok_or(())?;
+    if uuid.is_empty() {
+      return Err(());
+    }
+    Ok(Self {
+      pickup_at,
+      uuid: String::from(uuid),
+    })
+  }
+}
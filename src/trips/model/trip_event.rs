@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+
+use super::trip_status::TripStatus;
+use super::Trip;
+use crate::drivers::model::location_fix::LocationFix;
+
+// Pushed to the people following a trip
+#[derive(Debug, Clone, PartialEq)]
+pub enum TripEvent {
+  // `id` is the number of transitions so far, so clients resume from
+  // the trip record even after a restart
+  Status {
+    id: u64,
+    status: TripStatus,
+    at: DateTime<Utc>,
+    driver_uuid: Option<String>,
+  },
+  DriverPosition(LocationFix),
+}
+
+impl TripEvent {
+  // The trip as it is now
+  pub fn current_status(trip: &Trip) -> Self {
+    let at = trip
+      .transitions
+      .last()
+      .map(|transition| transition.at)
+      .unwrap_or(trip.created_at);
+    Self::Status {
+      id: trip.transitions.len() as u64,
+      status: trip.status,
+      at,
+      driver_uuid: trip.driver_uuid.clone(),
+    }
+  }
+
+  // Transitions after `last_event_id`, the driver is the current one
+  pub fn missed_statuses(trip: &Trip, last_event_id: u64) -> Vec<Self> {
+    trip
+      .transitions
+      .iter()
+      .enumerate()
+      .skip(last_event_id as usize)
+      .map(|(index, transition)| Self::Status {
+        id: index as u64 + 1,
+        status: transition.to,
+        at: transition.at,
+        driver_uuid: trip.driver_uuid.clone(),
+      })
+      .collect()
+  }
+
+  pub fn id(&self) -> Option<u64> {
+    match self {
+      Self::Status { id, .. } => Some(*id),
+      Self::DriverPosition(_) => None,
+    }
+  }
+
+  // Replayed and live statuses overlap, positions are always new
+  pub fn is_after(&self, last_event_id: Option<u64>) -> bool {
+    match (self.id(), last_event_id) {
+      (Some(id), Some(last_event_id)) => id > last_event_id,
+      _ => true,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::Duration;
+
+  use super::*;
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::ride_requirements::RideRequirements;
+
+  #[test]
+  fn test_missed_statuses_resume_after_last_event() {
+    let now = Utc::now();
+    let trip = Trip {
+      uuid: String::from("trip"),
+      created_at: now,
+      updated_at: now,
+      start_coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      end_coords: GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      },
+      stops: Vec::new(),
+      driver_uuid: None,
+      consumer_uuid: String::from("customer"),
+      requirements: RideRequirements::default(),
+      passengers: 1,
+      status: TripStatus::Requested,
+      transitions: Vec::new(),
+      offers: Vec::new(),
+      fare: None,
+      fare_estimate_uuid: None,
+      pickup_at: None,
+      booking: None,
+      reminded_at: None,
+      cancellation: None,
+      vehicle_plate: None,
+      payment_method: None,
+      version: 0,
+    };
+    assert_eq!(TripEvent::current_status(&trip).id(), Some(0));
+
+    let trip = trip
+      .offer("driver", None, now, now + Duration::seconds(20))
+      .and_then(|trip| trip.transition(TripStatus::Accepted, None, now))
+      .and_then(|trip| trip.transition(TripStatus::DriverArrived, None, now))
+      .unwrap();
+    let missed = TripEvent::missed_statuses(&trip, 1);
+    let ids: Vec<Option<u64>> = missed.iter().map(TripEvent::id).collect();
+    assert_eq!(ids, vec![Some(2), Some(3)]);
+    assert_eq!(missed.last(), Some(&TripEvent::current_status(&trip)));
+    assert!(TripEvent::missed_statuses(&trip, 3).is_empty());
+    assert!(missed[0].is_after(None));
+    assert!(!missed[0].is_after(Some(2)));
+  }
+}
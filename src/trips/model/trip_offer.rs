@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum OfferOutcome {
+  #[serde(rename = "pending")]
+  Pending,
+  #[serde(rename = "accepted")]
+  Accepted,
+  #[serde(rename = "declined")]
+  Declined,
+  #[serde(rename = "expired")]
+  Expired,
+  // The trip was cancelled or expired while offered
+  #[serde(rename = "withdrawn")]
+  Withdrawn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TripOffer {
+  pub driver_uuid: String,
+  pub offered_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+  pub outcome: OfferOutcome,
+  pub responded_at: Option<DateTime<Utc>>,
+}
+
+impl TripOffer {
+  pub fn is_pending(&self) -> bool {
+    self.outcome == OfferOutcome::Pending
+  }
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+
+// Read-only link for friends and family to follow a trip without an
+// account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TripShare {
+  // Unguessable, anyone holding it can follow the trip
+  pub token: String,
+  pub trip_uuid: String,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+  pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl TripShare {
+  // The link also stops working once the trip ends
+  pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+    self.revoked_at.is_none() && now < self.expires_at
+  }
+}
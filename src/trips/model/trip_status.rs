@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum TripStatus {
+  // Booked for later, dispatch starts shortly before pickup
+  #[serde(rename = "scheduled")]
+  Scheduled,
+  #[serde(rename = "requested")]
+  Requested,
+  #[serde(rename = "offered")]
+  Offered,
+  #[serde(rename = "accepted")]
+  Accepted,
+  #[serde(rename = "driver_arrived")]
+  DriverArrived,
+  #[serde(rename = "in_progress")]
+  InProgress,
+  #[serde(rename = "completed")]
+  Completed,
+  #[serde(rename = "cancelled")]
+  Cancelled,
+  #[serde(rename = "expired")]
+  Expired,
+  #[serde(rename = "no_show")]
+  NoShow,
+}
+
+impl TripStatus {
+  pub fn can_transition_to(&self, to: TripStatus) -> bool {
+    use TripStatus::*;
+    matches!(
+      (self, to),
+      // Pre-assigned drivers get the first offer
+      (Scheduled, Requested | Offered | Cancelled)
+        | (Requested, Offered | Cancelled | Expired)
+        // Declined or timed out offers go back to requested
+        | (Offered, Requested | Accepted | Cancelled | Expired)
+        | (Accepted, DriverArrived | Cancelled)
+        | (DriverArrived, InProgress | Cancelled | NoShow)
+        | (InProgress, Completed)
+    )
+  }
+
+  // Nothing can happen to the trip anymore
+  pub fn is_final(&self) -> bool {
+    use TripStatus::*;
+    matches!(self, Completed | Cancelled | Expired | NoShow)
+  }
+
+  // The driver has taken the trip on and not finished it yet
+  pub fn is_accepted(&self) -> bool {
+    use TripStatus::*;
+    matches!(self, Accepted | DriverArrived | InProgress)
+  }
+
+  // The customer can follow the driver once they have accepted
+  pub fn shares_driver_position(&self) -> bool {
+    use TripStatus::*;
+    matches!(self, Accepted | DriverArrived | InProgress)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_happy_path_transitions() {
+    let path = [
+      TripStatus::Requested,
+      TripStatus::Offered,
+      TripStatus::Accepted,
+      TripStatus::DriverArrived,
+      TripStatus::InProgress,
+      TripStatus::Completed,
+    ];
+    for pair in path.windows(2) {
+      assert!(pair[0].can_transition_to(pair[1]), "{:?}", pair);
+    }
+  }
+
+  #[test]
+  fn test_illegal_transitions() {
+    assert!(!TripStatus::Requested.can_transition_to(TripStatus::InProgress));
+    assert!(!TripStatus::InProgress.can_transition_to(TripStatus::Cancelled));
+    assert!(!TripStatus::Completed.can_transition_to(TripStatus::Requested));
+  }
+
+  #[test]
+  fn test_final_statuses_have_no_transitions() {
+    let all = [
+      TripStatus::Scheduled,
+      TripStatus::Requested,
+      TripStatus::Offered,
+      TripStatus::Accepted,
+      TripStatus::DriverArrived,
+      TripStatus::InProgress,
+      TripStatus::Completed,
+      TripStatus::Cancelled,
+      TripStatus::Expired,
+      TripStatus::NoShow,
+    ];
+    for from in all.iter().filter(|status| status.is_final()) {
+      assert!(
+        !all.iter().any(|to| from.can_transition_to(*to)),
+        "{:?}",
+        from
+      );
+      assert!(!from.shares_driver_position());
+    }
+  }
+
+  #[test]
+  fn test_serialization() {
+    let serialized = serde_json::to_string(&TripStatus::DriverArrived)
+      .expect("Failed to serialize");
+    assert_eq!(serialized, "\"driver_arrived\"");
+  }
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+
+use crate::custom_nanoid;
+use crate::shared::geo_point::GeoPoint;
+
+// Intermediate stop between the pickup and the destination, e.g. to drop
+// off a friend on the way
+#[derive(Debug, Clone, PartialEq)]
+pub struct TripStop {
+  pub uuid: String,
+  pub coords: GeoPoint,
+  // Reported by the driver
+  pub arrived_at: Option<DateTime<Utc>>,
+  pub departed_at: Option<DateTime<Utc>>,
+}
+
+impl TripStop {
+  pub fn new(coords: GeoPoint) -> Self {
+    Self {
+      uuid: custom_nanoid(),
+      coords,
+      arrived_at: None,
+      departed_at: None,
+    }
+  }
+
+  // Time spent at the stop, until now for the stop the car is at
+  pub fn waiting_secs(&self, now: DateTime<Utc>) -> i64 {
+    self.arrived_at.map_or(0, |arrived_at| {
+      (self.departed_at.unwrap_or(now) - arrived_at).num_seconds()
+    })
+  }
+}
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use super::trip_status::TripStatus;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TripTransitionError {
+  #[error("Cannot go from {from:?} to {to:?}")]
+  InvalidTransition { from: TripStatus, to: TripStatus },
+
+  #[error("Offer expired at {0}")]
+  OfferExpired(DateTime<Utc>),
+
+  #[error("Only scheduled trips can be assigned, trip is {0:?}")]
+  NotScheduled(TripStatus),
+
+  #[error("Stops cannot be changed once the trip is {0:?}")]
+  StopsClosed(TripStatus),
+
+  #[error("Stops are reached while the trip is in progress, trip is {0:?}")]
+  NotInProgress(TripStatus),
+
+  #[error("Stop {0} not found")]
+  StopNotFound(String),
+
+  #[error("Stop {0} is not the next one, or was already left")]
+  StopOutOfOrder(String),
+
+  #[error("Stop {0} was already reached")]
+  StopReached(String),
+
+  #[error("Trips have at most {0} stops")]
+  TooManyStops(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TripTransition {
+  pub from: TripStatus,
+  pub to: TripStatus,
+  pub at: DateTime<Utc>,
+  // User that caused the transition, none for automatic ones
+  pub actor_uuid: Option<String>,
+}
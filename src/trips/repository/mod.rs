@@ -1 +1,2 @@
+pub mod publishing_trip_repository;
 pub mod trip_repository;
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::trip_repository::{
+  CreateTrip, TripQuery, TripRepository, TripRepositoryError,
+};
+use crate::trips::model::daily_trip_summary::DailyTripSummary;
+use crate::trips::model::fare_estimate::FareEstimate;
+use crate::trips::model::trip_share::TripShare;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::model::Trip;
+use crate::trips::trip_event_hub::TripEventHub;
+
+// Publishes every stored transition to the event hub, whichever handler
+// or background task made it
+pub struct PublishingTripRepository<TR: TripRepository> {
+  inner: TR,
+  trip_events: Arc<TripEventHub>,
+}
+
+impl<TR: TripRepository> PublishingTripRepository<TR> {
+  pub fn new(inner: TR, trip_events: Arc<TripEventHub>) -> Self {
+    Self { inner, trip_events }
+  }
+}
+
+impl<TR: TripRepository> TripRepository for PublishingTripRepository<TR> {
+  async fn find_one(&self, uuid: &str) -> Option<Trip> {
+    self.inner.find_one(uuid).await
+  }
+
+  async fn find_by_status(&self, statuses: &[TripStatus]) -> Vec<Trip> {
+    self.inner.find_by_status(statuses).await
+  }
+
+  async fn find_for_driver(
+    &self,
+    driver_uuid: &str,
+    statuses: &[TripStatus],
+  ) -> Vec<Trip> {
+    self.inner.find_for_driver(driver_uuid, statuses).await
+  }
+
+  async fn find_page(&self, query: &TripQuery) -> Vec<Trip> {
+    self.inner.find_page(query).await
+  }
+
+  async fn summarise_for_driver(
+    &self,
+    driver_uuid: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+  ) -> Vec<DailyTripSummary> {
+    self.inner.summarise_for_driver(driver_uuid, from, to).await
+  }
+
+  async fn create(
+    &self,
+    create_trip: CreateTrip,
+  ) -> Result<Trip, TripRepositoryError> {
+    self.inner.create(create_trip).await
+  }
+
+  async fn update(
+    &self,
+    trip: Trip,
+    expected_status: TripStatus,
+  ) -> Result<Trip, TripRepositoryError> {
+    let trip = self.inner.update(trip, expected_status).await?;
+    self.trip_events.publish(&trip);
+    Ok(trip)
+  }
+
+  async fn find_estimate(&self, uuid: &str) -> Option<FareEstimate> {
+    self.inner.find_estimate(uuid).await
+  }
+
+  async fn create_estimate(
+    &self,
+    estimate: FareEstimate,
+  ) -> Result<FareEstimate, TripRepositoryError> {
+    self.inner.create_estimate(estimate).await
+  }
+
+  async fn find_share(&self, token: &str) -> Option<TripShare> {
+    self.inner.find_share(token).await
+  }
+
+  async fn create_share(
+    &self,
+    share: TripShare,
+  ) -> Result<TripShare, TripRepositoryError> {
+    self.inner.create_share(share).await
+  }
+
+  async fn revoke_share(
+    &self,
+    token: &str,
+    at: DateTime<Utc>,
+  ) -> Result<(), TripRepositoryError> {
+    self.inner.revoke_share(token, at).await
+  }
+}
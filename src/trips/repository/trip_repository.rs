@@ -1,11 +1,26 @@
 use std::sync::Arc;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
 use sqlx::Row;
-use sqlx::{postgres::PgRow, Pool, Postgres};
+use sqlx::{postgres::PgRow, Pool, Postgres, Transaction};
 use thiserror::Error;
 
+use crate::fares::model::fare_breakdown::{FareBreakdown, FareItem};
+use crate::fares::model::fare_range::FareRange;
 use crate::shared::database::Database;
+use crate::shared::geo_point::{GeoPoint, ServiceArea};
+use crate::shared::ride_requirements::RideRequirements;
+use crate::trips::model::daily_trip_summary::DailyTripSummary;
+use crate::trips::model::fare_estimate::FareEstimate;
+use crate::trips::model::trip_booking::TripBooking;
+use crate::trips::model::trip_cancellation::TripCancellation;
+use crate::trips::model::trip_cursor::TripCursor;
+use crate::trips::model::trip_offer::TripOffer;
+use crate::trips::model::trip_share::TripShare;
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::model::trip_stop::TripStop;
+use crate::trips::model::trip_transition::TripTransition;
 use crate::trips::model::Trip;
 
 #[derive(Debug, Error)]
@@ -16,16 +31,57 @@ pub enum TripRepositoryError {
   #[error("Serialization error: {0}")]
   SerializationError(#[from] serde_json::Error),
 
-  #[error("Other error: {0}")]
-  Other(String),
+  #[error("Trip status changed concurrently, expected {0:?}")]
+  StatusChanged(TripStatus),
+
+  #[error("Trip changed concurrently, expected version {0}")]
+  VersionChanged(i32),
 }
 
 pub trait TripRepository {
   async fn find_one(&self, uuid: &str) -> Option<Trip>;
+  async fn find_by_status(&self, statuses: &[TripStatus]) -> Vec<Trip>;
+  async fn find_for_driver(
+    &self,
+    driver_uuid: &str,
+    statuses: &[TripStatus],
+  ) -> Vec<Trip>;
+  // Trips matching every given filter, ordered by pickup time
+  async fn find_page(&self, query: &TripQuery) -> Vec<Trip>;
+  async fn summarise_for_driver(
+    &self,
+    driver_uuid: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+  ) -> Vec<DailyTripSummary>;
+  // Trips of a recurring booking date that already has one are not
+  // created again, the existing trip is returned instead
   async fn create(
     &self,
     create_trip: CreateTrip,
   ) -> Result<Trip, TripRepositoryError>;
+  // Saves status, driver, stops, offers and new transitions, but only
+  // while the stored status and version are still the expected ones
+  async fn update(
+    &self,
+    trip: Trip,
+    expected_status: TripStatus,
+  ) -> Result<Trip, TripRepositoryError>;
+  async fn find_estimate(&self, uuid: &str) -> Option<FareEstimate>;
+  async fn create_estimate(
+    &self,
+    estimate: FareEstimate,
+  ) -> Result<FareEstimate, TripRepositoryError>;
+  async fn find_share(&self, token: &str) -> Option<TripShare>;
+  async fn create_share(
+    &self,
+    share: TripShare,
+  ) -> Result<TripShare, TripRepositoryError>;
+  async fn revoke_share(
+    &self,
+    token: &str,
+    at: DateTime<Utc>,
+  ) -> Result<(), TripRepositoryError>;
 }
 
 pub struct TripRepositoryImpl {
@@ -40,77 +96,834 @@ impl TripRepositoryImpl {
   }
 }
 
+impl TripRepositoryImpl {
+  async fn load(&self, row: PgRow) -> Result<Trip, TripRepositoryError> {
+    let trip = Trip::try_from(row)?;
+    let transitions = sqlx::query(
+      "SELECT * FROM trip_transitions WHERE trip_uuid = $1 ORDER BY position",
+    )
+    .bind(&trip.uuid)
+    .fetch_all(&*self.pool)
+    .await?
+    .into_iter()
+    .map(TripTransition::try_from)
+    .collect::<Result<Vec<TripTransition>, TripRepositoryError>>()?;
+    let offers = sqlx::query(
+      "SELECT * FROM trip_offers WHERE trip_uuid = $1 ORDER BY offered_at",
+    )
+    .bind(&trip.uuid)
+    .fetch_all(&*self.pool)
+    .await?
+    .into_iter()
+    .map(TripOffer::try_from)
+    .collect::<Result<Vec<TripOffer>, TripRepositoryError>>()?;
+    let stops = sqlx::query(
+      "SELECT * FROM trip_stops WHERE trip_uuid = $1 ORDER BY position",
+    )
+    .bind(&trip.uuid)
+    .fetch_all(&*self.pool)
+    .await?
+    .into_iter()
+    .map(TripStop::from)
+    .collect();
+    let fare = self.load_fare(&trip.uuid).await?;
+    let cancellation =
+      sqlx::query("SELECT * FROM trip_cancellations WHERE trip_uuid = $1")
+        .bind(&trip.uuid)
+        .fetch_optional(&*self.pool)
+        .await?
+        .map(TripCancellation::try_from)
+        .transpose()?;
+    Ok(Trip {
+      stops,
+      transitions,
+      offers,
+      fare,
+      cancellation,
+      ..trip
+    })
+  }
+
+  async fn load_fare(
+    &self,
+    trip_uuid: &str,
+  ) -> Result<Option<FareBreakdown>, TripRepositoryError> {
+    let Some(row) =
+      sqlx::query("SELECT * FROM trip_fares WHERE trip_uuid = $1")
+        .bind(trip_uuid)
+        .fetch_optional(&*self.pool)
+        .await?
+    else {
+      return Ok(None);
+    };
+    let items = sqlx::query(
+      "SELECT * FROM trip_fare_items WHERE trip_uuid = $1 ORDER BY position",
+    )
+    .bind(trip_uuid)
+    .fetch_all(&*self.pool)
+    .await?
+    .into_iter()
+    .map(|row| FareItem {
+      code: row.get("code"),
+      amount_cents: row.get("amount_cents"),
+    })
+    .collect();
+    Ok(Some(FareBreakdown {
+      tariff_version: row.get("tariff_version"),
+      rate: serde_json::from_str(row.get("rate"))?,
+      items,
+      total_cents: row.get("total_cents"),
+      distance_m: row.get("distance_m"),
+    }))
+  }
+
+  async fn load_all(&self, rows: Vec<PgRow>) -> Vec<Trip> {
+    let mut trips = Vec::with_capacity(rows.len());
+    for row in rows {
+      if let Ok(trip) = self.load(row).await {
+        trips.push(trip);
+      }
+    }
+    trips
+  }
+}
+
+// Stops are added and removed during the trip, they are saved as a whole
+async fn save_stops(
+  transaction: &mut Transaction<'_, Postgres>,
+  trip_uuid: &str,
+  stops: &[TripStop],
+) -> Result<(), TripRepositoryError> {
+  sqlx::query("DELETE FROM trip_stops WHERE trip_uuid = $1")
+    .bind(trip_uuid)
+    .execute(&mut **transaction)
+    .await?;
+  for (position, stop) in stops.iter().enumerate() {
+    let query = r#"
+      INSERT INTO trip_stops (
+        uuid, trip_uuid, position, lat, lng, arrived_at, departed_at
+      )
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#;
+    sqlx::query(query)
+      .bind(&stop.uuid)
+      .bind(trip_uuid)
+      .bind(position as i32)
+      .bind(stop.coords.lat)
+      .bind(stop.coords.lng)
+      .bind(stop.arrived_at)
+      .bind(stop.departed_at)
+      .execute(&mut **transaction)
+      .await?;
+  }
+  Ok(())
+}
+
+fn serialize_statuses(
+  statuses: &[TripStatus],
+) -> Result<Vec<String>, serde_json::Error> {
+  statuses.iter().map(serde_json::to_string).collect()
+}
+
 impl TripRepository for TripRepositoryImpl {
   async fn find_one(&self, uuid: &str) -> Option<Trip> {
-    let rows = sqlx::query("SELECT * FROM trips WHERE uuid = ? LIMIT 1")
+    let row = sqlx::query("SELECT * FROM trips WHERE uuid = $1 LIMIT 1")
       .bind(uuid)
-      .map(|row: PgRow| Trip::from(row))
       .fetch_one(&*self.pool)
-      .await;
-    rows.ok()
+      .await
+      .ok()?;
+    self.load(row).await.ok()
+  }
+
+  async fn find_by_status(&self, statuses: &[TripStatus]) -> Vec<Trip> {
+    let Ok(statuses) = serialize_statuses(statuses) else {
+      return Vec::new();
+    };
+    let rows = sqlx::query(
+      "SELECT * FROM trips WHERE status = ANY($1) ORDER BY created_at",
+    )
+    .bind(statuses)
+    .fetch_all(&*self.pool)
+    .await
+    .unwrap_or_default();
+    self.load_all(rows).await
+  }
+
+  async fn find_for_driver(
+    &self,
+    driver_uuid: &str,
+    statuses: &[TripStatus],
+  ) -> Vec<Trip> {
+    let Ok(statuses) = serialize_statuses(statuses) else {
+      return Vec::new();
+    };
+    let query = r#"
+      SELECT * FROM trips WHERE driver_uuid = $1 AND status = ANY($2)
+      ORDER BY created_at
+    "#;
+    let rows = sqlx::query(query)
+      .bind(driver_uuid)
+      .bind(statuses)
+      .fetch_all(&*self.pool)
+      .await
+      .unwrap_or_default();
+    self.load_all(rows).await
+  }
+
+  async fn find_page(&self, query: &TripQuery) -> Vec<Trip> {
+    let Ok(statuses) = serialize_statuses(&query.statuses) else {
+      return Vec::new();
+    };
+    let (direction, comparison) = match query.order {
+      SortOrder::Asc => ("ASC", ">"),
+      SortOrder::Desc => ("DESC", "<"),
+    };
+    let sql = format!(
+      r#"
+      SELECT * FROM trips
+      WHERE ($1::TEXT IS NULL OR consumer_uuid = $1 OR driver_uuid = $1)
+        AND ($8::TEXT IS NULL OR consumer_uuid = $8)
+        AND ($9::TEXT IS NULL OR driver_uuid = $9)
+        AND ($10::TEXT IS NULL OR vehicle_plate = $10)
+        AND (
+          $11::DOUBLE PRECISION IS NULL
+          OR (start_lat BETWEEN $11 AND $13 AND start_lng BETWEEN $12 AND $14)
+        )
+        AND (cardinality($2::TEXT[]) = 0 OR status = ANY($2))
+        AND ($3::TIMESTAMPTZ IS NULL OR COALESCE(pickup_at, created_at) >= $3)
+        AND ($4::TIMESTAMPTZ IS NULL OR COALESCE(pickup_at, created_at) < $4)
+        AND (
+          $5::TIMESTAMPTZ IS NULL
+          OR (COALESCE(pickup_at, created_at), uuid) {comparison} ($5, $6)
+        )
+      ORDER BY COALESCE(pickup_at, created_at) {direction}, uuid {direction}
+      LIMIT $7
+    "#
+    );
+    let rows = sqlx::query(&sql)
+      .bind(&query.participant_uuid)
+      .bind(statuses)
+      .bind(query.from)
+      .bind(query.to)
+      .bind(query.after.as_ref().map(|cursor| cursor.pickup_at))
+      .bind(query.after.as_ref().map(|cursor| cursor.uuid.clone()))
+      .bind(query.limit)
+      .bind(&query.consumer_uuid)
+      .bind(&query.driver_uuid)
+      .bind(&query.vehicle_plate)
+      .bind(query.area.map(|area| area.min_lat))
+      .bind(query.area.map(|area| area.min_lng))
+      .bind(query.area.map(|area| area.max_lat))
+      .bind(query.area.map(|area| area.max_lng))
+      .fetch_all(&*self.pool)
+      .await
+      .unwrap_or_default();
+    self.load_all(rows).await
+  }
+
+  async fn summarise_for_driver(
+    &self,
+    driver_uuid: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+  ) -> Vec<DailyTripSummary> {
+    let (Ok(completed), Ok(cancelled)) = (
+      serde_json::to_string(&TripStatus::Completed),
+      serde_json::to_string(&TripStatus::Cancelled),
+    ) else {
+      return Vec::new();
+    };
+    let query = r#"
+      SELECT
+        (COALESCE(t.pickup_at, t.created_at) AT TIME ZONE 'Europe/Dublin')::DATE
+          AS date,
+        COUNT(*) FILTER (WHERE t.status = $4) AS completed_trips,
+        COUNT(*) FILTER (WHERE t.status = $5) AS cancelled_trips,
+        COALESCE(SUM(f.total_cents) FILTER (WHERE t.status = $4), 0)::BIGINT
+          AS fare_total_cents
+      FROM trips t
+      LEFT JOIN trip_fares f ON f.trip_uuid = t.uuid
+      WHERE t.driver_uuid = $1
+        AND (COALESCE(t.pickup_at, t.created_at) AT TIME ZONE 'Europe/Dublin')::DATE
+          BETWEEN $2 AND $3
+      GROUP BY 1
+      ORDER BY 1
+    "#;
+    sqlx::query(query)
+      .bind(driver_uuid)
+      .bind(from)
+      .bind(to)
+      .bind(completed)
+      .bind(cancelled)
+      .fetch_all(&*self.pool)
+      .await
+      .unwrap_or_default()
+      .into_iter()
+      .map(|row| DailyTripSummary {
+        date: row.get("date"),
+        completed_trips: row.get("completed_trips"),
+        cancelled_trips: row.get("cancelled_trips"),
+        fare_total_cents: row.get("fare_total_cents"),
+      })
+      .collect()
   }
 
   async fn create(
     &self,
     create_trip: CreateTrip,
   ) -> Result<Trip, TripRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
     let query = r#"
-      INSERT INTO users (uuid, start_coords, end_coords, driver_uuid, consumer_uuid)
-      VALUES ($1, $2, $3)
-      RETURNING uuid, created_at, updated_at, start_coords, end_coords, driver_uuid, consumer_uuid
+      INSERT INTO trips (
+        uuid, start_lat, start_lng, end_lat, end_lng, driver_uuid,
+        consumer_uuid, wheelchair_accessible, assistance_dog, child_seat,
+        preferred_language, fare_estimate_uuid, status, pickup_at, passengers,
+        booking_uuid, booking_occurs_on
+      )
+      VALUES (
+        $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+        $17
+      )
+      ON CONFLICT (booking_uuid, booking_occurs_on)
+        WHERE status <> '"cancelled"' DO NOTHING
+      RETURNING *
     "#;
-    sqlx::query(query)
+    let booking = create_trip.booking.as_ref();
+    let row = sqlx::query(query)
       .bind(&create_trip.uuid)
-      .bind(&create_trip.start_coords)
-      .bind(&create_trip.end_coords)
+      .bind(create_trip.start_coords.lat)
+      .bind(create_trip.start_coords.lng)
+      .bind(create_trip.end_coords.lat)
+      .bind(create_trip.end_coords.lng)
       .bind(&create_trip.driver_uuid)
       .bind(&create_trip.consumer_uuid)
-      .map(|row: PgRow| Trip::from(row))
+      .bind(create_trip.requirements.wheelchair_accessible)
+      .bind(create_trip.requirements.assistance_dog)
+      .bind(create_trip.requirements.child_seat)
+      .bind(&create_trip.requirements.preferred_language)
+      .bind(&create_trip.fare_estimate_uuid)
+      .bind(serde_json::to_string(&create_trip.initial_status())?)
+      .bind(create_trip.pickup_at)
+      .bind(create_trip.passengers)
+      .bind(booking.map(|booking| &booking.booking_uuid))
+      .bind(booking.map(|booking| booking.occurs_on))
+      .fetch_optional(&mut *transaction)
+      .await?;
+    // Left by an earlier run that could not record it with the series
+    let Some(row) = row else {
+      let query = r#"
+        SELECT * FROM trips
+        WHERE booking_uuid = $1 AND booking_occurs_on = $2
+          AND status <> '"cancelled"'
+      "#;
+      let row = sqlx::query(query)
+        .bind(booking.map(|booking| &booking.booking_uuid))
+        .bind(booking.map(|booking| booking.occurs_on))
+        .fetch_one(&mut *transaction)
+        .await?;
+      return self.load(row).await;
+    };
+    save_stops(&mut transaction, &create_trip.uuid, &create_trip.stops).await?;
+    transaction.commit().await?;
+    Ok(Trip {
+      stops: create_trip.stops,
+      ..Trip::try_from(row)?
+    })
+  }
+
+  async fn update(
+    &self,
+    trip: Trip,
+    expected_status: TripStatus,
+  ) -> Result<Trip, TripRepositoryError> {
+    let mut transaction = self.pool.begin().await?;
+    // Concurrent saves wait here, the later one then sees the new version
+    let stored = sqlx::query(
+      "SELECT status, version FROM trips WHERE uuid = $1 FOR UPDATE",
+    )
+    .bind(&trip.uuid)
+    .fetch_optional(&mut *transaction)
+    .await?;
+    let expected = serde_json::to_string(&expected_status)?;
+    if stored.as_ref().map(|row| row.get::<String, _>("status"))
+      != Some(expected)
+    {
+      return Err(TripRepositoryError::StatusChanged(expected_status));
+    }
+    if stored.map(|row| row.get::<i32, _>("version")) != Some(trip.version) {
+      return Err(TripRepositoryError::VersionChanged(trip.version));
+    }
+    let trip = Trip {
+      version: trip.version + 1,
+      ..trip
+    };
+    let query = r#"
+      UPDATE trips SET
+        status = $2, driver_uuid = $3, updated_at = $4, reminded_at = $5,
+        vehicle_plate = $6, payment_method = $7, version = $8,
+        passengers = $9
+      WHERE uuid = $1
+    "#;
+    sqlx::query(query)
+      .bind(&trip.uuid)
+      .bind(serde_json::to_string(&trip.status)?)
+      .bind(&trip.driver_uuid)
+      .bind(trip.updated_at)
+      .bind(trip.reminded_at)
+      .bind(&trip.vehicle_plate)
+      .bind(
+        trip
+          .payment_method
+          .map(|method| serde_json::to_string(&method))
+          .transpose()?,
+      )
+      .bind(trip.version)
+      .bind(trip.passengers)
+      .execute(&mut *transaction)
+      .await?;
+    // Saves without a status change, e.g. stop edits, add no transition,
+    // the ones already stored are left alone
+    for (position, transition) in trip.transitions.iter().enumerate() {
+      let query = r#"
+        INSERT INTO trip_transitions
+          (trip_uuid, position, from_status, to_status, at, actor_uuid)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (trip_uuid, position) DO NOTHING
+      "#;
+      sqlx::query(query)
+        .bind(&trip.uuid)
+        .bind(position as i32)
+        .bind(serde_json::to_string(&transition.from)?)
+        .bind(serde_json::to_string(&transition.to)?)
+        .bind(transition.at)
+        .bind(&transition.actor_uuid)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    save_stops(&mut transaction, &trip.uuid, &trip.stops).await?;
+    for offer in &trip.offers {
+      let query = r#"
+        INSERT INTO trip_offers (
+          trip_uuid, driver_uuid, offered_at, expires_at, outcome,
+          responded_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (trip_uuid, driver_uuid, offered_at) DO UPDATE SET
+          outcome = EXCLUDED.outcome,
+          responded_at = EXCLUDED.responded_at
+      "#;
+      sqlx::query(query)
+        .bind(&trip.uuid)
+        .bind(&offer.driver_uuid)
+        .bind(offer.offered_at)
+        .bind(offer.expires_at)
+        .bind(serde_json::to_string(&offer.outcome)?)
+        .bind(offer.responded_at)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    // Completed trips are never updated again, the fare is saved once
+    if let Some(fare) = &trip.fare {
+      let query = r#"
+        INSERT INTO trip_fares (
+          trip_uuid, tariff_version, rate, total_cents, distance_m
+        )
+        VALUES ($1, $2, $3, $4, $5)
+      "#;
+      sqlx::query(query)
+        .bind(&trip.uuid)
+        .bind(&fare.tariff_version)
+        .bind(serde_json::to_string(&fare.rate)?)
+        .bind(fare.total_cents)
+        .bind(fare.distance_m)
+        .execute(&mut *transaction)
+        .await?;
+      for (position, item) in fare.items.iter().enumerate() {
+        let query = r#"
+          INSERT INTO trip_fare_items
+            (trip_uuid, position, code, amount_cents)
+          VALUES ($1, $2, $3, $4)
+        "#;
+        sqlx::query(query)
+          .bind(&trip.uuid)
+          .bind(position as i32)
+          .bind(&item.code)
+          .bind(item.amount_cents)
+          .execute(&mut *transaction)
+          .await?;
+      }
+    }
+    // Saved once, together with the transition to cancelled
+    if let Some(cancellation) = &trip.cancellation {
+      let query = r#"
+        INSERT INTO trip_cancellations
+          (trip_uuid, cancelled_by, actor_uuid, reason, note, at, fee_cents)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (trip_uuid) DO NOTHING
+      "#;
+      sqlx::query(query)
+        .bind(&trip.uuid)
+        .bind(serde_json::to_string(&cancellation.cancelled_by)?)
+        .bind(&cancellation.actor_uuid)
+        .bind(serde_json::to_string(&cancellation.reason)?)
+        .bind(&cancellation.note)
+        .bind(cancellation.at)
+        .bind(cancellation.fee_cents)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await?;
+    Ok(trip)
+  }
+
+  async fn find_estimate(&self, uuid: &str) -> Option<FareEstimate> {
+    let row = sqlx::query("SELECT * FROM fare_estimates WHERE uuid = $1")
+      .bind(uuid)
       .fetch_one(&*self.pool)
       .await
-      .map_err(TripRepositoryError::from)
+      .ok()?;
+    FareEstimate::try_from(row).ok()
+  }
+
+  async fn create_estimate(
+    &self,
+    estimate: FareEstimate,
+  ) -> Result<FareEstimate, TripRepositoryError> {
+    let query = r#"
+      INSERT INTO fare_estimates (
+        uuid, consumer_uuid, created_at, start_lat, start_lng, end_lat,
+        end_lng, pickup_at, distance_m, duration_secs, tariff_version, rate,
+        min_cents, max_cents, passengers
+      )
+      VALUES (
+        $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+      )
+    "#;
+    sqlx::query(query)
+      .bind(&estimate.uuid)
+      .bind(&estimate.consumer_uuid)
+      .bind(estimate.created_at)
+      .bind(estimate.start_coords.lat)
+      .bind(estimate.start_coords.lng)
+      .bind(estimate.end_coords.lat)
+      .bind(estimate.end_coords.lng)
+      .bind(estimate.pickup_at)
+      .bind(estimate.distance_m)
+      .bind(estimate.duration_secs)
+      .bind(&estimate.fare.tariff_version)
+      .bind(serde_json::to_string(&estimate.fare.rate)?)
+      .bind(estimate.fare.min_cents)
+      .bind(estimate.fare.max_cents)
+      .bind(estimate.passengers)
+      .execute(&*self.pool)
+      .await?;
+    Ok(estimate)
+  }
+
+  async fn find_share(&self, token: &str) -> Option<TripShare> {
+    let row = sqlx::query("SELECT * FROM trip_shares WHERE token = $1")
+      .bind(token)
+      .fetch_one(&*self.pool)
+      .await
+      .ok()?;
+    Some(TripShare::from(row))
+  }
+
+  async fn create_share(
+    &self,
+    share: TripShare,
+  ) -> Result<TripShare, TripRepositoryError> {
+    let query = r#"
+      INSERT INTO trip_shares (
+        token, trip_uuid, created_at, expires_at, revoked_at
+      )
+      VALUES ($1, $2, $3, $4, $5)
+    "#;
+    sqlx::query(query)
+      .bind(&share.token)
+      .bind(&share.trip_uuid)
+      .bind(share.created_at)
+      .bind(share.expires_at)
+      .bind(share.revoked_at)
+      .execute(&*self.pool)
+      .await?;
+    Ok(share)
+  }
+
+  async fn revoke_share(
+    &self,
+    token: &str,
+    at: DateTime<Utc>,
+  ) -> Result<(), TripRepositoryError> {
+    let query = r#"
+      UPDATE trip_shares SET revoked_at = $2
+      WHERE token = $1 AND revoked_at IS NULL
+    "#;
+    sqlx::query(query)
+      .bind(token)
+      .bind(at)
+      .execute(&*self.pool)
+      .await?;
+    Ok(())
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CreateTrip {
   pub uuid: String,
-  pub start_coords: String,
-  pub end_coords: String,
+  pub start_coords: GeoPoint,
+  pub end_coords: GeoPoint,
+  pub stops: Vec<TripStop>,
   pub driver_uuid: Option<String>,
   pub consumer_uuid: String,
+  pub requirements: RideRequirements,
+  pub passengers: i32,
+  pub fare_estimate_uuid: Option<String>,
+  pub pickup_at: Option<DateTime<Utc>>,
+  pub booking: Option<TripBooking>,
 }
 
-impl From<PgRow> for Trip {
-  fn from(row: PgRow) -> Self {
-    Self {
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub enum SortOrder {
+  #[serde(rename = "asc")]
+  Asc,
+  #[default]
+  #[serde(rename = "desc")]
+  Desc,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TripQuery {
+  // Matched against both the customer and the driver of the trip
+  pub participant_uuid: Option<String>,
+  pub consumer_uuid: Option<String>,
+  pub driver_uuid: Option<String>,
+  pub vehicle_plate: Option<String>,
+  // Pickup location
+  pub area: Option<ServiceArea>,
+  // Any status when empty
+  pub statuses: Vec<TripStatus>,
+  // Pickup time range, the end is exclusive
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+  pub order: SortOrder,
+  // Only trips after the cursor in the chosen order
+  pub after: Option<TripCursor>,
+  pub limit: i64,
+}
+
+impl CreateTrip {
+  // Pre-booked trips wait for the scheduler, others go to dispatch
+  pub fn initial_status(&self) -> TripStatus {
+    match self.pickup_at {
+      Some(_) => TripStatus::Scheduled,
+      None => TripStatus::Requested,
+    }
+  }
+}
+
+impl TryFrom<PgRow> for Trip {
+  type Error = TripRepositoryError;
+
+  fn try_from(row: PgRow) -> Result<Self, Self::Error> {
+    Ok(Self {
       uuid: row.get("uuid"),
       created_at: row.get::<DateTime<Utc>, _>("created_at"),
       updated_at: row.get::<DateTime<Utc>, _>("updated_at"),
-      start_coords: row.get("start_coords"),
-      end_coords: row.get("end_coords"),
+      start_coords: GeoPoint {
+        lat: row.get("start_lat"),
+        lng: row.get("start_lng"),
+      },
+      end_coords: GeoPoint {
+        lat: row.get("end_lat"),
+        lng: row.get("end_lng"),
+      },
+      stops: Vec::new(),
       driver_uuid: row.get("driver_uuid"),
       consumer_uuid: row.get("consumer_uuid"),
+      requirements: RideRequirements::from(&row),
+      passengers: row.get("passengers"),
+      status: serde_json::from_str(row.get("status"))?,
+      transitions: Vec::new(),
+      offers: Vec::new(),
+      fare: None,
+      fare_estimate_uuid: row.get("fare_estimate_uuid"),
+      pickup_at: row.get("pickup_at"),
+      booking: row
+        .get::<Option<String>, _>("booking_uuid")
+        .zip(row.get::<Option<NaiveDate>, _>("booking_occurs_on"))
+        .map(|(booking_uuid, occurs_on)| TripBooking {
+          booking_uuid,
+          occurs_on,
+        }),
+      reminded_at: row.get("reminded_at"),
+      cancellation: None,
+      vehicle_plate: row.get("vehicle_plate"),
+      payment_method: row
+        .get::<Option<String>, _>("payment_method")
+        .map(|method| serde_json::from_str(&method))
+        .transpose()?,
+      version: row.get("version"),
+    })
+  }
+}
+
+impl TryFrom<PgRow> for FareEstimate {
+  type Error = TripRepositoryError;
+
+  fn try_from(row: PgRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      uuid: row.get("uuid"),
+      consumer_uuid: row.get("consumer_uuid"),
+      created_at: row.get::<DateTime<Utc>, _>("created_at"),
+      start_coords: GeoPoint {
+        lat: row.get("start_lat"),
+        lng: row.get("start_lng"),
+      },
+      end_coords: GeoPoint {
+        lat: row.get("end_lat"),
+        lng: row.get("end_lng"),
+      },
+      pickup_at: row.get::<DateTime<Utc>, _>("pickup_at"),
+      distance_m: row.get("distance_m"),
+      duration_secs: row.get("duration_secs"),
+      passengers: row.get("passengers"),
+      fare: FareRange {
+        tariff_version: row.get("tariff_version"),
+        rate: serde_json::from_str(row.get("rate"))?,
+        min_cents: row.get("min_cents"),
+        max_cents: row.get("max_cents"),
+      },
+    })
+  }
+}
+
+impl TryFrom<PgRow> for TripCancellation {
+  type Error = TripRepositoryError;
+
+  fn try_from(row: PgRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      cancelled_by: serde_json::from_str(row.get("cancelled_by"))?,
+      actor_uuid: row.get("actor_uuid"),
+      reason: serde_json::from_str(row.get("reason"))?,
+      note: row.get("note"),
+      at: row.get::<DateTime<Utc>, _>("at"),
+      fee_cents: row.get("fee_cents"),
+    })
+  }
+}
+
+impl From<PgRow> for TripStop {
+  fn from(row: PgRow) -> Self {
+    Self {
+      uuid: row.get("uuid"),
+      coords: GeoPoint {
+        lat: row.get("lat"),
+        lng: row.get("lng"),
+      },
+      arrived_at: row.get("arrived_at"),
+      departed_at: row.get("departed_at"),
+    }
+  }
+}
+
+impl TryFrom<PgRow> for TripOffer {
+  type Error = TripRepositoryError;
+
+  fn try_from(row: PgRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      driver_uuid: row.get("driver_uuid"),
+      offered_at: row.get::<DateTime<Utc>, _>("offered_at"),
+      expires_at: row.get::<DateTime<Utc>, _>("expires_at"),
+      outcome: serde_json::from_str(row.get("outcome"))?,
+      responded_at: row.get("responded_at"),
+    })
+  }
+}
+
+impl TryFrom<PgRow> for TripTransition {
+  type Error = TripRepositoryError;
+
+  fn try_from(row: PgRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      from: serde_json::from_str(row.get("from_status"))?,
+      to: serde_json::from_str(row.get("to_status"))?,
+      at: row.get::<DateTime<Utc>, _>("at"),
+      actor_uuid: row.get("actor_uuid"),
+    })
+  }
+}
+
+impl From<PgRow> for TripShare {
+  fn from(row: PgRow) -> Self {
+    Self {
+      token: row.get("token"),
+      trip_uuid: row.get("trip_uuid"),
+      created_at: row.get("created_at"),
+      expires_at: row.get("expires_at"),
+      revoked_at: row.get("revoked_at"),
     }
   }
 }
 
 #[cfg(test)]
 pub mod tests {
+  use crate::trips::model::daily_trip_summary::DailyTripSummary;
+  use crate::trips::model::fare_estimate::FareEstimate;
+  use crate::trips::model::trip_share::TripShare;
+  use crate::trips::model::trip_status::TripStatus;
   use crate::trips::model::Trip;
-  use chrono::Utc;
+  use chrono::{DateTime, NaiveDate, Utc};
+  use chrono_tz::Europe::Dublin;
+  use std::collections::BTreeMap;
   use std::sync::RwLock;
 
-  use super::{CreateTrip, TripRepository, TripRepositoryError};
+  use super::{
+    CreateTrip, SortOrder, TripQuery, TripRepository, TripRepositoryError,
+  };
+
+  fn matches(query: &TripQuery, trip: &Trip) -> bool {
+    let pickup_at = trip.pickup_time();
+    let equals = |filter: &Option<String>, value: Option<&str>| {
+      filter.as_deref().is_none_or(|filter| value == Some(filter))
+    };
+    let is_participant = query.participant_uuid.as_deref().is_none_or(|uuid| {
+      trip.consumer_uuid == uuid || trip.is_assigned_to(uuid)
+    });
+    let is_after = query.after.as_ref().is_none_or(|cursor| {
+      // Cursors only keep microseconds, like Postgres
+      let key = (pickup_at.timestamp_micros(), trip.uuid.as_str());
+      let after = (cursor.pickup_at.timestamp_micros(), cursor.uuid.as_str());
+      match query.order {
+        SortOrder::Asc => key > after,
+        SortOrder::Desc => key < after,
+      }
+    });
+    is_participant
+      && is_after
+      && equals(&query.consumer_uuid, Some(&trip.consumer_uuid))
+      && equals(&query.driver_uuid, trip.driver_uuid.as_deref())
+      && equals(&query.vehicle_plate, trip.vehicle_plate.as_deref())
+      && query
+        .area
+        .is_none_or(|area| area.contains(&trip.start_coords))
+      && (query.statuses.is_empty() || query.statuses.contains(&trip.status))
+      && query.from.is_none_or(|from| pickup_at >= from)
+      && query.to.is_none_or(|to| pickup_at < to)
+  }
 
   pub struct InMemoryTripRepository {
     pub trips: RwLock<Vec<Trip>>,
+    pub estimates: RwLock<Vec<FareEstimate>>,
+    pub shares: RwLock<Vec<TripShare>>,
   }
 
   impl InMemoryTripRepository {
     pub fn new() -> Self {
       Self {
         trips: RwLock::new(Vec::new()),
+        estimates: RwLock::new(Vec::new()),
+        shares: RwLock::new(Vec::new()),
       }
     }
   }
@@ -121,22 +934,192 @@ pub mod tests {
       trips.iter().find(|trip| trip.uuid == uuid).cloned()
     }
 
+    async fn find_by_status(&self, statuses: &[TripStatus]) -> Vec<Trip> {
+      let trips = self.trips.read().unwrap(); // Acquire read lock
+      trips
+        .iter()
+        .filter(|trip| statuses.contains(&trip.status))
+        .cloned()
+        .collect()
+    }
+
+    async fn find_for_driver(
+      &self,
+      driver_uuid: &str,
+      statuses: &[TripStatus],
+    ) -> Vec<Trip> {
+      let trips = self.trips.read().unwrap(); // Acquire read lock
+      trips
+        .iter()
+        .filter(|trip| {
+          trip.is_assigned_to(driver_uuid) && statuses.contains(&trip.status)
+        })
+        .cloned()
+        .collect()
+    }
+
+    async fn find_page(&self, query: &TripQuery) -> Vec<Trip> {
+      let trips = self.trips.read().unwrap(); // Acquire read lock
+      let mut page: Vec<Trip> = trips
+        .iter()
+        .filter(|trip| matches(query, trip))
+        .cloned()
+        .collect();
+      page.sort_by(|a, b| {
+        let ordering =
+          (a.pickup_time(), &a.uuid).cmp(&(b.pickup_time(), &b.uuid));
+        match query.order {
+          SortOrder::Asc => ordering,
+          SortOrder::Desc => ordering.reverse(),
+        }
+      });
+      page.truncate(query.limit.max(0) as usize);
+      page
+    }
+
+    async fn summarise_for_driver(
+      &self,
+      driver_uuid: &str,
+      from: NaiveDate,
+      to: NaiveDate,
+    ) -> Vec<DailyTripSummary> {
+      let trips = self.trips.read().unwrap(); // Acquire read lock
+      let mut days: BTreeMap<NaiveDate, DailyTripSummary> = BTreeMap::new();
+      for trip in trips.iter().filter(|trip| trip.is_assigned_to(driver_uuid)) {
+        let date = trip.pickup_time().with_timezone(&Dublin).date_naive();
+        if date < from || date > to {
+          continue;
+        }
+        let day = days.entry(date).or_insert(DailyTripSummary {
+          date,
+          completed_trips: 0,
+          cancelled_trips: 0,
+          fare_total_cents: 0,
+        });
+        match trip.status {
+          TripStatus::Completed => {
+            day.completed_trips += 1;
+            day.fare_total_cents +=
+              trip.fare.as_ref().map_or(0, |fare| fare.total_cents);
+          }
+          TripStatus::Cancelled => day.cancelled_trips += 1,
+          _ => {}
+        }
+      }
+      days.into_values().collect()
+    }
+
     async fn create(
       &self,
       create_trip: CreateTrip,
     ) -> Result<Trip, TripRepositoryError> {
       let mut trips = self.trips.write().unwrap(); // Acquire write lock
+      if let Some(trip) = trips.iter().find(|trip| {
+        create_trip.booking.is_some()
+          && trip.booking == create_trip.booking
+          && trip.status != TripStatus::Cancelled
+      }) {
+        return Ok(trip.clone());
+      }
+      let status = create_trip.initial_status();
       let trip = Trip {
         uuid: create_trip.uuid,
         created_at: Utc::now(),
         updated_at: Utc::now(),
-        start_coords: String::from(""),
-        end_coords: String::from(""),
-        driver_uuid: None,
-        consumer_uuid: String::from("")
+        start_coords: create_trip.start_coords,
+        end_coords: create_trip.end_coords,
+        stops: create_trip.stops,
+        driver_uuid: create_trip.driver_uuid,
+        consumer_uuid: create_trip.consumer_uuid,
+        requirements: create_trip.requirements,
+        passengers: create_trip.passengers,
+        status,
+        transitions: Vec::new(),
+        offers: Vec::new(),
+        fare: None,
+        fare_estimate_uuid: create_trip.fare_estimate_uuid,
+        pickup_at: create_trip.pickup_at,
+        booking: create_trip.booking,
+        reminded_at: None,
+        cancellation: None,
+        vehicle_plate: None,
+        payment_method: None,
+        version: 0,
       };
       trips.push(trip.clone());
       Ok(trip)
     }
+
+    async fn update(
+      &self,
+      trip: Trip,
+      expected_status: TripStatus,
+    ) -> Result<Trip, TripRepositoryError> {
+      let mut trips = self.trips.write().unwrap(); // Acquire write lock
+      let stored = trips
+        .iter_mut()
+        .find(|stored| stored.uuid == trip.uuid)
+        .filter(|stored| stored.status == expected_status)
+        .ok_or(TripRepositoryError::StatusChanged(expected_status))?;
+      if stored.version != trip.version {
+        return Err(TripRepositoryError::VersionChanged(trip.version));
+      }
+      // Stored transitions are kept like the keyed rows in Postgres
+      let mut transitions = stored.transitions.clone();
+      transitions
+        .extend(trip.transitions.iter().skip(transitions.len()).cloned());
+      *stored = Trip {
+        transitions,
+        version: trip.version + 1,
+        ..trip
+      };
+      Ok(stored.clone())
+    }
+
+    async fn find_estimate(&self, uuid: &str) -> Option<FareEstimate> {
+      let estimates = self.estimates.read().unwrap(); // Acquire read lock
+      estimates
+        .iter()
+        .find(|estimate| estimate.uuid == uuid)
+        .cloned()
+    }
+
+    async fn create_estimate(
+      &self,
+      estimate: FareEstimate,
+    ) -> Result<FareEstimate, TripRepositoryError> {
+      let mut estimates = self.estimates.write().unwrap(); // Acquire write lock
+      estimates.push(estimate.clone());
+      Ok(estimate)
+    }
+
+    async fn find_share(&self, token: &str) -> Option<TripShare> {
+      let shares = self.shares.read().unwrap(); // Acquire read lock
+      shares.iter().find(|share| share.token == token).cloned()
+    }
+
+    async fn create_share(
+      &self,
+      share: TripShare,
+    ) -> Result<TripShare, TripRepositoryError> {
+      let mut shares = self.shares.write().unwrap(); // Acquire write lock
+      shares.push(share.clone());
+      Ok(share)
+    }
+
+    async fn revoke_share(
+      &self,
+      token: &str,
+      at: DateTime<Utc>,
+    ) -> Result<(), TripRepositoryError> {
+      let mut shares = self.shares.write().unwrap(); // Acquire write lock
+      if let Some(share) = shares
+        .iter_mut()
+        .find(|share| share.token == token && share.revoked_at.is_none())
+      {
+        share.revoked_at = Some(at);
+      }
+      Ok(())
+    }
   }
 }
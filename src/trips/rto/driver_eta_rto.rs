@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum EtaTarget {
+  #[serde(rename = "pickup")]
+  Pickup,
+  #[serde(rename = "destination")]
+  Destination,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DriverEtaRto {
+  pub target: EtaTarget,
+  #[serde(rename = "distanceKm")]
+  pub distance_km: f64,
+  #[serde(rename = "etaSecs")]
+  pub eta_secs: i64,
+  // Time of the driver position the estimate is based on
+  #[serde(rename = "recordedAt")]
+  pub recorded_at: DateTime<Utc>,
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EligibleDriverRto {
+  #[serde(rename = "driverUuid")]
+  pub driver_uuid: String,
+  pub plate: String,
+}
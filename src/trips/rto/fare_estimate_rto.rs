@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::fares::model::rate_period::RatePeriod;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FareEstimateRto {
+  pub uuid: String,
+  #[serde(rename = "pickupAt")]
+  pub pickup_at: DateTime<Utc>,
+  #[serde(rename = "distanceM")]
+  pub distance_m: i64,
+  #[serde(rename = "durationSecs")]
+  pub duration_secs: i64,
+  #[serde(rename = "tariffVersion")]
+  pub tariff_version: String,
+  pub rate: RatePeriod,
+  pub currency: String,
+  #[serde(rename = "minCents")]
+  pub min_cents: i64,
+  #[serde(rename = "maxCents")]
+  pub max_cents: i64,
+}
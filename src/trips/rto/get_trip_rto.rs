@@ -1,5 +1,40 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::fares::rto::fare_breakdown_rto::FareBreakdownRto;
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::ride_requirements::RideRequirements;
+use crate::trips::model::payment_method::PaymentMethod;
+use crate::trips::model::trip_cancellation::{CancellationReason, CancelledBy};
+use crate::trips::model::trip_status::TripStatus;
+use crate::trips::rto::trip_stop_rto::TripStopRto;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripTransitionRto {
+  pub from: TripStatus,
+  pub to: TripStatus,
+  pub at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "actorUuid")]
+  pub actor_uuid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripCancellationRto {
+  #[serde(rename = "cancelledBy")]
+  pub cancelled_by: CancelledBy,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "actorUuid")]
+  pub actor_uuid: Option<String>,
+  pub reason: CancellationReason,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub note: Option<String>,
+  pub at: DateTime<Utc>,
+  pub currency: String,
+  #[serde(rename = "feeCents")]
+  pub fee_cents: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetTripRto {
   pub uuid: String,
@@ -7,5 +42,34 @@ pub struct GetTripRto {
   #[serde(rename = "driverUuid")]
   pub driver_uuid: Option<String>,
   #[serde(rename = "consumerUuid")]
-  pub consumer_uuid: String
+  pub consumer_uuid: String,
+  #[serde(rename = "startCoords")]
+  pub start_coords: GeoPoint,
+  #[serde(rename = "endCoords")]
+  pub end_coords: GeoPoint,
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub stops: Vec<TripStopRto>,
+  pub requirements: RideRequirements,
+  pub passengers: i32,
+  pub status: TripStatus,
+  #[serde(rename = "requestedAt")]
+  pub requested_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "pickupAt")]
+  pub pickup_at: Option<DateTime<Utc>>,
+  pub transitions: Vec<TripTransitionRto>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub fare: Option<FareBreakdownRto>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cancellation: Option<TripCancellationRto>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "vehiclePlate")]
+  pub vehicle_plate: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "paymentMethod")]
+  pub payment_method: Option<PaymentMethod>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "fareEstimateUuid")]
+  pub fare_estimate_uuid: Option<String>,
 }
@@ -1 +1,10 @@
+pub mod driver_eta_rto;
+pub mod eligible_driver_rto;
+pub mod fare_estimate_rto;
 pub mod get_trip_rto;
+pub mod shared_trip_rto;
+pub mod trip_event_rto;
+pub mod trip_page_rto;
+pub mod trip_share_rto;
+pub mod trip_stop_rto;
+pub mod trip_summary_rto;
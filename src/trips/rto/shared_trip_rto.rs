@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::driver_eta_rto::DriverEtaRto;
+use crate::shared::geo_point::GeoPoint;
+use crate::trips::model::trip_status::TripStatus;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SharedDriverPositionRto {
+  pub coords: GeoPoint,
+  #[serde(rename = "recordedAt")]
+  pub recorded_at: DateTime<Utc>,
+}
+
+// Public view of a shared trip, without the customer or the addresses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedTripRto {
+  pub status: TripStatus,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "driverFirstName")]
+  pub driver_first_name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "vehiclePlate")]
+  pub vehicle_plate: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "driverPosition")]
+  pub driver_position: Option<SharedDriverPositionRto>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub eta: Option<DriverEtaRto>,
+  #[serde(rename = "expiresAt")]
+  pub expires_at: DateTime<Utc>,
+}
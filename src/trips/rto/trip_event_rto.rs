@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+use crate::trips::model::trip_status::TripStatus;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum TripEventRto {
+  #[serde(rename = "status")]
+  Status {
+    id: u64,
+    status: TripStatus,
+    at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "driverUuid")]
+    driver_uuid: Option<String>,
+  },
+  #[serde(rename = "driverPosition")]
+  DriverPosition {
+    coords: GeoPoint,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heading: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speed: Option<f64>,
+    #[serde(rename = "recordedAt")]
+    recorded_at: DateTime<Utc>,
+  },
+}
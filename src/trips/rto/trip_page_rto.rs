@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use super::get_trip_rto::GetTripRto;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripPageRto {
+  pub trips: Vec<GetTripRto>,
+  // Missing on the last page
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "nextCursor")]
+  pub next_cursor: Option<String>,
+}
@@ -0,0 +1,11 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripShareRto {
+  pub token: String,
+  // Path of the public view, for the app to prefix with its host
+  pub url: String,
+  #[serde(rename = "expiresAt")]
+  pub expires_at: DateTime<Utc>,
+}
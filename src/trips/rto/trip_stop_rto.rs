@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripStopRto {
+  pub uuid: String,
+  pub coords: GeoPoint,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "arrivedAt")]
+  pub arrived_at: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "departedAt")]
+  pub departed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,26 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyTripSummaryRto {
+  pub date: NaiveDate,
+  #[serde(rename = "completedTrips")]
+  pub completed_trips: i64,
+  #[serde(rename = "cancelledTrips")]
+  pub cancelled_trips: i64,
+  #[serde(rename = "fareTotalCents")]
+  pub fare_total_cents: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripSummaryRto {
+  pub from: NaiveDate,
+  pub to: NaiveDate,
+  pub currency: String,
+  #[serde(rename = "completedTrips")]
+  pub completed_trips: i64,
+  #[serde(rename = "fareTotalCents")]
+  pub fare_total_cents: i64,
+  // Only days with trips are listed
+  pub days: Vec<DailyTripSummaryRto>,
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::Stream;
+
+use super::model::trip_event::TripEvent;
+use super::model::trip_status::TripStatus;
+use super::model::Trip;
+use crate::drivers::model::location_fix::LocationFix;
+
+// Fans trip events out to connected clients. Nothing is kept once the
+// last client of a trip is gone, missed statuses are replayed from the
+// trip itself.
+#[derive(Default)]
+pub struct TripEventHub {
+  channels: RwLock<HashMap<String, TripChannel>>,
+}
+
+#[derive(Default)]
+struct TripChannel {
+  // Number of transitions of the newest version of the trip seen
+  transitions: usize,
+  status: Option<TripStatus>,
+  driver_uuid: Option<String>,
+  subscribers: Vec<UnboundedSender<TripEvent>>,
+}
+
+impl TripChannel {
+  // Trips are published from several places, older versions are ignored
+  fn track(&mut self, trip: &Trip) -> bool {
+    if self.status.is_some() && trip.transitions.len() <= self.transitions {
+      return false;
+    }
+    self.transitions = trip.transitions.len();
+    self.status = Some(trip.status);
+    self.driver_uuid = trip.driver_uuid.clone();
+    true
+  }
+
+  fn send(&mut self, event: &TripEvent) {
+    self
+      .subscribers
+      .retain(|subscriber| subscriber.unbounded_send(event.clone()).is_ok());
+  }
+}
+
+// Events of one trip for one client. Ends with the trip, and leaves the
+// hub when dropped.
+pub struct TripSubscription {
+  receiver: UnboundedReceiver<TripEvent>,
+  trip_events: Arc<TripEventHub>,
+  trip_uuid: String,
+}
+
+impl Stream for TripSubscription {
+  type Item = TripEvent;
+
+  fn poll_next(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<TripEvent>> {
+    Pin::new(&mut self.receiver).poll_next(cx)
+  }
+}
+
+impl Drop for TripSubscription {
+  fn drop(&mut self) {
+    self.receiver.close();
+    self.trip_events.prune(&self.trip_uuid);
+  }
+}
+
+impl TripEventHub {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // Subscribe before loading the trip so no transition falls in between,
+  // then `track` the loaded trip
+  pub fn subscribe(self: &Arc<Self>, trip_uuid: &str) -> TripSubscription {
+    let mut channels = self.channels.write().unwrap(); // Acquire write lock
+    let channel = channels.entry(String::from(trip_uuid)).or_default();
+    channel
+      .subscribers
+      .retain(|subscriber| !subscriber.is_closed());
+    let (sender, receiver) = unbounded();
+    channel.subscribers.push(sender);
+    TripSubscription {
+      receiver,
+      trip_events: Arc::clone(self),
+      trip_uuid: String::from(trip_uuid),
+    }
+  }
+
+  fn prune(&self, trip_uuid: &str) {
+    let mut channels = self.channels.write().unwrap(); // Acquire write lock
+    let Some(channel) = channels.get_mut(trip_uuid) else {
+      return;
+    };
+    channel
+      .subscribers
+      .retain(|subscriber| !subscriber.is_closed());
+    if channel.subscribers.is_empty() {
+      channels.remove(trip_uuid);
+    }
+  }
+
+  pub fn track(&self, trip: &Trip) {
+    let mut channels = self.channels.write().unwrap(); // Acquire write lock
+    if let Some(channel) = channels.get_mut(&trip.uuid) {
+      channel.track(trip);
+    }
+    // Ending the streams tells clients the trip is over
+    if trip.status.is_final() {
+      channels.remove(&trip.uuid);
+    }
+  }
+
+  pub fn publish(&self, trip: &Trip) {
+    let mut channels = self.channels.write().unwrap(); // Acquire write lock
+    let Some(channel) = channels.get_mut(&trip.uuid) else {
+      return;
+    };
+    if channel.track(trip) {
+      channel.send(&TripEvent::current_status(trip));
+    }
+    if trip.status.is_final() || channel.subscribers.is_empty() {
+      channels.remove(&trip.uuid);
+    }
+  }
+
+  // Reaches the trips the driver is currently driving
+  pub fn publish_position(&self, fix: &LocationFix) {
+    let mut channels = self.channels.write().unwrap(); // Acquire write lock
+    let event = TripEvent::DriverPosition(fix.clone());
+    for channel in channels.values_mut() {
+      let is_driving = channel.driver_uuid.as_deref()
+        == Some(fix.driver_uuid.as_str())
+        && channel
+          .status
+          .is_some_and(|status| status.shares_driver_position());
+      if is_driving {
+        channel.send(&event);
+      }
+    }
+    channels.retain(|_, channel| !channel.subscribers.is_empty());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{Duration, Utc};
+  use futures::StreamExt;
+
+  use super::*;
+  use crate::shared::geo_point::GeoPoint;
+  use crate::shared::ride_requirements::RideRequirements;
+
+  fn trip() -> Trip {
+    Trip {
+      uuid: String::from("trip"),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      start_coords: GeoPoint {
+        lat: 53.3498,
+        lng: -6.2603,
+      },
+      end_coords: GeoPoint {
+        lat: 53.4264,
+        lng: -6.2499,
+      },
+      stops: Vec::new(),
+      driver_uuid: None,
+      consumer_uuid: String::from("customer"),
+      requirements: RideRequirements::default(),
+      passengers: 1,
+      status: TripStatus::Requested,
+      transitions: Vec::new(),
+      offers: Vec::new(),
+      fare: None,
+      fare_estimate_uuid: None,
+      pickup_at: None,
+      booking: None,
+      reminded_at: None,
+      cancellation: None,
+      vehicle_plate: None,
+      payment_method: None,
+      version: 0,
+    }
+  }
+
+  fn fix() -> LocationFix {
+    LocationFix {
+      driver_uuid: String::from("driver"),
+      coords: GeoPoint {
+        lat: 53.35,
+        lng: -6.26,
+      },
+      accuracy_m: 5.0,
+      heading_deg: None,
+      speed_mps: None,
+      recorded_at: Utc::now(),
+    }
+  }
+
+  #[actix_web::test]
+  async fn test_statuses_and_positions_reach_subscribers() {
+    let hub = Arc::new(TripEventHub::new());
+    let now = Utc::now();
+    let events = hub.subscribe("trip");
+    let trip = trip();
+    let position = fix();
+    hub.track(&trip);
+
+    let offered = trip
+      .clone()
+      .offer("driver", None, now, now + Duration::seconds(20))
+      .unwrap();
+    hub.publish(&offered);
+    // Not shared before the driver accepts
+    hub.publish_position(&position);
+    let accepted = offered
+      .transition(TripStatus::Accepted, Some(String::from("driver")), now)
+      .unwrap();
+    hub.publish(&accepted);
+    // Stale versions of the trip are not published again
+    hub.publish(&trip);
+    hub.publish_position(&position);
+    let completed = accepted
+      .transition(TripStatus::DriverArrived, None, now)
+      .and_then(|trip| trip.transition(TripStatus::InProgress, None, now))
+      .and_then(|trip| trip.transition(TripStatus::Completed, None, now))
+      .unwrap();
+    hub.publish(&completed);
+
+    let events: Vec<TripEvent> = events.collect().await;
+    let ids: Vec<Option<u64>> = events.iter().map(TripEvent::id).collect();
+    assert_eq!(ids, vec![Some(1), Some(2), None, Some(5)]);
+    assert_eq!(events[2], TripEvent::DriverPosition(position));
+  }
+
+  #[test]
+  fn test_channel_dropped_with_last_subscriber() {
+    let hub = Arc::new(TripEventHub::new());
+    let first = hub.subscribe("trip");
+    let second = hub.subscribe("trip");
+    drop(first);
+    assert_eq!(hub.channels.read().unwrap().len(), 1);
+    drop(second);
+    assert!(hub.channels.read().unwrap().is_empty());
+  }
+}
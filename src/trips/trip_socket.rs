@@ -0,0 +1,94 @@
+use std::future::ready;
+use std::pin::pin;
+use std::time::{Duration, Instant};
+
+use actix_ws::{
+  CloseCode, CloseReason, Closed, Message, MessageStream, Session,
+};
+use futures::{stream, StreamExt};
+
+use super::model::trip_event::TripEvent;
+use super::rto::trip_event_rto::TripEventRto;
+use super::trip_event_hub::TripSubscription;
+
+// Clients that stop answering pings are disconnected
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(45);
+
+enum Input {
+  Client(Message),
+  ClientGone,
+  Event(TripEvent),
+  TripEnded,
+  Heartbeat,
+}
+
+// Sends the replayed events, then live ones until the trip ends or the
+// client goes away. Status events already sent are skipped, the replay
+// and the subscription can overlap.
+pub async fn run_trip_socket(
+  mut session: Session,
+  messages: MessageStream,
+  replay: Vec<TripEvent>,
+  events: TripSubscription,
+  mut last_event_id: Option<u64>,
+) {
+  let client = messages
+    .filter_map(|message| ready(message.ok().map(Input::Client)))
+    .chain(stream::once(ready(Input::ClientGone)));
+  let events = stream::iter(replay)
+    .chain(events)
+    .map(Input::Event)
+    .chain(stream::once(ready(Input::TripEnded)));
+  let heartbeats = stream::unfold(
+    actix_web::rt::time::interval(HEARTBEAT_INTERVAL),
+    |mut interval| async move {
+      interval.tick().await;
+      Some((Input::Heartbeat, interval))
+    },
+  );
+  let mut inputs =
+    pin!(stream::select(client, stream::select(events, heartbeats)));
+
+  let mut last_pong = Instant::now();
+  let close_reason = loop {
+    let Some(input) = inputs.next().await else {
+      break None;
+    };
+    let sent = match input {
+      Input::Client(Message::Ping(bytes)) => session.pong(&bytes).await,
+      Input::Client(Message::Pong(_)) => {
+        last_pong = Instant::now();
+        Ok(())
+      }
+      Input::Client(Message::Close(reason)) => break reason,
+      // Clients only listen
+      Input::Client(_) => Ok(()),
+      Input::ClientGone => break None,
+      Input::Event(event) if event.is_after(last_event_id) => {
+        last_event_id = event.id().or(last_event_id);
+        send_event(&mut session, event).await
+      }
+      Input::Event(_) => Ok(()),
+      Input::TripEnded => break Some(CloseReason::from(CloseCode::Normal)),
+      Input::Heartbeat if last_pong.elapsed() > CLIENT_TIMEOUT => {
+        break Some(CloseReason::from(CloseCode::Away))
+      }
+      Input::Heartbeat => session.ping(b"").await,
+    };
+    if sent.is_err() {
+      break None;
+    }
+  };
+  let _ = session.close(close_reason).await;
+}
+
+async fn send_event(
+  session: &mut Session,
+  event: TripEvent,
+) -> Result<(), Closed> {
+  match serde_json::to_string(&TripEventRto::from(event)) {
+    Ok(text) => session.text(text).await,
+    Err(_) => Ok(()),
+  }
+}
@@ -0,0 +1,103 @@
+use std::future::ready;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web_lab::sse;
+use futures::{stream, Stream, StreamExt};
+
+use super::model::trip_event::TripEvent;
+use super::model::trip_status::TripStatus;
+use super::model::Trip;
+use super::rto::driver_eta_rto::{DriverEtaRto, EtaTarget};
+use super::rto::trip_event_rto::TripEventRto;
+use super::trip_event_hub::TripSubscription;
+use crate::drivers::model::location_fix::LocationFix;
+use crate::routing::router::{RouteMetric, Router};
+use crate::shared::geo_point::GeoPoint;
+
+// Comment lines keep proxies from closing an idle stream
+pub const SSE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+struct StreamState {
+  last_event_id: Option<u64>,
+  status: TripStatus,
+  pickup: GeoPoint,
+  destination: GeoPoint,
+  router: Arc<dyn Router>,
+}
+
+// Status changes and, while the driver is on the way, the driver ETA.
+// The stream ends with the trip, as the subscription does.
+pub fn trip_event_stream(
+  trip: &Trip,
+  replay: Vec<TripEvent>,
+  events: TripSubscription,
+  last_event_id: Option<u64>,
+  router: Arc<dyn Router>,
+) -> impl Stream<Item = sse::Event> {
+  let state = StreamState {
+    last_event_id,
+    status: trip.status,
+    pickup: trip.start_coords,
+    destination: trip.end_coords,
+    router,
+  };
+  stream::iter(replay)
+    .chain(events)
+    .scan(state, |state, event| {
+      ready(Some(to_sse_event(state, event)))
+    })
+    .filter_map(ready)
+}
+
+fn to_sse_event(
+  state: &mut StreamState,
+  event: TripEvent,
+) -> Option<sse::Event> {
+  if !event.is_after(state.last_event_id) {
+    return None;
+  }
+  match event {
+    TripEvent::Status { id, status, .. } => {
+      state.last_event_id = Some(id);
+      state.status = status;
+      let data = sse::Data::new_json(TripEventRto::from(event)).ok()?;
+      Some(data.event("status").id(id.to_string()).into())
+    }
+    TripEvent::DriverPosition(fix) => {
+      let eta = driver_eta(
+        &*state.router,
+        state.status,
+        &state.pickup,
+        &state.destination,
+        &fix,
+      )?;
+      let data = sse::Data::new_json(eta).ok()?;
+      Some(data.event("eta").into())
+    }
+  }
+}
+
+// Pickup until the customer is on board, then the destination
+pub fn driver_eta(
+  router: &dyn Router,
+  status: TripStatus,
+  pickup: &GeoPoint,
+  destination: &GeoPoint,
+  fix: &LocationFix,
+) -> Option<DriverEtaRto> {
+  let (target, coords) = match status {
+    TripStatus::Accepted | TripStatus::DriverArrived => {
+      (EtaTarget::Pickup, pickup)
+    }
+    TripStatus::InProgress => (EtaTarget::Destination, destination),
+    _ => return None,
+  };
+  let route = router.route(&fix.coords, coords, RouteMetric::Fastest);
+  Some(DriverEtaRto {
+    target,
+    distance_km: route.distance_m as f64 / 1000.0,
+    eta_secs: route.duration_secs,
+    recorded_at: fix.recorded_at,
+  })
+}
@@ -7,5 +7,8 @@ use crate::shared::role::Role;
 pub struct CreateUserDto {
   #[serde(rename = "userName")]
   pub user_name: String,
+  #[validate(length(min = 1, max = 50))]
+  #[serde(rename = "firstName")]
+  pub first_name: Option<String>,
   pub role: Role,
 }
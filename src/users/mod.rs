@@ -8,7 +8,6 @@ use actix_web::{web, HttpResponse, Responder};
 use dto::create_user_dto::CreateUserDto;
 use dto::get_user_dto::GetUserDto;
 use model::access_token_claims::AccessTokenClaims;
-use nanoid::nanoid;
 use repository::user_repository::UserRepositoryError;
 use rto::get_user_rto::GetUserRto;
 use validator::Validate;
@@ -58,6 +57,7 @@ impl From<User> for GetUserRto {
     Self {
       uuid: user.uuid,
       user_name: user.user_name,
+      first_name: user.first_name,
       role: user.role,
     }
   }
@@ -91,7 +91,18 @@ fn user_created(user: User) -> HttpResponse {
 }
 
 fn failed_create_user(error: UserRepositoryError) -> HttpResponse {
-  HttpResponse::InternalServerError().finish()
+  match error {
+    UserRepositoryError::UserNameTaken(_) => HttpResponse::Conflict()
+      .content_type("application/json")
+      .json(HttpError::with_code(
+        "user_name_taken",
+        "User name already taken",
+      )),
+    UserRepositoryError::DatabaseError(_)
+    | UserRepositoryError::SerializationError(_) => {
+      HttpResponse::InternalServerError().finish()
+    }
+  }
 }
 
 impl From<CreateUserDto> for CreateUser {
@@ -99,6 +110,7 @@ impl From<CreateUserDto> for CreateUser {
     Self {
       uuid: custom_nanoid(),
       user_name: dto.user_name,
+      first_name: dto.first_name,
       role: dto.role,
     }
   }
@@ -117,7 +129,6 @@ mod tests {
 
   use actix_web::{http::StatusCode, HttpRequest};
   use chrono::Utc;
-  use nanoid::nanoid;
   use repository::user_repository::tests::InMemoryUserRepository;
 
   use crate::helpers::tests::{
@@ -136,6 +147,7 @@ mod tests {
       created_at: Utc::now(),
       updated_at: Utc::now(),
       user_name: "John Doe".to_string(),
+      first_name: None,
       role: Role::Admin,
     };
 
@@ -169,6 +181,7 @@ mod tests {
       created_at: Utc::now(),
       updated_at: Utc::now(),
       user_name: "John Doe".to_string(),
+      first_name: None,
       role: Role::Admin,
     };
 
@@ -190,10 +203,47 @@ mod tests {
     assert_eq!(rto.message, "User not found");
   }
 
+  #[actix_web::test]
+  async fn test_create_user_duplicate_user_name_conflict() {
+    let jwt_secret = custom_nanoid();
+
+    let user = User {
+      uuid: custom_nanoid(),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      user_name: "John Doe".to_string(),
+      first_name: None,
+      role: Role::Driver,
+    };
+
+    let request: HttpRequest = http_request(&jwt_secret);
+
+    let responder = create_user(
+      web::Data::from(Arc::new(InMemoryUserRepository {
+        users: RwLock::new(vec![user]),
+      })),
+      web::Json(CreateUserDto {
+        user_name: "JOHN DOE".to_string(),
+        first_name: None,
+        role: Role::Driver,
+      }),
+      create_fake_access_token_claims(),
+    )
+    .await;
+
+    let rto: HttpError =
+      parse_http_response(responder, &request, StatusCode::CONFLICT).await;
+
+    // Assertions
+    assert_eq!(rto.code.as_deref(), Some("user_name_taken"));
+    assert_eq!(rto.message, "User name already taken");
+  }
+
   #[test]
   fn test_create_user_dto_to_create_user() {
     let dto = CreateUserDto {
       user_name: "test_user".to_string(),
+      first_name: None,
       role: Role::Admin,
     };
 
@@ -211,6 +261,7 @@ mod tests {
       created_at: Utc::now(),
       updated_at: Utc::now(),
       user_name: "test_user".to_string(),
+      first_name: None,
       role: Role::Admin,
     };
 
@@ -228,6 +279,7 @@ mod tests {
       created_at: Utc::now(),
       updated_at: Utc::now(),
       user_name: "test_user".to_string(),
+      first_name: None,
       role: Role::Admin,
     };
     let rto: CreatedRto = user.clone().into();
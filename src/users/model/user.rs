@@ -8,5 +8,7 @@ pub struct User {
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
   pub user_name: String,
+  // Shown to customers, e.g. for the driver on a shared trip
+  pub first_name: Option<String>,
   pub role: Role,
 }
@@ -10,14 +10,33 @@ use crate::{shared::database::Database, users::model::user::User};
 
 #[derive(Debug, Error)]
 pub enum UserRepositoryError {
+  #[error("User name already taken: {0}")]
+  UserNameTaken(String),
+
   #[error("Database error: {0}")]
-  DatabaseError(#[from] sqlx::Error),
+  DatabaseError(sqlx::Error),
 
   #[error("Serialization error: {0}")]
   SerializationError(#[from] serde_json::Error),
+}
 
-  #[error("Other error: {0}")]
-  Other(String),
+// Case-insensitive index from migrations/0002_unique_user_names.sql
+const USER_NAME_UNIQUE_INDEX: &str = "users_user_name_unique_idx";
+
+impl UserRepositoryError {
+  // Only a clash on the user name index is caused by the caller,
+  // everything else is an infrastructure failure.
+  fn from_sqlx(error: sqlx::Error, user_name: &str) -> Self {
+    match &error {
+      sqlx::Error::Database(db_error)
+        if db_error.is_unique_violation()
+          && db_error.constraint() == Some(USER_NAME_UNIQUE_INDEX) =>
+      {
+        Self::UserNameTaken(String::from(user_name))
+      }
+      _ => Self::DatabaseError(error),
+    }
+  }
 }
 
 pub trait UserRepository {
@@ -42,7 +61,7 @@ impl UserRepositoryImpl {
 
 impl UserRepository for UserRepositoryImpl {
   async fn find_one(&self, uuid: &str) -> Option<User> {
-    let rows = sqlx::query("SELECT * FROM users WHERE uuid = ? LIMIT 1")
+    let rows = sqlx::query("SELECT * FROM users WHERE uuid = $1 LIMIT 1")
       .bind(uuid)
       .map(|row: PgRow| User::from(row))
       .fetch_one(&*self.pool)
@@ -55,18 +74,21 @@ impl UserRepository for UserRepositoryImpl {
     create_user: CreateUser,
   ) -> Result<User, UserRepositoryError> {
     let query = r#"
-      INSERT INTO users (uuid, user_name, role)
-      VALUES ($1, $2, $3)
-      RETURNING uuid, user_name, role
+      INSERT INTO users (uuid, user_name, first_name, role)
+      VALUES ($1, $2, $3, $4)
+      RETURNING uuid, created_at, updated_at, user_name, first_name, role
     "#;
     sqlx::query(query)
       .bind(&create_user.uuid)
       .bind(&create_user.user_name)
-      .bind(serde_json::to_string(&create_user.role).unwrap())
+      .bind(&create_user.first_name)
+      .bind(serde_json::to_string(&create_user.role)?)
       .map(|row: PgRow| User::from(row))
       .fetch_one(&*self.pool)
       .await
-      .map_err(UserRepositoryError::from)
+      .map_err(|error| {
+        UserRepositoryError::from_sqlx(error, &create_user.user_name)
+      })
   }
 }
 
@@ -74,6 +96,7 @@ impl UserRepository for UserRepositoryImpl {
 pub struct CreateUser {
   pub uuid: String,
   pub user_name: String,
+  pub first_name: Option<String>,
   pub role: Role,
 }
 
@@ -84,6 +107,7 @@ impl From<PgRow> for User {
       created_at: row.get::<DateTime<Utc>, _>("created_at"),
       updated_at: row.get::<DateTime<Utc>, _>("updated_at"),
       user_name: row.get("user_name"),
+      first_name: row.get("first_name"),
       role: serde_json::from_str(row.get("role")).unwrap(),
     }
   }
@@ -94,6 +118,7 @@ pub mod tests {
   use chrono::Utc;
 
   use super::{CreateUser, UserRepository, UserRepositoryError};
+  use crate::shared::role::Role;
   use crate::users::model::user::User;
   use std::sync::RwLock;
 
@@ -120,15 +145,52 @@ pub mod tests {
       user: CreateUser,
     ) -> Result<User, UserRepositoryError> {
       let mut users = self.users.write().unwrap(); // Acquire write lock
+      // Mirror the case-insensitive unique index of the users table
+      let user_name_taken = users.iter().any(|existing| {
+        existing.user_name.to_lowercase() == user.user_name.to_lowercase()
+      });
+      if user_name_taken {
+        return Err(UserRepositoryError::UserNameTaken(user.user_name));
+      }
       let user = User {
         uuid: user.uuid,
         created_at: Utc::now(),
         updated_at: Utc::now(),
         user_name: user.user_name,
+        first_name: user.first_name,
         role: user.role,
       };
       users.push(user.clone());
       Ok(user)
     }
   }
+
+  #[actix_web::test]
+  async fn test_create_rejects_case_insensitive_duplicate() {
+    let repository = InMemoryUserRepository::new();
+    repository
+      .create(CreateUser {
+        uuid: String::from("uuid_1"),
+        user_name: String::from("JohnDoe"),
+        first_name: None,
+        role: Role::Driver,
+      })
+      .await
+      .unwrap();
+
+    let result = repository
+      .create(CreateUser {
+        uuid: String::from("uuid_2"),
+        user_name: String::from("johndoe"),
+        first_name: None,
+        role: Role::Customer,
+      })
+      .await;
+
+    assert!(matches!(
+      result,
+      Err(UserRepositoryError::UserNameTaken(user_name)) if user_name == "johndoe"
+    ));
+    assert_eq!(repository.users.read().unwrap().len(), 1);
+  }
 }
@@ -7,5 +7,8 @@ pub struct GetUserRto {
   pub uuid: String,
   #[serde(rename = "userName")]
   pub user_name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "firstName")]
+  pub first_name: Option<String>,
   pub role: Role,
 }
@@ -0,0 +1,206 @@
+pub mod model;
+pub mod rto;
+
+use std::env;
+use std::fs;
+
+use actix_web::{web, HttpResponse, Responder};
+use model::zone::Zone;
+use rto::zone_rto::ZoneRto;
+use thiserror::Error;
+
+use crate::fares::model::fare_breakdown::FareItem;
+use crate::shared::config::Config;
+use crate::shared::geo_point::GeoPoint;
+use crate::shared::http_error::HttpError;
+use crate::users::model::access_token_claims::AccessTokenClaims;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ZoneError {
+  #[error("Pickups are not allowed in {0}")]
+  NoPickups(String),
+}
+
+// Zones of the organisation, for apps to draw them and warn customers
+// before they book
+pub async fn get_zones(
+  config: web::Data<Config>,
+  _auth: AccessTokenClaims,
+) -> impl Responder {
+  let zones: Vec<ZoneRto> = config
+    .organisation
+    .zones
+    .iter()
+    .cloned()
+    .map(ZoneRto::from)
+    .collect();
+  HttpResponse::Ok()
+    .content_type("application/json")
+    .json(zones)
+}
+
+// JSON array of zones from ORGANISATION_ZONES_PATH, none when unset. A
+// broken file is reported and no zone rules apply.
+pub fn load_zones() -> Vec<Zone> {
+  let Ok(path) = env::var("ORGANISATION_ZONES_PATH") else {
+    return Vec::new();
+  };
+  let zones = fs::read_to_string(&path)
+    .map_err(|error| error.to_string())
+    .and_then(|json| {
+      serde_json::from_str(&json).map_err(|error| error.to_string())
+    });
+  match zones {
+    Ok(zones) => zones,
+    Err(error) => {
+      println!("Cannot load zones from {}: {}", path, error);
+      Vec::new()
+    }
+  }
+}
+
+// Where the driver picks the customer up. Pickups in a zone with a
+// designated point are moved there, then refused if the point is in a
+// zone without pickups.
+pub fn pickup_point(
+  zones: &[Zone],
+  pickup: &GeoPoint,
+) -> Result<GeoPoint, ZoneError> {
+  let point = zones
+    .iter()
+    .filter(|zone| zone.contains(pickup))
+    .find_map(Zone::designated_pickup)
+    .unwrap_or(*pickup);
+  match zones
+    .iter()
+    .find(|zone| zone.forbids_pickups() && zone.contains(&point))
+  {
+    Some(zone) => Err(ZoneError::NoPickups(zone.name.clone())),
+    None => Ok(point),
+  }
+}
+
+// One item per zone the trip starts or ends in, e.g.
+// "dublin_airport_surcharge"
+pub fn zone_surcharges(
+  zones: &[Zone],
+  pickup: &GeoPoint,
+  destination: &GeoPoint,
+) -> Vec<FareItem> {
+  zones
+    .iter()
+    .filter(|zone| zone.contains(pickup) || zone.contains(destination))
+    .filter(|zone| zone.surcharge_cents() > 0)
+    .map(|zone| {
+      FareItem::new(&format!("{}_surcharge", zone.code), zone.surcharge_cents())
+    })
+    .collect()
+}
+
+pub fn pickup_refused(error: ZoneError) -> HttpResponse {
+  HttpResponse::BadRequest()
+    .content_type("application/json")
+    .json(HttpError::with_code(
+      "pickup_not_allowed",
+      &error.to_string(),
+    ))
+}
+
+// Transform Zone domain to RTO
+impl From<Zone> for ZoneRto {
+  fn from(zone: Zone) -> Self {
+    Self {
+      code: zone.code,
+      name: zone.name,
+      polygon: zone.polygon,
+      rules: zone.rules,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use model::zone_rule::ZoneRule;
+
+  use super::*;
+
+  // Axis aligned box around a point, half a side in degrees
+  fn square(
+    code: &str,
+    center: GeoPoint,
+    half: f64,
+    rules: Vec<ZoneRule>,
+  ) -> Zone {
+    Zone {
+      code: String::from(code),
+      name: code.replace('_', " "),
+      polygon: vec![
+        GeoPoint {
+          lat: center.lat - half,
+          lng: center.lng - half,
+        },
+        GeoPoint {
+          lat: center.lat + half,
+          lng: center.lng - half,
+        },
+        GeoPoint {
+          lat: center.lat + half,
+          lng: center.lng + half,
+        },
+        GeoPoint {
+          lat: center.lat - half,
+          lng: center.lng + half,
+        },
+      ],
+      rules,
+    }
+  }
+
+  #[test]
+  fn test_pickup_rules_and_surcharges() {
+    let terminal = GeoPoint {
+      lat: 53.4264,
+      lng: -6.2499,
+    };
+    let rank = GeoPoint {
+      lat: 53.4270,
+      lng: -6.2440,
+    };
+    let temple_bar = GeoPoint {
+      lat: 53.3455,
+      lng: -6.2643,
+    };
+    let zones = vec![
+      square(
+        "dublin_airport",
+        terminal,
+        0.02,
+        vec![
+          ZoneRule::DesignatedPickup { point: rank },
+          ZoneRule::Surcharge { cents: 300 },
+        ],
+      ),
+      square("temple_bar", temple_bar, 0.002, vec![ZoneRule::NoPickups]),
+    ];
+
+    assert_eq!(pickup_point(&zones, &terminal), Ok(rank));
+    assert_eq!(
+      pickup_point(&zones, &temple_bar),
+      Err(ZoneError::NoPickups(String::from("temple bar")))
+    );
+    let elsewhere = GeoPoint {
+      lat: 53.3498,
+      lng: -6.2603,
+    };
+    assert_eq!(pickup_point(&zones, &elsewhere), Ok(elsewhere));
+
+    // Dropping off in a zone without pickups is fine, the airport is
+    // charged both ways
+    let surcharges = zone_surcharges(&zones, &temple_bar, &terminal);
+    assert_eq!(
+      surcharges,
+      vec![FareItem::new("dublin_airport_surcharge", 300)]
+    );
+    assert!(zone_surcharges(&zones, &elsewhere, &temple_bar).is_empty());
+  }
+}
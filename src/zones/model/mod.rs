@@ -0,0 +1,2 @@
+pub mod zone;
+pub mod zone_rule;
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use super::zone_rule::ZoneRule;
+use crate::shared::geo_point::GeoPoint;
+
+// Area with its own pickup rules or charges, e.g. an airport forecourt
+// or the city centre
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Zone {
+  // Short stable code, also names the surcharge on receipts
+  pub code: String,
+  pub name: String,
+  // Vertices in order, the last one connects back to the first
+  pub polygon: Vec<GeoPoint>,
+  #[serde(default)]
+  pub rules: Vec<ZoneRule>,
+}
+
+impl Zone {
+  // Ray casting. Zones are small enough for degrees to be treated as
+  // plane coordinates.
+  pub fn contains(&self, point: &GeoPoint) -> bool {
+    let Some(mut previous) =
+      self.polygon.last().filter(|_| self.polygon.len() >= 3)
+    else {
+      return false;
+    };
+    let mut inside = false;
+    for vertex in &self.polygon {
+      let crosses = (vertex.lat > point.lat) != (previous.lat > point.lat)
+        && point.lng
+          < (previous.lng - vertex.lng) * (point.lat - vertex.lat)
+            / (previous.lat - vertex.lat)
+            + vertex.lng;
+      if crosses {
+        inside = !inside;
+      }
+      previous = vertex;
+    }
+    inside
+  }
+
+  pub fn forbids_pickups(&self) -> bool {
+    self.rules.contains(&ZoneRule::NoPickups)
+  }
+
+  pub fn designated_pickup(&self) -> Option<GeoPoint> {
+    self.rules.iter().find_map(|rule| match rule {
+      ZoneRule::DesignatedPickup { point } => Some(*point),
+      _ => None,
+    })
+  }
+
+  pub fn surcharge_cents(&self) -> i64 {
+    self
+      .rules
+      .iter()
+      .map(|rule| match rule {
+        ZoneRule::Surcharge { cents } => *cents,
+        _ => 0,
+      })
+      .sum()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_contains() {
+    // Grafton Street, a thin concave L shape
+    let zone = Zone {
+      code: String::from("grafton_street"),
+      name: String::from("Grafton Street"),
+      polygon: vec![
+        GeoPoint {
+          lat: 53.3385,
+          lng: -6.2610,
+        },
+        GeoPoint {
+          lat: 53.3440,
+          lng: -6.2610,
+        },
+        GeoPoint {
+          lat: 53.3440,
+          lng: -6.2590,
+        },
+        GeoPoint {
+          lat: 53.3430,
+          lng: -6.2590,
+        },
+        GeoPoint {
+          lat: 53.3430,
+          lng: -6.2600,
+        },
+        GeoPoint {
+          lat: 53.3385,
+          lng: -6.2600,
+        },
+      ],
+      rules: vec![ZoneRule::NoPickups],
+    };
+    assert!(zone.contains(&GeoPoint {
+      lat: 53.3410,
+      lng: -6.2605,
+    }));
+    assert!(zone.contains(&GeoPoint {
+      lat: 53.3435,
+      lng: -6.2595,
+    }));
+    // In the notch of the L
+    assert!(!zone.contains(&GeoPoint {
+      lat: 53.3410,
+      lng: -6.2595,
+    }));
+    assert!(!zone.contains(&GeoPoint {
+      lat: 53.3498,
+      lng: -6.2603,
+    }));
+    assert!(zone.forbids_pickups());
+    assert_eq!(zone.surcharge_cents(), 0);
+  }
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum ZoneRule {
+  // e.g. pedestrianised streets
+  #[serde(rename = "no_pickups")]
+  NoPickups,
+  // Pickups anywhere in the zone are moved to this point, e.g. the
+  // airport taxi rank
+  #[serde(rename = "designated_pickup")]
+  DesignatedPickup { point: GeoPoint },
+  // Added once to the fare of trips starting or ending in the zone
+  #[serde(rename = "surcharge")]
+  Surcharge { cents: i64 },
+}
@@ -0,0 +1 @@
+pub mod zone_rto;
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::geo_point::GeoPoint;
+use crate::zones::model::zone_rule::ZoneRule;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneRto {
+  pub code: String,
+  pub name: String,
+  pub polygon: Vec<GeoPoint>,
+  pub rules: Vec<ZoneRule>,
+}